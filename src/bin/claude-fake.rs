@@ -0,0 +1,8 @@
+//! Fake Claude Code CLI for offline end-to-end tests
+//!
+//! See [`claude_agent_sdk_rs::testing::fake_cli`] for the supported environment variables.
+
+#[tokio::main]
+async fn main() {
+    std::process::exit(claude_agent_sdk_rs::testing::fake_cli::run().await);
+}
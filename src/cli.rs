@@ -0,0 +1,781 @@
+//! Discovery of the Claude Code CLI, version negotiation and capability detection
+//!
+//! [`CliLocator`] finds every `claude` executable the SDK could use, in this order:
+//!
+//! | Source | Where |
+//! |--------|-------|
+//! | [`CliSource::Explicit`] | [`ClaudeAgentOptions::cli_path`] |
+//! | [`CliSource::EnvVar`] | The `CLAUDE_CLI_PATH` environment variable |
+//! | [`CliSource::Path`] | Each directory on `PATH` |
+//! | [`CliSource::CommonLocation`] | Standard install locations (`/usr/local/bin`, `~/.local/bin`, ...) |
+//!
+//! Each candidate is probed with `--version`, and [`CliLocator::locate`] returns the
+//! first one inside the configured version range. Probe results are cached for the
+//! whole process (see [`version_output`]), and [`prewarm`] fills the cache ahead of the
+//! first connect. The version also determines which
+//! optional CLI features are available ([`CliCapabilities`]), so options the CLI cannot
+//! honour are rejected before it is spawned.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use claude_agent_sdk_rs::cli::{CliLocator, CliVersion};
+//!
+//! # fn main() -> claude_agent_sdk_rs::Result<()> {
+//! let locator = CliLocator::builder()
+//!     .min_version(CliVersion::new(2, 0, 12))
+//!     .build();
+//!
+//! for candidate in locator.candidates() {
+//!     println!("{} ({:?}): {:?}", candidate.path.display(), candidate.source, candidate.version);
+//! }
+//!
+//! let cli = locator.locate()?;
+//! println!("using {}", cli.path.display());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use typed_builder::TypedBuilder;
+
+use crate::errors::{ClaudeError, CliNotFoundError, CliVersionError, Result};
+use crate::internal::transport::subprocess::{
+    create_async_hidden_command, create_sync_hidden_command,
+};
+use crate::types::config::ClaudeAgentOptions;
+use crate::version::parse_version;
+
+/// Environment variable naming the CLI executable to use
+pub const CLI_PATH_ENV: &str = "CLAUDE_CLI_PATH";
+
+/// A `major.minor.patch` CLI version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CliVersion {
+    /// Major version
+    pub major: u32,
+    /// Minor version
+    pub minor: u32,
+    /// Patch version
+    pub patch: u32,
+}
+
+impl CliVersion {
+    /// Create a version
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse the first line of `claude --version` output, e.g. `2.0.14 (Claude Code)`
+    pub fn from_version_output(output: &str) -> Option<Self> {
+        output
+            .lines()
+            .next()?
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    }
+}
+
+impl FromStr for CliVersion {
+    type Err = ClaudeError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (major, minor, patch) = parse_version(s.trim()).ok_or_else(|| {
+            ClaudeError::InvalidConfig(format!("invalid CLI version '{}'", s.trim()))
+        })?;
+        Ok(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for CliVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl Serialize for CliVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CliVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Optional CLI features that depend on the CLI version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CliFeature {
+    /// `CLAUDE_CODE_ENABLE_SDK_FILE_CHECKPOINTING` and the `rewind_files` control request
+    FileCheckpointing,
+    /// `--plugin-dir`
+    Plugins,
+    /// `--json-schema` structured output
+    JsonSchema,
+}
+
+impl CliFeature {
+    /// Every feature, in declaration order
+    pub const ALL: [CliFeature; 3] = [
+        CliFeature::FileCheckpointing,
+        CliFeature::Plugins,
+        CliFeature::JsonSchema,
+    ];
+
+    /// First CLI release assumed to support this feature
+    ///
+    /// These bounds are provisional: they have not yet been checked against the Claude
+    /// Code changelog (<https://github.com/anthropics/claude-code/blob/main/CHANGELOG.md>).
+    /// If an older CLI is known to work, set `skip_version_check` (or
+    /// `CLAUDE_AGENT_SDK_SKIP_VERSION_CHECK`) to bypass them.
+    pub const fn min_version(self) -> CliVersion {
+        match self {
+            CliFeature::FileCheckpointing => CliVersion::new(2, 0, 30),
+            CliFeature::Plugins => CliVersion::new(2, 0, 12),
+            CliFeature::JsonSchema => CliVersion::new(2, 0, 45),
+        }
+    }
+
+    /// The option that requires this feature
+    fn option_name(self) -> &'static str {
+        match self {
+            CliFeature::FileCheckpointing => "enable_file_checkpointing",
+            CliFeature::Plugins => "plugins",
+            CliFeature::JsonSchema => "output_format (json_schema)",
+        }
+    }
+}
+
+/// The features available in a given CLI version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CliCapabilities {
+    /// CLI version the capabilities were derived from
+    pub version: CliVersion,
+}
+
+impl CliCapabilities {
+    /// Capabilities of `version`
+    pub fn for_version(version: CliVersion) -> Self {
+        Self { version }
+    }
+
+    /// Whether `feature` is available
+    pub fn supports(&self, feature: CliFeature) -> bool {
+        self.version >= feature.min_version()
+    }
+
+    /// Every available feature
+    pub fn features(&self) -> Vec<CliFeature> {
+        CliFeature::ALL
+            .into_iter()
+            .filter(|feature| self.supports(*feature))
+            .collect()
+    }
+
+    /// Features that `options` use
+    pub fn required_by(options: &ClaudeAgentOptions) -> Vec<CliFeature> {
+        let mut required = Vec::new();
+        if options.enable_file_checkpointing {
+            required.push(CliFeature::FileCheckpointing);
+        }
+        if !options.plugins.is_empty() {
+            required.push(CliFeature::Plugins);
+        }
+        if options
+            .output_format
+            .as_ref()
+            .is_some_and(|format| format.get("type") == Some(&serde_json::json!("json_schema")))
+        {
+            required.push(CliFeature::JsonSchema);
+        }
+        required
+    }
+
+    /// Reject `options` that use a feature this CLI version lacks
+    pub fn check_options(&self, options: &ClaudeAgentOptions) -> Result<()> {
+        let Some(feature) = Self::required_by(options)
+            .into_iter()
+            .find(|feature| !self.supports(*feature))
+        else {
+            return Ok(());
+        };
+        Err(CliVersionError::new(
+            format!(
+                "{} requires Claude Code CLI {} or newer, found {}",
+                feature.option_name(),
+                feature.min_version(),
+                self.version
+            ),
+            None,
+            Some(self.version.to_string()),
+        )
+        .into())
+    }
+}
+
+/// Where a CLI candidate was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliSource {
+    /// [`ClaudeAgentOptions::cli_path`] or [`CliLocator`]'s `cli_path`
+    Explicit,
+    /// The `CLAUDE_CLI_PATH` environment variable
+    EnvVar,
+    /// A directory on `PATH`
+    Path,
+    /// A standard install location
+    CommonLocation,
+}
+
+/// A CLI executable found by [`CliLocator`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliCandidate {
+    /// Executable path
+    pub path: PathBuf,
+    /// Where it was found
+    pub source: CliSource,
+    /// Parsed version, or `None` if `--version` failed or printed something unexpected
+    pub version: Option<CliVersion>,
+    /// First line printed by `--version`
+    pub version_output: Option<String>,
+}
+
+impl CliCandidate {
+    /// Features available in this candidate, if its version is known
+    pub fn capabilities(&self) -> Option<CliCapabilities> {
+        self.version.map(CliCapabilities::for_version)
+    }
+
+    /// Get the candidate's version from the cache, running `--version` on a miss
+    fn probe(path: PathBuf, source: CliSource, env: &HashMap<String, String>) -> Self {
+        let version_output = version_output_blocking(&path, env);
+        Self::with_version_output(path, source, version_output)
+    }
+
+    fn with_version_output(
+        path: PathBuf,
+        source: CliSource,
+        version_output: Option<String>,
+    ) -> Self {
+        Self {
+            version: version_output
+                .as_deref()
+                .and_then(CliVersion::from_version_output),
+            path,
+            source,
+            version_output,
+        }
+    }
+}
+
+/// Finds Claude Code CLI executables and selects one within a version range
+///
+/// Without explicit bounds, versions below
+/// [`MIN_CLI_VERSION`](crate::version::MIN_CLI_VERSION) are still accepted by
+/// [`locate`](Self::locate); the transport logs a warning for them. Explicit bounds are
+/// enforced as errors.
+#[derive(Debug, Clone, Default, TypedBuilder)]
+pub struct CliLocator {
+    /// Executable to try first
+    #[builder(default, setter(into, strip_option))]
+    pub cli_path: Option<PathBuf>,
+    /// Lowest acceptable version (inclusive)
+    #[builder(default, setter(strip_option))]
+    pub min_version: Option<CliVersion>,
+    /// Highest acceptable version (inclusive)
+    #[builder(default, setter(strip_option))]
+    pub max_version: Option<CliVersion>,
+    /// Extra environment variables for the `--version` probes
+    #[builder(default)]
+    pub env: HashMap<String, String>,
+}
+
+impl CliLocator {
+    /// Locator using the CLI path and version bounds from `options`
+    pub fn from_options(options: &ClaudeAgentOptions) -> Self {
+        Self {
+            cli_path: options.cli_path.clone(),
+            min_version: options.min_cli_version,
+            max_version: options.max_cli_version,
+            env: options.env.clone(),
+        }
+    }
+
+    /// Every existing CLI executable, in search order, each probed with `--version`
+    ///
+    /// The same executable reached through several sources is listed once.
+    pub fn candidates(&self) -> Vec<CliCandidate> {
+        self.search_paths()
+            .into_iter()
+            .map(|(path, source)| CliCandidate::probe(path, source, &self.env))
+            .collect()
+    }
+
+    /// The first candidate whose version is within bounds
+    ///
+    /// Candidates are probed lazily, so only the executables up to the selected one are
+    /// run. With no bounds configured, a candidate whose version cannot be determined is
+    /// accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClaudeError::CliNotFound`] if there is no candidate, or
+    /// [`ClaudeError::CliVersion`] if none is within bounds.
+    pub fn locate(&self) -> Result<CliCandidate> {
+        let mut rejected = Vec::new();
+        for (path, source) in self.search_paths() {
+            let candidate = CliCandidate::probe(path, source, &self.env);
+            match self.check_version(&candidate.path, candidate.version) {
+                Ok(()) => return Ok(candidate),
+                Err(e) => rejected.push(e.to_string()),
+            }
+        }
+        Err(Self::none_suitable(rejected))
+    }
+
+    /// Async variant of [`locate`](Self::locate), probing with [`version_output`]
+    /// instead of blocking the calling thread
+    pub async fn locate_async(&self) -> Result<CliCandidate> {
+        let mut rejected = Vec::new();
+        for (path, source) in self.search_paths() {
+            let output = version_output(&path, &self.env).await;
+            let candidate = CliCandidate::with_version_output(path, source, output);
+            match self.check_version(&candidate.path, candidate.version) {
+                Ok(()) => return Ok(candidate),
+                Err(e) => rejected.push(e.to_string()),
+            }
+        }
+        Err(Self::none_suitable(rejected))
+    }
+
+    /// Error for a search that found no candidate, or only rejected ones
+    fn none_suitable(rejected: Vec<String>) -> ClaudeError {
+        if rejected.is_empty() {
+            return CliNotFoundError::new(
+                "Claude Code CLI not found. Please ensure 'claude' is in your PATH or set CLAUDE_CLI_PATH environment variable.",
+                None,
+            )
+            .into();
+        }
+        CliVersionError::new(
+            format!("no suitable Claude Code CLI: {}", rejected.join("; ")),
+            None,
+            None,
+        )
+        .into()
+    }
+
+    /// Check `version` of the CLI at `path` against the configured bounds
+    pub fn check_version(&self, path: &Path, version: Option<CliVersion>) -> Result<()> {
+        if self.min_version.is_none() && self.max_version.is_none() {
+            return Ok(());
+        }
+        let error = |message: String| -> ClaudeError {
+            CliVersionError::new(
+                message,
+                Some(path.to_path_buf()),
+                version.map(|v| v.to_string()),
+            )
+            .into()
+        };
+
+        let Some(version) = version else {
+            return Err(error(format!(
+                "cannot determine the version of {}",
+                path.display()
+            )));
+        };
+        if let Some(min) = self.min_version
+            && version < min
+        {
+            return Err(error(format!(
+                "{} is version {}, minimum is {}",
+                path.display(),
+                version,
+                min
+            )));
+        }
+        if let Some(max) = self.max_version
+            && version > max
+        {
+            return Err(error(format!(
+                "{} is version {}, maximum is {}",
+                path.display(),
+                version,
+                max
+            )));
+        }
+        Ok(())
+    }
+
+    /// The first existing executable, without probing its version
+    pub(crate) fn first_path(&self) -> Option<PathBuf> {
+        self.search_paths().into_iter().next().map(|(path, _)| path)
+    }
+
+    /// Existing executables in search order, without duplicates
+    fn search_paths(&self) -> Vec<(PathBuf, CliSource)> {
+        let mut found = Vec::new();
+        let mut seen = HashSet::new();
+        let mut push = |path: PathBuf, source: CliSource| {
+            if !is_executable(&path) {
+                return;
+            }
+            let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if seen.insert(key) {
+                found.push((path, source));
+            }
+        };
+
+        if let Some(path) = &self.cli_path {
+            push(path.clone(), CliSource::Explicit);
+        }
+        if let Some(path) = std::env::var_os(CLI_PATH_ENV) {
+            push(PathBuf::from(path), CliSource::EnvVar);
+        }
+        if let Some(path_var) = std::env::var_os("PATH") {
+            for dir in std::env::split_paths(&path_var) {
+                for name in EXECUTABLE_NAMES {
+                    push(dir.join(name), CliSource::Path);
+                }
+            }
+        }
+        for path in common_locations() {
+            push(path, CliSource::CommonLocation);
+        }
+
+        found
+    }
+}
+
+/// Resolve the CLI that `options` select and cache its version, so later connects and
+/// [`get_claude_code_version`](crate::version::get_claude_code_version) skip the
+/// `--version` probe
+///
+/// The first connect otherwise runs `claude --version` for each CLI executable, which
+/// takes 100-500 ms. Servers can call this at startup to pay that cost
+/// before the first session; it also fails fast if the CLI is missing or outside the
+/// [`min_cli_version`](ClaudeAgentOptions::min_cli_version) /
+/// [`max_cli_version`](ClaudeAgentOptions::max_cli_version) range.
+///
+/// # Example
+///
+/// ```no_run
+/// use claude_agent_sdk_rs::ClaudeAgentOptions;
+///
+/// # #[tokio::main]
+/// # async fn main() -> claude_agent_sdk_rs::Result<()> {
+/// let options = ClaudeAgentOptions::default();
+/// let cli = claude_agent_sdk_rs::cli::prewarm(&options).await?;
+/// println!("{} is version {:?}", cli.path.display(), cli.version);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn prewarm(options: &ClaudeAgentOptions) -> Result<CliCandidate> {
+    let locator = CliLocator::from_options(options);
+    // Like the transport, use an explicit `cli_path` as is instead of searching
+    if let Some(path) = options.cli_path.clone() {
+        if !is_executable(&path) {
+            return Err(CliNotFoundError::new(
+                "Claude Code CLI not found at the configured cli_path",
+                Some(path),
+            )
+            .into());
+        }
+        let version_output = version_output(&path, &options.env).await;
+        let candidate =
+            CliCandidate::with_version_output(path, CliSource::Explicit, version_output);
+        locator.check_version(&candidate.path, candidate.version)?;
+        return Ok(candidate);
+    }
+    locator.locate_async().await
+}
+
+/// First line of `--version` output of the CLI at `path`, or `None` if it cannot be run
+///
+/// The CLI runs with the inherited environment plus `env`, like a session with
+/// [`ClaudeAgentOptions::env`]. Results are cached process-wide by canonical path and
+/// modification time, so every client shares one entry per executable and replacing it
+/// (e.g. upgrading the CLI) replaces its entry. `env` is not part of the key: the first
+/// probe's result is reused, and no environment values are kept. Concurrent callers for
+/// the same executable share a single probe. Failed probes are not cached.
+pub async fn version_output(path: &Path, env: &HashMap<String, String>) -> Option<String> {
+    version_cell(path)
+        .output
+        .get_or_try_init(|| async {
+            let output = create_async_hidden_command(path)
+                .arg("--version")
+                .envs(env)
+                .output()
+                .await
+                .map_err(|_| ())?;
+            first_line(output)
+        })
+        .await
+        .ok()
+        .cloned()
+}
+
+/// Blocking variant of [`version_output`], sharing its cache
+///
+/// Blocking callers probe one at a time per executable, so concurrent ones share a
+/// single `--version` run as well.
+pub(crate) fn version_output_blocking(
+    path: &Path,
+    env: &HashMap<String, String>,
+) -> Option<String> {
+    let cell = version_cell(path);
+    let _probing = cell
+        .blocking
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(cached) = cell.output.get() {
+        return Some(cached.clone());
+    }
+    let output = create_sync_hidden_command(path)
+        .arg("--version")
+        .envs(env)
+        .output()
+        .ok()?;
+    let line = first_line(output).ok()?;
+    let _ = cell.output.set(line.clone());
+    Some(line)
+}
+
+/// First line of a successful `--version` run
+fn first_line(output: std::process::Output) -> std::result::Result<String, ()> {
+    if !output.status.success() {
+        return Err(());
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .ok_or(())
+}
+
+/// Cached `--version` output of one build of the CLI
+#[derive(Default)]
+struct VersionCell {
+    /// Probe result; async callers initialize it single-flight
+    output: tokio::sync::OnceCell<String>,
+    /// Held by a blocking caller while it probes
+    blocking: Mutex<()>,
+}
+
+/// Cache entry for an executable: its modification time when cached, and its probe
+struct VersionEntry {
+    modified: Option<SystemTime>,
+    cell: Arc<VersionCell>,
+}
+
+/// Process-wide `--version` cache by canonical path, shared by all clients
+static VERSION_CACHE: OnceLock<Mutex<HashMap<PathBuf, VersionEntry>>> = OnceLock::new();
+
+/// The cache cell for the executable at `path`
+///
+/// The same path with a different modification time is a different executable, so its
+/// stale entry is replaced.
+fn version_cell(path: &Path) -> Arc<VersionCell> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+    let mut cache = VERSION_CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let entry = cache.entry(path).or_insert_with(|| VersionEntry {
+        modified,
+        cell: Default::default(),
+    });
+    if entry.modified != modified {
+        *entry = VersionEntry {
+            modified,
+            cell: Default::default(),
+        };
+    }
+    Arc::clone(&entry.cell)
+}
+
+/// File names of the CLI executable
+#[cfg(not(target_os = "windows"))]
+const EXECUTABLE_NAMES: &[&str] = &["claude"];
+#[cfg(target_os = "windows")]
+const EXECUTABLE_NAMES: &[&str] = &["claude.exe", "claude.cmd"];
+
+/// Standard install locations, checked after `PATH`
+fn common_locations() -> Vec<PathBuf> {
+    let home_dir = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE")) // Windows fallback
+        .ok()
+        .map(PathBuf::from);
+    let mut paths = Vec::new();
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        paths.extend([
+            PathBuf::from("/usr/local/bin/claude"),
+            PathBuf::from("/opt/homebrew/bin/claude"),
+            PathBuf::from("/usr/bin/claude"),
+        ]);
+        if let Some(ref home) = home_dir {
+            paths.push(home.join(".local/bin/claude"));
+            paths.push(home.join("bin/claude"));
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Some(ref home) = home_dir {
+            paths.extend([
+                home.join("AppData\\Local\\Programs\\Claude\\claude.exe"),
+                home.join("AppData\\Roaming\\npm\\claude.cmd"),
+                home.join("AppData\\Roaming\\npm\\claude.exe"),
+            ]);
+        }
+        paths.extend([
+            PathBuf::from("C:\\Program Files\\Claude\\claude.exe"),
+            PathBuf::from("C:\\Program Files (x86)\\Claude\\claude.exe"),
+        ]);
+    }
+
+    paths
+}
+
+/// Whether `path` is a file this user can execute
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_version_parsing_and_ordering() {
+        assert_eq!(
+            CliVersion::from_version_output("2.0.14 (Claude Code)\n"),
+            Some(CliVersion::new(2, 0, 14))
+        );
+        assert_eq!(CliVersion::from_version_output("unknown"), None);
+        assert!("2.1".parse::<CliVersion>().is_err());
+        assert!(CliVersion::new(2, 0, 14) < CliVersion::new(2, 1, 0));
+        assert!(CliVersion::new(10, 0, 0) > CliVersion::new(9, 99, 99));
+        assert_eq!(CliVersion::new(2, 0, 14).to_string(), "2.0.14");
+    }
+
+    #[test]
+    fn test_capabilities_follow_version() {
+        let old = CliCapabilities::for_version(CliVersion::new(2, 0, 0));
+        assert!(old.features().is_empty());
+
+        let new = CliCapabilities::for_version(CliVersion::new(3, 0, 0));
+        assert_eq!(new.features(), CliFeature::ALL);
+
+        let middle = CliCapabilities::for_version(CliVersion::new(2, 0, 12));
+        assert!(middle.supports(CliFeature::Plugins));
+        assert!(!middle.supports(CliFeature::JsonSchema));
+    }
+
+    #[test]
+    fn test_check_options_rejects_unsupported_features() {
+        let options = ClaudeAgentOptions::builder()
+            .output_format(serde_json::json!({"type": "json_schema", "schema": {}}))
+            .build();
+        assert_eq!(
+            CliCapabilities::required_by(&options),
+            [CliFeature::JsonSchema]
+        );
+
+        let err = CliCapabilities::for_version(CliVersion::new(2, 0, 12))
+            .check_options(&options)
+            .unwrap_err();
+        assert!(matches!(err, ClaudeError::CliVersion(_)));
+        assert!(err.to_string().contains("2.0.45"));
+
+        CliCapabilities::for_version(CliVersion::new(2, 0, 45))
+            .check_options(&options)
+            .unwrap();
+        CliCapabilities::for_version(CliVersion::new(2, 0, 0))
+            .check_options(&ClaudeAgentOptions::default())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_check_version_bounds() {
+        let path = Path::new("/usr/bin/claude");
+        assert!(CliLocator::default().check_version(path, None).is_ok());
+
+        let locator = CliLocator::builder()
+            .min_version(CliVersion::new(2, 0, 10))
+            .max_version(CliVersion::new(2, 9, 0))
+            .build();
+        assert!(
+            locator
+                .check_version(path, Some(CliVersion::new(2, 0, 10)))
+                .is_ok()
+        );
+        assert!(
+            locator
+                .check_version(path, Some(CliVersion::new(2, 9, 0)))
+                .is_ok()
+        );
+        assert!(
+            locator
+                .check_version(path, Some(CliVersion::new(2, 0, 9)))
+                .is_err()
+        );
+        assert!(
+            locator
+                .check_version(path, Some(CliVersion::new(3, 0, 0)))
+                .is_err()
+        );
+        assert!(locator.check_version(path, None).is_err());
+    }
+
+    #[test]
+    fn test_explicit_path_is_searched_first() {
+        let exe = std::env::current_exe().unwrap();
+        let locator = CliLocator::builder().cli_path(&exe).build();
+
+        let paths = locator.search_paths();
+        assert_eq!(paths[0], (exe.clone(), CliSource::Explicit));
+        assert_eq!(locator.first_path(), Some(exe));
+
+        let missing = CliLocator::builder()
+            .cli_path("/nonexistent/claude")
+            .build();
+        assert!(
+            missing
+                .search_paths()
+                .iter()
+                .all(|(_, source)| *source != CliSource::Explicit)
+        );
+    }
+}
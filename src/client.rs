@@ -15,6 +15,11 @@ use crate::types::efficiency::{build_efficiency_hooks, merge_hooks};
 use crate::types::hooks::{HookEvent, HookMatcher};
 use crate::types::mcp::McpSdkServerConfig;
 use crate::types::messages::{Message, UserContentBlock};
+use crate::types::plugin::{Plugin, load_all};
+use crate::types::process::{ClientStatus, ResourceUsage, ShutdownReport};
+
+/// How long [`ClaudeClient::ping`] waits for the CLI to answer
+pub const DEFAULT_PING_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 
 /// Client for bidirectional streaming interactions with Claude
 ///
@@ -58,9 +63,14 @@ pub struct ClaudeClient {
     /// Shutdown receiver - signals when background task completes
     shutdown_rx: Option<tokio::sync::oneshot::Receiver<()>>,
     connected: bool,
+    /// Plugins validated at connect time
+    plugins: Vec<Plugin>,
     /// Custom transport for testing (feature-gated)
     #[cfg(feature = "testing")]
     _custom_transport: Option<Arc<dyn Transport>>,
+    /// Cassette used by `connect()` instead of a plain subprocess (feature-gated)
+    #[cfg(feature = "testing")]
+    _cassette: Option<(std::path::PathBuf, crate::testing::CassetteMode)>,
 }
 
 impl ClaudeClient {
@@ -83,8 +93,11 @@ impl ClaudeClient {
             query: None,
             shutdown_rx: None,
             connected: false,
+            plugins: Vec::new(),
             #[cfg(feature = "testing")]
             _custom_transport: None,
+            #[cfg(feature = "testing")]
+            _cassette: None,
         }
     }
 
@@ -117,17 +130,18 @@ impl ClaudeClient {
     ///
     /// This combines user-provided hooks with efficiency hooks and converts
     /// them to the internal format expected by the CLI.
-    fn build_hooks_config(&self) -> Option<HashMap<String, Vec<HookMatcher>>> {
+    pub(crate) fn build_hooks_config(
+        options: &ClaudeAgentOptions,
+    ) -> Option<HashMap<String, Vec<HookMatcher>>> {
         // Build efficiency hooks if configured
-        let efficiency_hooks = self
-            .options
+        let efficiency_hooks = options
             .efficiency
             .as_ref()
             .map(build_efficiency_hooks)
             .unwrap_or_default();
 
         // Merge user hooks with efficiency hooks
-        let merged_hooks = merge_hooks(self.options.hooks.clone(), efficiency_hooks);
+        let merged_hooks = merge_hooks(options.hooks.clone(), efficiency_hooks);
 
         // Convert hooks to internal format
         merged_hooks.as_ref().map(|hooks_map| {
@@ -163,9 +177,10 @@ impl ClaudeClient {
         // Extract SDK MCP servers from options
         let sdk_mcp_servers = self.extract_sdk_mcp_servers();
         query.set_sdk_mcp_servers(sdk_mcp_servers);
+        query.set_can_use_tool(self.options.can_use_tool.clone());
 
         // Build hooks configuration
-        let hooks = self.build_hooks_config();
+        let hooks = Self::build_hooks_config(&self.options);
 
         // Start reading messages in background
         let shutdown_rx = query.start().await?;
@@ -184,8 +199,8 @@ impl ClaudeClient {
 
     /// Create a new ClaudeClient with early validation
     ///
-    /// Unlike `new()`, this validates the configuration eagerly by attempting
-    /// to create the transport. This catches issues like invalid working directory
+    /// Unlike `new()`, this validates the configuration eagerly by checking the options
+    /// and attempting to create the transport. This catches issues like invalid working directory
     /// or missing CLI before `connect()` is called.
     ///
     /// # Arguments
@@ -195,6 +210,7 @@ impl ClaudeClient {
     /// # Errors
     ///
     /// Returns an error if:
+    /// - [`ClaudeAgentOptions::validate`] reports an error (warnings are logged)
     /// - The working directory does not exist or is not a directory
     /// - Claude CLI cannot be found
     ///
@@ -207,6 +223,8 @@ impl ClaudeClient {
     /// # Ok::<(), claude_agent_sdk_rs::ClaudeError>(())
     /// ```
     pub fn try_new(options: ClaudeAgentOptions) -> Result<Self> {
+        options.ensure_valid()?;
+
         // Validate by attempting to create transport (but don't keep it)
         let prompt = QueryPrompt::Streaming;
         let _ = SubprocessTransport::new(prompt, options.clone())?;
@@ -216,8 +234,11 @@ impl ClaudeClient {
             query: None,
             shutdown_rx: None,
             connected: false,
+            plugins: Vec::new(),
             #[cfg(feature = "testing")]
             _custom_transport: None,
+            #[cfg(feature = "testing")]
+            _cassette: None,
         })
     }
 
@@ -251,11 +272,48 @@ impl ClaudeClient {
             query: None,
             shutdown_rx: None,
             connected: false,
+            plugins: Vec::new(),
             // Store the transport for use in connect_with_transport
             _custom_transport: Some(transport),
+            _cassette: None,
         }
     }
 
+    /// Create a client that records to or replays from a cassette file
+    ///
+    /// `connect()` resolves the mode against the file: in record mode the real CLI is
+    /// used and all traffic is saved (with secrets redacted) on `disconnect()`; in
+    /// replay mode the recorded responses are served and every write must match the
+    /// recording. See [`crate::testing::CassetteMode`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "testing")]
+    /// # async fn example() -> claude_agent_sdk_rs::Result<()> {
+    /// use claude_agent_sdk_rs::{ClaudeClient, ClaudeAgentOptions};
+    /// use claude_agent_sdk_rs::testing::CassetteMode;
+    ///
+    /// let mut client = ClaudeClient::with_cassette(
+    ///     ClaudeAgentOptions::default(),
+    ///     "tests/cassettes/greeting.json",
+    ///     CassetteMode::from_env()?,
+    /// );
+    /// client.connect().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "testing")]
+    pub fn with_cassette(
+        options: ClaudeAgentOptions,
+        path: impl Into<std::path::PathBuf>,
+        mode: crate::testing::CassetteMode,
+    ) -> Self {
+        let mut client = Self::new(options);
+        client._cassette = Some((path.into(), mode));
+        client
+    }
+
     /// Connect with a pre-configured transport (for testing)
     ///
     /// This method uses the mock transport for testing purposes, skipping
@@ -266,6 +324,8 @@ impl ClaudeClient {
             return Ok(());
         }
 
+        self.plugins = load_all(&self.options.plugins, self.options.cwd.as_deref()).await?;
+
         let transport = self._custom_transport.take().ok_or_else(|| {
             ClaudeError::InvalidConfig(
                 "No custom transport configured. Use with_transport() first.".to_string(),
@@ -283,6 +343,30 @@ impl ClaudeClient {
         self.setup_query(query, false).await
     }
 
+    /// Connect with a pre-configured transport and run the `initialize` handshake
+    ///
+    /// Unlike [`connect_with_transport`](Self::connect_with_transport), this sends the
+    /// `initialize` control request (registering hooks) and waits for its response, so
+    /// the transport must answer it, e.g. via [`CliSimulator`](crate::testing::CliSimulator).
+    #[cfg(feature = "testing")]
+    pub async fn connect_with_transport_and_initialize(&mut self) -> Result<()> {
+        if self.connected {
+            return Ok(());
+        }
+
+        self.plugins = load_all(&self.options.plugins, self.options.cwd.as_deref()).await?;
+
+        let transport = self._custom_transport.take().ok_or_else(|| {
+            ClaudeError::InvalidConfig(
+                "No custom transport configured. Use with_transport() first.".to_string(),
+            )
+        })?;
+
+        transport.connect().await?;
+        let query = QueryFull::new_with_transport(transport);
+        self.setup_query(query, true).await
+    }
+
     /// Connect to Claude (analogous to Python's __aenter__)
     ///
     /// This establishes the connection to the Claude Code CLI and initializes
@@ -291,6 +375,8 @@ impl ClaudeClient {
     /// # Errors
     ///
     /// Returns an error if:
+    /// - [`ClaudeAgentOptions::validate`] reports an error (warnings are logged)
+    /// - A configured plugin is invalid ([`ClaudeError::Plugin`])
     /// - Claude CLI cannot be found or started
     /// - The initialization handshake fails
     /// - Hook registration fails
@@ -299,6 +385,24 @@ impl ClaudeClient {
             return Ok(());
         }
 
+        self.options.ensure_valid()?;
+        // Fail early on broken plugin directories instead of inside the CLI
+        self.plugins = load_all(&self.options.plugins, self.options.cwd.as_deref()).await?;
+
+        #[cfg(feature = "testing")]
+        if let Some((path, mode)) = &self._cassette {
+            let options = self.options.clone();
+            let transport = crate::testing::cassette::cassette_transport(path, *mode, || {
+                Ok(Arc::new(SubprocessTransport::new(
+                    QueryPrompt::Streaming,
+                    options,
+                )?))
+            })?;
+            transport.connect().await?;
+            let query = QueryFull::new_with_transport(transport);
+            return self.setup_query(query, true).await;
+        }
+
         // Create transport in streaming mode (no initial prompt)
         let prompt = QueryPrompt::Streaming;
         let transport = SubprocessTransport::new(prompt, self.options.clone())?;
@@ -559,8 +663,8 @@ impl ClaudeClient {
             let rx = query.message_rx.clone();
 
             // No mutex needed - flume receiver is lock-free
-            while let Ok(json) = rx.recv_async().await {
-                match MessageParser::parse(json) {
+            while let Ok(item) = rx.recv_async().await {
+                match item.and_then(MessageParser::parse) {
                     Ok(msg) => yield Ok(msg),
                     Err(e) => yield Err(e),
                 }
@@ -622,8 +726,8 @@ impl ClaudeClient {
             let rx = query.message_rx.clone();
 
             // No mutex needed - flume receiver is lock-free
-            while let Ok(json) = rx.recv_async().await {
-                match MessageParser::parse(json) {
+            while let Ok(item) = rx.recv_async().await {
+                match item.and_then(MessageParser::parse) {
                     Ok(msg) => {
                         let is_result = matches!(msg, Message::Result(_));
                         yield Ok(msg);
@@ -826,16 +930,127 @@ impl ClaudeClient {
         self.query_with_session(prompt, session_id).await
     }
 
+    /// Plugins loaded and validated by [`connect`](Self::connect)
+    pub fn plugins(&self) -> &[Plugin] {
+        &self.plugins
+    }
+
+    /// Slash commands contributed by the loaded plugins, namespaced as `/plugin:command`
+    pub fn plugin_slash_commands(&self) -> Vec<String> {
+        self.plugins
+            .iter()
+            .flat_map(|plugin| plugin.slash_commands())
+            .collect()
+    }
+
+    /// Snapshot of the connection and the CLI process behind it, for monitoring
+    ///
+    /// Never fails: a client that is not connected reports `connected: false` and
+    /// empty counters.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use claude_agent_sdk_rs::{ClaudeClient, ClaudeAgentOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut client = ClaudeClient::new(ClaudeAgentOptions::default());
+    /// # client.connect().await?;
+    /// let status = client.status();
+    /// if let Some(process) = &status.process {
+    ///     println!("pid {} up {:?}, CLI {:?}", process.pid, process.uptime(), process.cli_version);
+    /// }
+    /// println!("{} pending control requests", status.pending_control_requests);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn status(&self) -> ClientStatus {
+        let Some(query) = self.query.as_ref() else {
+            return ClientStatus::default();
+        };
+        ClientStatus {
+            connected: self.connected,
+            process: query.transport.process_info(),
+            transport_ready: query.transport.is_ready(),
+            pending_control_requests: query.pending_control_requests(),
+            queued_messages: query.queued_messages(),
+            last_activity: query.last_activity(),
+        }
+    }
+
+    /// Check that the CLI is responsive by round-tripping a cheap control request
+    ///
+    /// Returns the round-trip time. The request does not involve the model, so it
+    /// answers promptly even while a query is running. Waits at most
+    /// [`DEFAULT_PING_TIMEOUT`]; use [`ping_with_timeout`](Self::ping_with_timeout) to
+    /// choose the deadline.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client is not connected, or the CLI fails to respond
+    /// or has exited. A missing response fails with [`ClaudeError::Timeout`].
+    pub async fn ping(&self) -> Result<std::time::Duration> {
+        self.ping_with_timeout(DEFAULT_PING_TIMEOUT).await
+    }
+
+    /// Like [`ping`](Self::ping), failing with [`ClaudeError::Timeout`] after `timeout`
+    pub async fn ping_with_timeout(
+        &self,
+        timeout: std::time::Duration,
+    ) -> Result<std::time::Duration> {
+        let query = self.query.as_ref().ok_or_else(|| {
+            ClaudeError::InvalidConfig("Client not connected. Call connect() first.".to_string())
+        })?;
+        query.ping(timeout).await
+    }
+
+    /// PID of the CLI process, or `None` if not connected or not using a subprocess
+    pub fn process_id(&self) -> Option<u32> {
+        self.query.as_ref()?.transport.process_id()
+    }
+
+    /// Current CPU, memory, thread and file descriptor usage of the CLI process
+    ///
+    /// Read from `/proc`, so only available on Linux. Covers the CLI process itself,
+    /// not the commands it has started.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client is not connected, has no CLI process, or the
+    /// usage cannot be read.
+    pub fn resource_usage(&self) -> Result<ResourceUsage> {
+        let query = self.query.as_ref().ok_or_else(|| {
+            ClaudeError::InvalidConfig("Client not connected. Call connect() first.".to_string())
+        })?;
+        let pid = query.transport.process_id().ok_or_else(|| {
+            ClaudeError::Transport("Transport has no running CLI process".to_string())
+        })?;
+
+        #[cfg(target_os = "linux")]
+        return Ok(ResourceUsage::read(pid)?);
+        #[cfg(not(target_os = "linux"))]
+        return Err(ClaudeError::Transport(format!(
+            "Resource usage of process {} is only available on Linux",
+            pid
+        )));
+    }
+
     /// Disconnect from Claude (analogous to Python's __aexit__)
     ///
-    /// This cleanly shuts down the connection to Claude Code CLI.
+    /// This cleanly shuts down the connection to Claude Code CLI: stdin is closed, then
+    /// the process is stopped following
+    /// [`ClaudeAgentOptions::shutdown_policy`](crate::ClaudeAgentOptions::shutdown_policy),
+    /// escalating to `SIGTERM` and `SIGKILL` if it does not exit in time.
+    ///
+    /// Returns a report of how the process stopped. Disconnecting a client that is
+    /// not connected reports [`ShutdownStage::NoProcess`](crate::ShutdownStage::NoProcess).
     ///
     /// # Errors
     ///
     /// Returns an error if disconnection fails.
-    pub async fn disconnect(&mut self) -> Result<()> {
+    pub async fn disconnect(&mut self) -> Result<ShutdownReport> {
         if !self.connected {
-            return Ok(());
+            return Ok(ShutdownReport::default());
         }
 
         // Mark as disconnected early to prevent Drop warning if close() fails
@@ -853,11 +1068,11 @@ impl ClaudeClient {
                 let _ = tokio::time::timeout(std::time::Duration::from_millis(500), rx).await;
             }
 
-            // Close the transport (waits for process to exit)
-            query.transport.close().await?;
+            // Close the transport, escalating if the process does not exit
+            return query.transport.shutdown().await;
         }
 
-        Ok(())
+        Ok(ShutdownReport::default())
     }
 }
 
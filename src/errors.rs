@@ -3,8 +3,13 @@
 use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::types::validation::OptionIssue;
+
 /// Main error type for the Claude Agent SDK
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum ClaudeError {
     /// CLI connection error
     #[error("CLI connection error: {0}")]
@@ -22,6 +27,11 @@ pub enum ClaudeError {
     #[error("Message parse error: {0}")]
     MessageParse(#[from] MessageParseError),
 
+    /// A single line of CLI output exceeded `max_buffer_size`; the line was skipped
+    /// and reading continues
+    #[error("Buffer size exceeded: {0}")]
+    BufferSizeExceeded(#[from] BufferSizeExceededError),
+
     /// Transport error
     #[error("Transport error: {0}")]
     Transport(String),
@@ -30,18 +40,38 @@ pub enum ClaudeError {
     #[error("Control protocol error: {0}")]
     ControlProtocol(String),
 
+    /// The CLI did not answer a control request in time
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
     /// Invalid configuration
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
 
+    /// `ClaudeAgentOptions::validate` found errors
+    #[error("Invalid options: {0}")]
+    InvalidOptions(#[from] OptionsValidationError),
+
     /// CLI not found error
     #[error("CLI not found: {0}")]
     CliNotFound(#[from] CliNotFoundError),
 
+    /// CLI version outside the accepted range, or missing a feature the options need
+    #[error("CLI version error: {0}")]
+    CliVersion(#[from] CliVersionError),
+
     /// Image validation error
     #[error("Image validation error: {0}")]
     ImageValidation(#[from] ImageValidationError),
 
+    /// Plugin loading error
+    #[error("Plugin error: {0}")]
+    Plugin(#[from] PluginError),
+
+    /// Agent profile loading error
+    #[error("Profile error: {0}")]
+    Profile(#[from] ProfileError),
+
     /// IO error
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -71,6 +101,33 @@ impl CliNotFoundError {
     }
 }
 
+/// Error when the Claude Code CLI version is not acceptable
+#[derive(Debug, Error)]
+#[error("{message}")]
+pub struct CliVersionError {
+    /// Error message
+    pub message: String,
+    /// Path of the rejected CLI, if a single one was checked
+    pub cli_path: Option<PathBuf>,
+    /// Version it reported, if known
+    pub version: Option<String>,
+}
+
+impl CliVersionError {
+    /// Create a new CLI version error
+    pub fn new(
+        message: impl Into<String>,
+        cli_path: Option<PathBuf>,
+        version: Option<String>,
+    ) -> Self {
+        Self {
+            message: message.into(),
+            cli_path,
+            version,
+        }
+    }
+}
+
 /// Error when connecting to Claude Code CLI
 #[derive(Debug, Error)]
 #[error("Connection error: {message}")]
@@ -111,6 +168,21 @@ impl ProcessError {
     }
 }
 
+/// Error when options fail [`ClaudeAgentOptions::validate`](crate::ClaudeAgentOptions::validate)
+#[derive(Debug, Error)]
+#[error("{}", issues.iter().map(|issue| issue.to_string()).collect::<Vec<_>>().join("; "))]
+pub struct OptionsValidationError {
+    /// The error-severity issues
+    pub issues: Vec<OptionIssue>,
+}
+
+impl OptionsValidationError {
+    /// Create a new options validation error
+    pub fn new(issues: Vec<OptionIssue>) -> Self {
+        Self { issues }
+    }
+}
+
 /// Error when JSON decoding fails
 #[derive(Debug, Error)]
 #[error("JSON decode error: {message}")]
@@ -131,6 +203,23 @@ impl JsonDecodeError {
     }
 }
 
+/// Error when a line of CLI output is longer than the configured buffer limit
+#[derive(Debug, Error)]
+#[error("skipped a {line_bytes} byte line, maximum is {limit} bytes")]
+pub struct BufferSizeExceededError {
+    /// Size of the skipped line in bytes, including the newline
+    pub line_bytes: usize,
+    /// Configured `max_buffer_size`
+    pub limit: usize,
+}
+
+impl BufferSizeExceededError {
+    /// Create a new buffer size error
+    pub fn new(line_bytes: usize, limit: usize) -> Self {
+        Self { line_bytes, limit }
+    }
+}
+
 /// Error when message parsing fails
 #[derive(Debug, Error)]
 #[error("Message parse error: {message}")]
@@ -168,5 +257,107 @@ impl ImageValidationError {
     }
 }
 
+/// Error when a plugin directory or one of its components is invalid
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum PluginError {
+    /// The plugin directory does not exist
+    #[error("plugin directory not found: {}", path.display())]
+    DirectoryNotFound {
+        /// Plugin directory
+        path: PathBuf,
+    },
+
+    /// `.claude-plugin/plugin.json` is missing
+    #[error("plugin manifest not found: {}", path.display())]
+    ManifestNotFound {
+        /// Expected manifest path
+        path: PathBuf,
+    },
+
+    /// The manifest could not be parsed
+    #[error("invalid plugin manifest {}: {message}", path.display())]
+    InvalidManifest {
+        /// Manifest path
+        path: PathBuf,
+        /// Parse or validation failure
+        message: String,
+    },
+
+    /// A component path declared in the manifest does not exist
+    #[error("plugin '{plugin}' declares missing {component}: {}", path.display())]
+    MissingComponent {
+        /// Plugin name
+        plugin: String,
+        /// Component kind (`commands`, `agents`, `hooks`, `mcpServers`)
+        component: &'static str,
+        /// Declared path
+        path: PathBuf,
+    },
+
+    /// A command, agent, hooks or MCP server file is malformed
+    #[error("invalid plugin {component} {}: {message}", path.display())]
+    InvalidComponent {
+        /// Component kind (`commands`, `agents`, `hooks`, `mcpServers`)
+        component: &'static str,
+        /// File that failed to load
+        path: PathBuf,
+        /// Parse or validation failure
+        message: String,
+    },
+}
+
+/// Error when an agent profile file cannot be loaded or a profile cannot be resolved
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ProfileError {
+    /// The profile file could not be read
+    #[error("cannot read profile file {}: {source}", path.display())]
+    Io {
+        /// Profile file
+        path: PathBuf,
+        /// Underlying IO error
+        source: std::io::Error,
+    },
+
+    /// The file extension is not `.toml` or `.json`
+    #[error("unsupported profile format: {}", path.display())]
+    UnsupportedFormat {
+        /// Profile file
+        path: PathBuf,
+    },
+
+    /// The document is not valid TOML/JSON
+    #[error("invalid profile file: {0}")]
+    Parse(String),
+
+    /// No profile with the requested name
+    #[error("unknown profile '{name}' (available: {})", available.join(", "))]
+    UnknownProfile {
+        /// Requested profile
+        name: String,
+        /// Profiles defined in the file
+        available: Vec<String>,
+    },
+
+    /// A `${VAR}` reference names an unset variable and has no default
+    #[error("environment variable '{variable}' referenced at {location} is not set")]
+    MissingVariable {
+        /// Variable name
+        variable: String,
+        /// Path to the string containing the reference, e.g. `env.API_KEY`
+        location: String,
+    },
+
+    /// The resolved profile does not match the profile schema
+    #[error("invalid profile '{profile}': {message}")]
+    Invalid {
+        /// Profile name, `default` for the top-level settings
+        profile: String,
+        /// What is wrong with it
+        message: String,
+    },
+}
+
 /// Result type for the Claude Agent SDK
 pub type Result<T> = std::result::Result<T, ClaudeError>;
@@ -1,10 +1,12 @@
 //! Internal client implementation
 
 use futures::stream::StreamExt;
+use tracing::warn;
 
-use crate::errors::Result;
+use crate::errors::{ClaudeError, Result};
 use crate::types::config::ClaudeAgentOptions;
 use crate::types::messages::Message;
+use crate::types::plugin::load_all;
 
 use super::message_parser::MessageParser;
 use super::transport::subprocess::QueryPrompt;
@@ -17,7 +19,9 @@ pub struct InternalClient {
 
 impl InternalClient {
     /// Create a new client
-    pub fn new(prompt: QueryPrompt, options: ClaudeAgentOptions) -> Result<Self> {
+    pub async fn new(prompt: QueryPrompt, options: ClaudeAgentOptions) -> Result<Self> {
+        options.ensure_valid_for_one_shot()?;
+        load_all(&options.plugins, options.cwd.as_deref()).await?;
         let transport = SubprocessTransport::new(prompt, options)?;
         Ok(Self { transport })
     }
@@ -29,11 +33,27 @@ impl InternalClient {
 
         // Collect all messages
         let mut messages = Vec::new();
+        let mut oversized = None;
         {
             let mut stream = self.transport.read_messages();
 
             while let Some(result) = stream.next().await {
-                let json = result?;
+                let json = match result {
+                    Ok(json) => json,
+                    Err(ClaudeError::JsonDecode(e)) => {
+                        // Stray non-JSON output (e.g. a CLI warning) is skipped, as in QueryFull
+                        warn!("Skipping undecodable line from CLI: {}", e);
+                        continue;
+                    }
+                    Err(e @ ClaudeError::BufferSizeExceeded(_)) => {
+                        // A message is missing, so the result is incomplete: finish the
+                        // session, then report the first oversized line
+                        warn!("{}", e);
+                        oversized.get_or_insert(e);
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
                 let message = MessageParser::parse(json)?;
                 messages.push(message);
             }
@@ -43,6 +63,9 @@ impl InternalClient {
         // Close transport
         self.transport.close().await?;
 
-        Ok(messages)
+        match oversized {
+            Some(e) => Err(e),
+            None => Ok(messages),
+        }
     }
 }
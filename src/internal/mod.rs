@@ -3,4 +3,5 @@
 pub mod client;
 pub mod message_parser;
 pub mod query_full;
+pub mod secrets;
 pub mod transport;
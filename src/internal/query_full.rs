@@ -3,15 +3,20 @@
 use dashmap::DashMap;
 use futures::stream::StreamExt;
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::sync::OnceLock;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::oneshot;
+use tracing::warn;
 
 use crate::errors::{ClaudeError, Result};
 use crate::types::hooks::{HookCallback, HookContext, HookInput, HookMatcher};
-use crate::types::mcp::McpSdkServerConfig;
+use crate::types::mcp::{McpNotificationSink, McpRequestContext, McpSdkServerConfig};
+use crate::types::permissions::{
+    CanUseToolCallback, PermissionResult, PermissionUpdate, ToolPermissionContext,
+};
 
 use super::transport::Transport;
 
@@ -36,7 +41,6 @@ struct ControlResponse {
 
 #[derive(Debug, serde::Deserialize)]
 struct ControlResponseData {
-    #[allow(dead_code)]
     subtype: String,
     request_id: String,
     #[serde(flatten)]
@@ -64,12 +68,20 @@ pub struct QueryFull {
     next_callback_id: Arc<AtomicU64>,
     request_counter: Arc<AtomicU64>,
     /// Pending control request responses - concurrent access via DashMap
-    pending_responses: Arc<DashMap<String, oneshot::Sender<serde_json::Value>>>,
-    message_tx: flume::Sender<serde_json::Value>,
+    pending_responses: Arc<DashMap<String, oneshot::Sender<Result<serde_json::Value>>>>,
+    /// Message sender - moved into the background task by `start()` so the
+    /// channel closes when the CLI's output ends
+    message_tx: std::sync::Mutex<Option<flume::Sender<Result<serde_json::Value>>>>,
     /// Message receiver - cloneable without mutex thanks to flume
-    pub(crate) message_rx: flume::Receiver<serde_json::Value>,
+    pub(crate) message_rx: flume::Receiver<Result<serde_json::Value>>,
+    /// Set once the read loop has ended; no further control responses will arrive
+    closed: Arc<AtomicBool>,
+    /// Milliseconds since the Unix epoch of the last output from the CLI (0 = none yet)
+    last_activity_ns: Arc<AtomicU64>,
     /// Initialization result - set once during initialize(), read many times
     initialization_result: OnceLock<serde_json::Value>,
+    /// Permission callback for `can_use_tool` requests
+    can_use_tool: Option<CanUseToolCallback>,
 }
 
 impl QueryFull {
@@ -84,9 +96,12 @@ impl QueryFull {
             next_callback_id: Arc::new(AtomicU64::new(0)),
             request_counter: Arc::new(AtomicU64::new(0)),
             pending_responses: Arc::new(DashMap::new()),
-            message_tx,
+            message_tx: std::sync::Mutex::new(Some(message_tx)),
             message_rx,
+            closed: Arc::new(AtomicBool::new(false)),
+            last_activity_ns: Arc::new(AtomicU64::new(0)),
             initialization_result: OnceLock::new(),
+            can_use_tool: None,
         }
     }
 
@@ -102,12 +117,20 @@ impl QueryFull {
             next_callback_id: Arc::new(AtomicU64::new(0)),
             request_counter: Arc::new(AtomicU64::new(0)),
             pending_responses: Arc::new(DashMap::new()),
-            message_tx,
+            message_tx: std::sync::Mutex::new(Some(message_tx)),
             message_rx,
+            closed: Arc::new(AtomicBool::new(false)),
+            last_activity_ns: Arc::new(AtomicU64::new(0)),
             initialization_result: OnceLock::new(),
+            can_use_tool: None,
         }
     }
 
+    /// Set the permission callback used to answer `can_use_tool` requests
+    pub fn set_can_use_tool(&mut self, callback: Option<CanUseToolCallback>) {
+        self.can_use_tool = callback;
+    }
+
     /// Set SDK MCP servers
     pub fn set_sdk_mcp_servers(&mut self, servers: HashMap<String, McpSdkServerConfig>) {
         self.sdk_mcp_servers.clear();
@@ -121,46 +144,14 @@ impl QueryFull {
         &self,
         hooks: Option<HashMap<String, Vec<HookMatcher>>>,
     ) -> Result<serde_json::Value> {
-        // Build hooks configuration
-        let mut hooks_config: HashMap<String, Vec<serde_json::Value>> = HashMap::new();
-
-        if let Some(hooks_map) = hooks {
-            for (event, matchers) in hooks_map {
-                let mut event_matchers = Vec::new();
-
-                for matcher in matchers {
-                    let mut callback_ids = Vec::new();
-
-                    for callback in matcher.hooks {
-                        let callback_id = format!(
-                            "hook_{}",
-                            self.next_callback_id.fetch_add(1, Ordering::SeqCst)
-                        );
-                        self.hook_callbacks.insert(callback_id.clone(), callback);
-                        callback_ids.push(callback_id);
-                    }
-
-                    let mut matcher_json = json!({
-                        "matcher": matcher.matcher,
-                        "hookCallbackIds": callback_ids
-                    });
-
-                    // Add timeout if specified
-                    if let Some(timeout) = matcher.timeout {
-                        matcher_json["timeout"] = json!(timeout);
-                    }
-
-                    event_matchers.push(matcher_json);
-                }
-
-                hooks_config.insert(event, event_matchers);
-            }
-        }
-
-        // Send initialize request
-        let request = json!({
-            "subtype": "initialize",
-            "hooks": if hooks_config.is_empty() { json!(null) } else { json!(hooks_config) }
+        // Register each callback under a fresh ID and send the request
+        let request = initialize_request(hooks, |callback| {
+            let callback_id = format!(
+                "hook_{}",
+                self.next_callback_id.fetch_add(1, Ordering::SeqCst)
+            );
+            self.hook_callbacks.insert(callback_id.clone(), callback);
+            callback_id
         });
 
         let response = self.send_control_request(request).await?;
@@ -181,7 +172,15 @@ impl QueryFull {
         let hook_callbacks = Arc::clone(&self.hook_callbacks);
         let sdk_mcp_servers = Arc::clone(&self.sdk_mcp_servers);
         let pending_responses = Arc::clone(&self.pending_responses);
-        let message_tx = self.message_tx.clone();
+        let can_use_tool = self.can_use_tool.clone();
+        let closed = Arc::clone(&self.closed);
+        let last_activity_ns = Arc::clone(&self.last_activity_ns);
+        let message_tx = self
+            .message_tx
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| ClaudeError::Transport("Query already started".to_string()))?;
 
         // Create a channel to signal when background task is ready
         let (ready_tx, ready_rx) = oneshot::channel();
@@ -197,6 +196,12 @@ impl QueryFull {
             let _ = ready_tx.send(());
 
             while let Some(result) = stream.next().await {
+                // Full precision, so it never sorts before the spawn time of a fast CLI
+                let now_ns = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_nanos() as u64);
+                last_activity_ns.store(now_ns, Ordering::Relaxed);
+
                 match result {
                     Ok(message) => {
                         let msg_type = message.get("type").and_then(|v| v.as_str());
@@ -211,7 +216,18 @@ impl QueryFull {
                                     if let Some((_, tx)) =
                                         pending_responses.remove(&response.response.request_id)
                                     {
-                                        let _ = tx.send(response.response.data);
+                                        let data = response.response.data;
+                                        let result = if response.response.subtype == "error" {
+                                            Err(ClaudeError::ControlProtocol(
+                                                data.get("error")
+                                                    .and_then(|e| e.as_str())
+                                                    .unwrap_or("Unknown error")
+                                                    .to_string(),
+                                            ))
+                                        } else {
+                                            Ok(data)
+                                        };
+                                        let _ = tx.send(result);
                                     }
                                 }
                             }
@@ -223,6 +239,7 @@ impl QueryFull {
                                     let transport_clone = Arc::clone(&transport_for_hooks);
                                     let hook_callbacks_clone = Arc::clone(&hook_callbacks);
                                     let sdk_mcp_servers_clone = Arc::clone(&sdk_mcp_servers);
+                                    let can_use_tool_clone = can_use_tool.clone();
 
                                     tokio::spawn(async move {
                                         if let Err(e) = Self::handle_control_request(
@@ -230,6 +247,7 @@ impl QueryFull {
                                             transport_clone,
                                             hook_callbacks_clone,
                                             sdk_mcp_servers_clone,
+                                            can_use_tool_clone,
                                         )
                                         .await
                                         {
@@ -240,14 +258,30 @@ impl QueryFull {
                             }
                             _ => {
                                 // Regular message - send to stream
-                                let _ = message_tx.send(message);
+                                let _ = message_tx.send(Ok(message));
                             }
                         }
                     }
-                    Err(_) => break,
+                    Err(ClaudeError::JsonDecode(e)) => {
+                        // Stray non-JSON output (e.g. a CLI warning) must not end the session
+                        warn!("Skipping undecodable line from CLI: {}", e);
+                    }
+                    Err(e @ ClaudeError::BufferSizeExceeded(_)) => {
+                        // The oversized line was skipped; report it and keep reading
+                        warn!("{}", e);
+                        let _ = message_tx.send(Err(e));
+                    }
+                    Err(e) => {
+                        let _ = message_tx.send(Err(e));
+                        break;
+                    }
                 }
             }
 
+            // The CLI is gone: fail control requests that will never be answered
+            closed.store(true, Ordering::SeqCst);
+            Self::fail_pending_responses(&pending_responses);
+
             // Signal that background task has completed
             let _ = shutdown_tx.send(());
         });
@@ -261,15 +295,63 @@ impl QueryFull {
     }
 
     /// Handle incoming control request from CLI
+    ///
+    /// Replies with a `success` response carrying the handler's result, or an `error`
+    /// response when the request cannot be handled, so the CLI never waits forever.
     async fn handle_control_request(
         request: IncomingControlRequest,
         transport: Arc<dyn Transport>,
         hook_callbacks: Arc<DashMap<String, HookCallback>>,
         sdk_mcp_servers: Arc<DashMap<String, McpSdkServerConfig>>,
+        can_use_tool: Option<CanUseToolCallback>,
     ) -> Result<()> {
         let request_id = request.request_id;
-        let request_data = request.request;
 
+        let result = Self::dispatch_control_request(
+            request.request,
+            Arc::clone(&transport),
+            hook_callbacks,
+            sdk_mcp_servers,
+            can_use_tool,
+        )
+        .await;
+
+        let response = match result {
+            Ok(ref response_data) => json!({
+                "type": "control_response",
+                "response": {
+                    "subtype": "success",
+                    "request_id": request_id,
+                    "response": response_data
+                }
+            }),
+            Err(ref e) => json!({
+                "type": "control_response",
+                "response": {
+                    "subtype": "error",
+                    "request_id": request_id,
+                    "error": e.to_string()
+                }
+            }),
+        };
+
+        let response_str = serde_json::to_string(&response)
+            .map_err(|e| ClaudeError::Transport(format!("Failed to serialize response: {}", e)))?;
+
+        // Write via transport - stdin/stdout have separate locks, no deadlock
+        transport.write(&response_str).await?;
+
+        result.map(|_| ())
+    }
+
+    /// Run the handler for an incoming control request and return its response data
+    async fn dispatch_control_request(
+        request_data: serde_json::Value,
+        transport: Arc<dyn Transport>,
+        hook_callbacks: Arc<DashMap<String, HookCallback>>,
+        sdk_mcp_servers: Arc<DashMap<String, McpSdkServerConfig>>,
+        can_use_tool: Option<CanUseToolCallback>,
+    ) -> Result<serde_json::Value> {
         let subtype = request_data
             .get("subtype")
             .and_then(|v| v.as_str())
@@ -331,12 +413,66 @@ impl QueryFull {
                     ClaudeError::ControlProtocol("Missing message for mcp_message".to_string())
                 })?;
 
-                let mcp_response =
-                    Self::handle_sdk_mcp_request(sdk_mcp_servers, server_name, mcp_message.clone())
-                        .await?;
+                let context = Self::mcp_request_context(Arc::clone(&transport), server_name);
+                let mcp_response = Self::handle_sdk_mcp_request(
+                    sdk_mcp_servers,
+                    server_name,
+                    mcp_message.clone(),
+                    context,
+                )
+                .await?;
 
                 json!({"mcp_response": mcp_response})
             }
+            "can_use_tool" => {
+                let callback = can_use_tool.ok_or_else(|| {
+                    ClaudeError::ControlProtocol(
+                        "can_use_tool request received but no permission callback is set"
+                            .to_string(),
+                    )
+                })?;
+
+                let tool_name = request_data
+                    .get("tool_name")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| ClaudeError::ControlProtocol("Missing tool_name".to_string()))?
+                    .to_string();
+                let input = request_data.get("input").cloned().unwrap_or(json!({}));
+                let suggestions: Vec<PermissionUpdate> = request_data
+                    .get("permission_suggestions")
+                    .cloned()
+                    .and_then(|v| serde_json::from_value(v).ok())
+                    .unwrap_or_default();
+                let context = ToolPermissionContext {
+                    signal: None,
+                    suggestions,
+                };
+
+                match callback(tool_name, input.clone(), context).await {
+                    // The CLI expects the (possibly rewritten) input on every allow
+                    PermissionResult::Allow(allow) => {
+                        let mut response = json!({
+                            "behavior": "allow",
+                            "updatedInput": allow.updated_input.unwrap_or(input)
+                        });
+                        if let Some(permissions) = allow.updated_permissions {
+                            response["updatedPermissions"] = serde_json::to_value(permissions)
+                                .map_err(|e| {
+                                    ClaudeError::ControlProtocol(format!(
+                                        "Failed to serialize permission updates: {}",
+                                        e
+                                    ))
+                                })?;
+                        }
+                        response
+                    }
+                    PermissionResult::Deny(deny) => json!({
+                        "behavior": "deny",
+                        "message": deny.message,
+                        "interrupt": deny.interrupt
+                    }),
+                }
+            }
             _ => {
                 return Err(ClaudeError::ControlProtocol(format!(
                     "Unsupported control request subtype: {}",
@@ -345,27 +481,23 @@ impl QueryFull {
             }
         };
 
-        // Send success response
-        let response = json!({
-            "type": "control_response",
-            "response": {
-                "subtype": "success",
-                "request_id": request_id,
-                "response": response_data
-            }
-        });
-
-        let response_str = serde_json::to_string(&response)
-            .map_err(|e| ClaudeError::Transport(format!("Failed to serialize response: {}", e)))?;
-
-        // Write via transport - stdin/stdout have separate locks, no deadlock
-        transport.write(&response_str).await?;
-
-        Ok(())
+        Ok(response_data)
     }
 
     /// Send control request to CLI
     async fn send_control_request(&self, request: serde_json::Value) -> Result<serde_json::Value> {
+        self.send_control_request_with_timeout(request, None).await
+    }
+
+    /// Send a control request, giving up after `timeout` if one is set
+    ///
+    /// A request that times out is dropped from the pending set, so a late response
+    /// is ignored.
+    async fn send_control_request_with_timeout(
+        &self,
+        request: serde_json::Value,
+        timeout: Option<Duration>,
+    ) -> Result<serde_json::Value> {
         let request_id = format!(
             "req_{}_{}",
             self.request_counter.fetch_add(1, Ordering::SeqCst),
@@ -387,27 +519,46 @@ impl QueryFull {
             .map_err(|e| ClaudeError::Transport(format!("Failed to serialize request: {}", e)))?;
 
         // Write via transport - stdin/stdout have separate locks, no deadlock
-        self.transport.write(&request_str).await?;
+        if let Err(e) = self.transport.write(&request_str).await {
+            self.pending_responses.remove(&request_id);
+            return Err(e);
+        }
+        if self.closed.load(Ordering::SeqCst) {
+            Self::fail_pending_responses(&self.pending_responses);
+        }
 
         // Wait for response
-        let response = rx.await.map_err(|_| {
+        let response = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, rx).await {
+                Ok(response) => response,
+                Err(_) => {
+                    self.pending_responses.remove(&request_id);
+                    return Err(ClaudeError::Timeout(format!(
+                        "no response to {} within {:?}",
+                        request_id, timeout
+                    )));
+                }
+            },
+            None => rx.await,
+        };
+        response.map_err(|_| {
             ClaudeError::ControlProtocol("Control request response channel closed".to_string())
-        })?;
-
-        Ok(response)
+        })?
     }
 
-    /// Receive messages
-    #[allow(dead_code)]
-    pub async fn receive_messages(&self) -> Vec<serde_json::Value> {
-        let mut messages = Vec::new();
-        let rx = self.message_rx.clone();
-
-        while let Ok(message) = rx.recv_async().await {
-            messages.push(message);
+    /// Resolve every pending control request with a connection-closed error
+    fn fail_pending_responses(
+        pending_responses: &DashMap<String, oneshot::Sender<Result<serde_json::Value>>>,
+    ) {
+        let request_ids: Vec<String> = pending_responses.iter().map(|e| e.key().clone()).collect();
+        for request_id in request_ids {
+            if let Some((_, tx)) = pending_responses.remove(&request_id) {
+                let _ = tx.send(Err(ClaudeError::ControlProtocol(format!(
+                    "CLI closed the connection before responding to {}",
+                    request_id
+                ))));
+            }
         }
-
-        messages
     }
 
     /// Send interrupt signal to Claude
@@ -472,6 +623,35 @@ impl QueryFull {
         Ok(())
     }
 
+    /// Round-trip an `mcp_status` control request, which the CLI answers without
+    /// involving the model, and return how long it took
+    ///
+    /// Fails with [`ClaudeError::Timeout`] if no response arrives within `timeout`.
+    pub async fn ping(&self, timeout: Duration) -> Result<Duration> {
+        let started = Instant::now();
+        self.send_control_request_with_timeout(json!({"subtype": "mcp_status"}), Some(timeout))
+            .await?;
+        Ok(started.elapsed())
+    }
+
+    /// Number of control requests awaiting a response from the CLI
+    pub fn pending_control_requests(&self) -> usize {
+        self.pending_responses.len()
+    }
+
+    /// Number of received messages not yet consumed
+    pub fn queued_messages(&self) -> usize {
+        self.message_rx.len()
+    }
+
+    /// When the last line of output was received from the CLI
+    pub fn last_activity(&self) -> Option<SystemTime> {
+        match self.last_activity_ns.load(Ordering::Relaxed) {
+            0 => None,
+            ns => Some(UNIX_EPOCH + Duration::from_nanos(ns)),
+        }
+    }
+
     /// Get server initialization info
     ///
     /// Returns the initialization result that was obtained during connect().
@@ -481,11 +661,41 @@ impl QueryFull {
         self.initialization_result.get().cloned()
     }
 
+    /// Build the request context for an SDK MCP server
+    ///
+    /// Notifications emitted by the server (progress, log messages) are sent to the
+    /// CLI as `mcp_message` control requests. They are fire-and-forget: the CLI's
+    /// acknowledgement has no pending entry and is dropped by the reader loop.
+    fn mcp_request_context(transport: Arc<dyn Transport>, server_name: &str) -> McpRequestContext {
+        let server_name = server_name.to_string();
+        let notifier: McpNotificationSink = Arc::new(move |notification| {
+            let transport = Arc::clone(&transport);
+            let server_name = server_name.clone();
+            Box::pin(async move {
+                let request = json!({
+                    "type": "control_request",
+                    "request_id": format!("req_mcp_{}", uuid::Uuid::new_v4().simple()),
+                    "request": {
+                        "subtype": "mcp_message",
+                        "server_name": server_name,
+                        "message": notification
+                    }
+                });
+                let request_str = serde_json::to_string(&request).map_err(|e| {
+                    ClaudeError::Transport(format!("Failed to serialize MCP notification: {}", e))
+                })?;
+                transport.write(&request_str).await
+            })
+        });
+        McpRequestContext::new(notifier)
+    }
+
     /// Handle SDK MCP request by routing to the appropriate server
     async fn handle_sdk_mcp_request(
         sdk_mcp_servers: Arc<DashMap<String, McpSdkServerConfig>>,
         server_name: &str,
         message: serde_json::Value,
+        context: McpRequestContext,
     ) -> Result<serde_json::Value> {
         // Clone the server config to release the DashMap guard before async call
         let server_config = sdk_mcp_servers
@@ -498,8 +708,50 @@ impl QueryFull {
         // Call the server's handle_message method
         server_config
             .instance
-            .handle_message(message)
+            .handle_message_with_context(message, context)
             .await
             .map_err(|e| ClaudeError::ControlProtocol(format!("MCP server error: {}", e)))
     }
 }
+
+/// Build the `initialize` control request, naming each hook callback with `register`
+///
+/// Events are emitted in sorted order so that IDs are assigned deterministically.
+pub(crate) fn initialize_request(
+    hooks: Option<HashMap<String, Vec<HookMatcher>>>,
+    mut register: impl FnMut(HookCallback) -> String,
+) -> serde_json::Value {
+    let mut hooks_config: BTreeMap<String, Vec<serde_json::Value>> = BTreeMap::new();
+
+    if let Some(hooks_map) = hooks {
+        let mut events: Vec<_> = hooks_map.into_iter().collect();
+        events.sort_by(|a, b| a.0.cmp(&b.0));
+        for (event, matchers) in events {
+            let mut event_matchers = Vec::new();
+
+            for matcher in matchers {
+                let callback_ids: Vec<String> =
+                    matcher.hooks.into_iter().map(&mut register).collect();
+
+                let mut matcher_json = json!({
+                    "matcher": matcher.matcher,
+                    "hookCallbackIds": callback_ids
+                });
+
+                // Add timeout if specified
+                if let Some(timeout) = matcher.timeout {
+                    matcher_json["timeout"] = json!(timeout);
+                }
+
+                event_matchers.push(matcher_json);
+            }
+
+            hooks_config.insert(event, event_matchers);
+        }
+    }
+
+    json!({
+        "subtype": "initialize",
+        "hooks": if hooks_config.is_empty() { json!(null) } else { json!(hooks_config) }
+    })
+}
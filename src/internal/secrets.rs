@@ -0,0 +1,91 @@
+//! Secret detection shared by dry-run invocations and cassettes
+//!
+//! [`CliInvocation`](crate::types::invocation::CliInvocation) and recorded cassettes
+//! redact the same names, so what counts as a secret is decided here only.
+
+/// Name segments that mark an environment variable, header or flag as secret
+const SECRET_SEGMENTS: &[&str] = &[
+    "apikey",
+    "auth",
+    "authorization",
+    "cookie",
+    "credential",
+    "credentials",
+    "key",
+    "passwd",
+    "password",
+    "secret",
+    "token",
+];
+
+/// Prefix of Anthropic API keys, redacted wherever they appear
+pub const API_KEY_PREFIX: &str = "sk-ant-";
+
+/// Whether a variable, header or flag name looks like it holds a secret
+///
+/// Names are split into `_`, `-` and `.` separated segments and at camelCase
+/// boundaries, so `ANTHROPIC_API_KEY`, `X-Api-Key` and `accessToken` match while
+/// `max-thinking-tokens` does not. This errs towards redacting: `apiKeyHelper`, a path
+/// to a script printing the key, matches too.
+pub fn is_secret_name(name: &str) -> bool {
+    name.trim_start_matches('-')
+        .split(['_', '-', '.'])
+        .flat_map(camel_case_words)
+        .any(|word| SECRET_SEGMENTS.contains(&word.to_ascii_lowercase().as_str()))
+}
+
+/// Split `accessToken` into `access` and `Token`, and `HTTPHeader` into `HTTP` and
+/// `Header`
+fn camel_case_words(segment: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = segment.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start = 0;
+    for i in 1..chars.len() {
+        let (index, c) = chars[i];
+        let prev = chars[i - 1].1;
+        let next_is_lower = chars.get(i + 1).is_some_and(|(_, n)| n.is_lowercase());
+        if c.is_uppercase() && (!prev.is_uppercase() || next_is_lower) {
+            words.push(&segment[start..index]);
+            start = index;
+        }
+    }
+    words.push(&segment[start..]);
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_secret_name() {
+        for name in [
+            "ANTHROPIC_API_KEY",
+            "X-Api-Key",
+            "Authorization",
+            "apiKey",
+            "--auth-token",
+            "accessToken",
+            "clientSecret",
+            "refreshToken",
+            "privateKey",
+            "apiKeyHelper",
+            "GITHUB_TOKEN",
+            "OPENAI_API_KEY",
+            "X-Auth-Token",
+            "OAuthToken",
+        ] {
+            assert!(is_secret_name(name), "{name}");
+        }
+        for name in [
+            "max-thinking-tokens",
+            "MONKEY",
+            "model",
+            "PATH",
+            "monkeyPatch",
+            "tokenizerName",
+        ] {
+            assert!(!is_secret_name(name), "{name}");
+        }
+    }
+}
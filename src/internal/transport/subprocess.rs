@@ -3,12 +3,13 @@
 use async_trait::async_trait;
 use futures::stream::Stream;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
-use std::process::Stdio;
-use std::sync::Arc;
+use std::process::{ExitStatus, Stdio};
 use std::sync::atomic::{AtomicBool, Ordering};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, ChildStdout, Command};
 use tokio::sync::Mutex;
 use tracing::warn;
@@ -22,42 +23,51 @@ const CREATE_NO_WINDOW: u32 = 0x08000000;
 
 /// Create a std::process::Command that won't show a console window on Windows
 #[cfg(target_os = "windows")]
-fn create_sync_hidden_command<S: AsRef<std::ffi::OsStr>>(program: S) -> std::process::Command {
+pub(crate) fn create_sync_hidden_command<S: AsRef<std::ffi::OsStr>>(
+    program: S,
+) -> std::process::Command {
     let mut cmd = std::process::Command::new(program);
     cmd.creation_flags(CREATE_NO_WINDOW);
     cmd
 }
 
 #[cfg(not(target_os = "windows"))]
-fn create_sync_hidden_command<S: AsRef<std::ffi::OsStr>>(program: S) -> std::process::Command {
+pub(crate) fn create_sync_hidden_command<S: AsRef<std::ffi::OsStr>>(
+    program: S,
+) -> std::process::Command {
     std::process::Command::new(program)
 }
 
 /// Create a tokio::process::Command that won't show a console window on Windows
 #[cfg(target_os = "windows")]
-fn create_async_hidden_command<S: AsRef<std::ffi::OsStr>>(program: S) -> Command {
+pub(crate) fn create_async_hidden_command<S: AsRef<std::ffi::OsStr>>(program: S) -> Command {
     let mut cmd = Command::new(program);
     cmd.creation_flags(CREATE_NO_WINDOW);
     cmd
 }
 
 #[cfg(not(target_os = "windows"))]
-fn create_async_hidden_command<S: AsRef<std::ffi::OsStr>>(program: S) -> Command {
+pub(crate) fn create_async_hidden_command<S: AsRef<std::ffi::OsStr>>(program: S) -> Command {
     Command::new(program)
 }
 
+use crate::cli::{self, CliCapabilities, CliLocator, CliVersion};
 use crate::errors::{
-    ClaudeError, CliNotFoundError, ConnectionError, JsonDecodeError, ProcessError, Result,
+    BufferSizeExceededError, ClaudeError, CliNotFoundError, ConnectionError, JsonDecodeError,
+    ProcessError, Result,
 };
 use crate::types::config::ClaudeAgentOptions;
 use crate::types::messages::UserContentBlock;
+use crate::types::process::{
+    ProcessInfo, ProcessIsolation, ProcessLimits, ShutdownPolicy, ShutdownReport, ShutdownStage,
+};
 use crate::version::{
     ENTRYPOINT, MIN_CLI_VERSION, SDK_VERSION, SKIP_VERSION_CHECK_ENV, check_version,
 };
 
 use super::Transport;
 
-const DEFAULT_MAX_BUFFER_SIZE: usize = 10 * 1024 * 1024; // 10MB
+pub(crate) const DEFAULT_MAX_BUFFER_SIZE: usize = 10 * 1024 * 1024; // 10MB
 
 /// Query prompt type
 #[derive(Clone)]
@@ -93,7 +103,12 @@ impl From<Vec<UserContentBlock>> for QueryPrompt {
 /// All internal state that requires mutation is wrapped in synchronization primitives,
 /// allowing all trait methods to use `&self` instead of `&mut self`.
 pub struct SubprocessTransport {
+    /// Explicit `cli_path`, or the first executable found
     cli_path: PathBuf,
+    /// Whether connect() must search for an executable within the version bounds
+    cli_searched: bool,
+    /// Executable selected by that search - set once in connect()
+    selected_cli: OnceLock<PathBuf>,
     cwd: Option<PathBuf>,
     options: ClaudeAgentOptions,
     prompt: QueryPrompt,
@@ -104,8 +119,13 @@ pub struct SubprocessTransport {
     /// stdout for reading - uses tokio Mutex for async access
     stdout: Arc<Mutex<Option<BufReader<ChildStdout>>>>,
     max_buffer_size: usize,
+    max_output_bytes: Option<u64>,
     /// Ready state - uses AtomicBool for lock-free access
     ready: AtomicBool,
+    /// When the process was spawned - set once in connect()
+    started_at: OnceLock<SystemTime>,
+    /// Version reported by `--version` - set once in connect() unless the check is skipped
+    cli_version: OnceLock<String>,
 }
 
 impl SubprocessTransport {
@@ -130,14 +150,20 @@ impl SubprocessTransport {
         let cli_path = if let Some(ref path) = options.cli_path {
             path.clone()
         } else {
-            Self::find_cli()?
+            Self::find_cli(&options)?
         };
+        // With version bounds the executable is chosen in connect(), by probing
+        let cli_searched = options.cli_path.is_none()
+            && (options.min_cli_version.is_some() || options.max_cli_version.is_some());
 
         let cwd = options.cwd.clone().or_else(|| std::env::current_dir().ok());
         let max_buffer_size = options.max_buffer_size.unwrap_or(DEFAULT_MAX_BUFFER_SIZE);
+        let max_output_bytes = options.max_output_bytes;
 
         Ok(Self {
             cli_path,
+            cli_searched,
+            selected_cli: OnceLock::new(),
             cwd,
             options,
             prompt,
@@ -145,117 +171,41 @@ impl SubprocessTransport {
             stdin: Arc::new(Mutex::new(None)),
             stdout: Arc::new(Mutex::new(None)),
             max_buffer_size,
+            max_output_bytes,
             ready: AtomicBool::new(false),
+            started_at: OnceLock::new(),
+            cli_version: OnceLock::new(),
         })
     }
 
-    /// Find the Claude CLI executable
-    fn find_cli() -> Result<PathBuf> {
-        // Strategy 1: Try executing 'claude' directly from PATH
-        // This is the most reliable method as it respects the shell's PATH resolution
-        if let Ok(output) = create_sync_hidden_command("claude")
-            .arg("--version")
-            .output()
-            && output.status.success()
-        {
-            // 'claude' is in PATH and executable, return it as-is
-            // The OS will resolve it when we spawn the process
-            return Ok(PathBuf::from("claude"));
-        }
-
-        // Strategy 2: Use 'which' command to locate claude in PATH (Unix-like systems)
-        #[cfg(not(target_os = "windows"))]
-        if let Ok(output) = std::process::Command::new("which").arg("claude").output()
-            && output.status.success()
-        {
-            let path_str = String::from_utf8_lossy(&output.stdout);
-            let path = PathBuf::from(path_str.trim());
-            // Verify the path exists and is executable
-            if path.exists() && path.is_file() {
-                return Ok(path);
-            }
-        }
-
-        // Strategy 3: Use 'where' command on Windows
-        #[cfg(target_os = "windows")]
-        if let Ok(output) = create_sync_hidden_command("where").arg("claude").output() {
-            if output.status.success() {
-                let path_str = String::from_utf8_lossy(&output.stdout);
-                // 'where' returns all matches, take the first one
-                if let Some(first_line) = path_str.lines().next() {
-                    let path = PathBuf::from(first_line.trim());
-                    if path.exists() && path.is_file() {
-                        return Ok(path);
-                    }
-                }
-            }
-        }
-
-        // Strategy 4: Check common installation locations
-        // Get home directory for path expansion
-        let home_dir = std::env::var("HOME")
-            .or_else(|_| std::env::var("USERPROFILE")) // Windows fallback
-            .ok()
-            .map(PathBuf::from);
-
-        // Common installation locations
-        let mut common_paths: Vec<PathBuf> = vec![];
-
-        // Unix-like paths
-        #[cfg(not(target_os = "windows"))]
-        {
-            common_paths.extend(vec![
-                PathBuf::from("/usr/local/bin/claude"),
-                PathBuf::from("/opt/homebrew/bin/claude"),
-                PathBuf::from("/usr/bin/claude"),
-            ]);
-
-            // Add home-relative paths if home directory is available
-            if let Some(ref home) = home_dir {
-                common_paths.push(home.join(".local/bin/claude"));
-                common_paths.push(home.join("bin/claude"));
-            }
-        }
-
-        // Windows paths
-        #[cfg(target_os = "windows")]
-        {
-            if let Some(ref home) = home_dir {
-                common_paths.extend(vec![
-                    home.join("AppData\\Local\\Programs\\Claude\\claude.exe"),
-                    home.join("AppData\\Roaming\\npm\\claude.cmd"),
-                    home.join("AppData\\Roaming\\npm\\claude.exe"),
-                ]);
-            }
-            common_paths.extend(vec![
-                PathBuf::from("C:\\Program Files\\Claude\\claude.exe"),
-                PathBuf::from("C:\\Program Files (x86)\\Claude\\claude.exe"),
-            ]);
-        }
-
-        // Check each common path
-        for path in common_paths {
-            if path.exists() && path.is_file() {
-                return Ok(path);
-            }
-        }
+    /// Resolved CLI executable
+    ///
+    /// With version bounds and no explicit `cli_path`, this is the first executable found
+    /// until connect() has selected the first one within bounds.
+    pub(crate) fn cli_path(&self) -> &Path {
+        self.selected_cli.get().unwrap_or(&self.cli_path)
+    }
 
-        // Strategy 5: Check if CLAUDE_CLI_PATH environment variable is set
-        if let Ok(cli_path) = std::env::var("CLAUDE_CLI_PATH") {
-            let path = PathBuf::from(cli_path);
-            if path.exists() && path.is_file() {
-                return Ok(path);
-            }
-        }
+    /// Working directory the CLI is started in
+    pub(crate) fn cwd(&self) -> Option<&Path> {
+        self.cwd.as_deref()
+    }
 
-        Err(ClaudeError::CliNotFound(CliNotFoundError::new(
-            "Claude Code CLI not found. Please ensure 'claude' is in your PATH or set CLAUDE_CLI_PATH environment variable.",
-            None,
-        )))
+    /// Find the first Claude CLI executable in [`CliLocator`] order, without probing it
+    ///
+    /// With `min_cli_version` or `max_cli_version` set, connect() later replaces it with
+    /// the first executable within bounds.
+    fn find_cli(options: &ClaudeAgentOptions) -> Result<PathBuf> {
+        CliLocator::from_options(options).first_path().ok_or_else(|| {
+            ClaudeError::CliNotFound(CliNotFoundError::new(
+                "Claude Code CLI not found. Please ensure 'claude' is in your PATH or set CLAUDE_CLI_PATH environment variable.",
+                None,
+            ))
+        })
     }
 
     /// Build command arguments from options
-    fn build_command(&self) -> Vec<String> {
+    pub(crate) fn build_command(&self) -> Vec<String> {
         let mut args = vec!["--output-format".to_string(), "stream-json".to_string()];
 
         // --verbose is REQUIRED when using --output-format=stream-json
@@ -378,9 +328,13 @@ impl SubprocessTransport {
         }
 
         // Add permission prompt tool name
+        // A can_use_tool callback routes permission prompts over the control protocol
         if let Some(ref tool_name) = self.options.permission_prompt_tool_name {
             args.push("--permission-prompt-tool".to_string());
             args.push(tool_name.clone());
+        } else if self.options.can_use_tool.is_some() {
+            args.push("--permission-prompt-tool".to_string());
+            args.push("stdio".to_string());
         }
 
         // Add output format (structured outputs / JSON schema)
@@ -464,12 +418,6 @@ impl SubprocessTransport {
             }
         }
 
-        // Add additional directories
-        for dir in &self.options.add_dirs {
-            args.push("--add-dir".to_string());
-            args.push(dir.display().to_string());
-        }
-
         // Add MCP servers configuration
         match &self.options.mcp_servers {
             crate::types::mcp::McpServers::Dict(servers) => {
@@ -597,36 +545,48 @@ impl SubprocessTransport {
         Some(serde_json::to_string(&serde_json::Value::Object(settings_obj)).unwrap_or_default())
     }
 
-    /// Check Claude CLI version
+    /// Check Claude CLI version and that it supports the configured options
     async fn check_claude_version(&self) -> Result<()> {
-        // Skip if option is set OR environment variable is set
-        if self.options.skip_version_check || std::env::var(SKIP_VERSION_CHECK_ENV).is_ok() {
+        if self.cli_searched && self.selected_cli.get().is_none() {
+            let candidate = CliLocator::from_options(&self.options)
+                .locate_async()
+                .await?;
+            let _ = self.selected_cli.set(candidate.path);
+        }
+
+        // Skip if option is set OR environment variable is set, unless explicit version
+        // bounds must be enforced
+        let bounded =
+            self.options.min_cli_version.is_some() || self.options.max_cli_version.is_some();
+        if !bounded
+            && (self.options.skip_version_check || std::env::var(SKIP_VERSION_CHECK_ENV).is_ok())
+        {
             return Ok(());
         }
 
-        let output = create_async_hidden_command(&self.cli_path)
-            .arg("--version")
-            .output()
+        // Cached per executable, so only the first connect pays for `--version`
+        let version_output = cli::version_output(self.cli_path(), &self.options.env)
             .await
-            .map_err(|e| {
-                ClaudeError::Connection(ConnectionError::new(format!(
-                    "Failed to get Claude version: {}",
-                    e
-                )))
-            })?;
-
-        let version_output = String::from_utf8_lossy(&output.stdout);
+            .unwrap_or_default();
         let version = version_output
-            .lines()
+            .split_whitespace()
             .next()
-            .and_then(|line| line.split_whitespace().next())
             .unwrap_or("")
             .trim();
+        if !version.is_empty() {
+            let _ = self.cli_version.set(version.to_string());
+        }
+
+        let parsed = CliVersion::from_version_output(version);
+        CliLocator::from_options(&self.options).check_version(self.cli_path(), parsed)?;
+        if let Some(parsed) = parsed {
+            CliCapabilities::for_version(parsed).check_options(&self.options)?;
+        }
 
         if !check_version(version) {
             warn!(
                 "Claude Code CLI ({}) version {} is below minimum required version {}. Some features may not work correctly.",
-                self.cli_path.display(),
+                self.cli_path().display(),
                 version,
                 MIN_CLI_VERSION
             );
@@ -636,7 +596,7 @@ impl SubprocessTransport {
     }
 
     /// Build environment variables
-    fn build_env(&self) -> HashMap<String, String> {
+    pub(crate) fn build_env(&self) -> HashMap<String, String> {
         let mut env = self.options.env.clone();
         env.insert("CLAUDE_CODE_ENTRYPOINT".to_string(), ENTRYPOINT.to_string());
         env.insert(
@@ -656,6 +616,346 @@ impl SubprocessTransport {
     }
 }
 
+/// Start the CLI in its own process group or session and arrange for it to be killed
+/// with this process, as configured in `options`
+#[cfg(unix)]
+fn apply_process_isolation(cmd: &mut Command, options: &ClaudeAgentOptions) {
+    match options.process_isolation {
+        ProcessIsolation::Inherit => {}
+        ProcessIsolation::ProcessGroup => {
+            cmd.process_group(0);
+        }
+        ProcessIsolation::Session => {
+            // SAFETY: the closure only calls setsid(2), which is async-signal-safe
+            unsafe {
+                cmd.pre_exec(|| {
+                    if libc::setsid() == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    if options.kill_on_parent_death {
+        let parent = std::process::id() as libc::pid_t;
+        // SAFETY: the closure only calls prctl(2) and getppid(2), which are
+        // async-signal-safe, and does not allocate
+        unsafe {
+            cmd.pre_exec(move || {
+                if libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                // The parent may have died between fork and prctl
+                if libc::getppid() != parent {
+                    return Err(std::io::Error::from_raw_os_error(libc::ESRCH));
+                }
+                Ok(())
+            });
+        }
+    }
+}
+
+/// `resource` argument type of setrlimit(2), which glibc declares as an enum
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type RlimitResource = libc::c_int;
+
+/// Set both the soft and hard limit of `resource` in the current process
+///
+/// Called between `fork` and `exec`, so it must stay async-signal-safe.
+#[cfg(unix)]
+fn set_rlimit(resource: RlimitResource, value: u64) -> std::io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    };
+    // SAFETY: `limit` is a valid rlimit that outlives the call
+    if unsafe { libc::setrlimit(resource, &limit) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Apply `limits` to the CLI process between `fork` and `exec`
+#[cfg(unix)]
+fn apply_process_limits(cmd: &mut Command, limits: &ProcessLimits) -> Result<()> {
+    if limits.is_empty() {
+        return Ok(());
+    }
+    // The nice range is checked by ClaudeAgentOptions::validate
+
+    // Prepared before forking: the child may not allocate
+    let cgroup_procs = match &limits.cgroup {
+        Some(dir) => Some(cgroup_procs_path(dir)?),
+        None => None,
+    };
+    let ProcessLimits {
+        max_address_space,
+        max_cpu_time,
+        max_open_files,
+        nice,
+        ..
+    } = limits.clone();
+    let max_cpu_seconds = max_cpu_time.map(|t| t.as_secs() + u64::from(t.subsec_nanos() > 0));
+
+    // SAFETY: the closure only makes async-signal-safe system calls (open, write,
+    // close, setpriority, setrlimit) and does not allocate
+    unsafe {
+        cmd.pre_exec(move || {
+            if let Some(path) = &cgroup_procs {
+                // Writing 0 to cgroup.procs moves the writing process
+                let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+                if fd == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                let written = libc::write(fd, b"0".as_ptr().cast(), 1);
+                let error = std::io::Error::last_os_error();
+                libc::close(fd);
+                if written != 1 {
+                    return Err(error);
+                }
+            }
+            if let Some(nice) = nice
+                && libc::setpriority(libc::PRIO_PROCESS, 0, nice) == -1
+            {
+                return Err(std::io::Error::last_os_error());
+            }
+            if let Some(bytes) = max_address_space {
+                set_rlimit(libc::RLIMIT_AS, bytes)?;
+            }
+            if let Some(seconds) = max_cpu_seconds {
+                set_rlimit(libc::RLIMIT_CPU, seconds)?;
+            }
+            if let Some(files) = max_open_files {
+                set_rlimit(libc::RLIMIT_NOFILE, files)?;
+            }
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
+/// Path of `cgroup.procs` in the cgroup v2 directory `dir`
+#[cfg(target_os = "linux")]
+fn cgroup_procs_path(dir: &std::path::Path) -> Result<std::ffi::CString> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let procs = dir.join("cgroup.procs");
+    if !procs.is_file() {
+        return Err(ClaudeError::InvalidConfig(format!(
+            "{} is not a cgroup v2 directory",
+            dir.display()
+        )));
+    }
+    std::ffi::CString::new(procs.as_os_str().as_bytes())
+        .map_err(|_| ClaudeError::InvalidConfig("cgroup path contains a NUL byte".to_string()))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn cgroup_procs_path(_dir: &std::path::Path) -> Result<std::ffi::CString> {
+    Err(ClaudeError::InvalidConfig(
+        "cgroup placement is only supported on Linux".to_string(),
+    ))
+}
+
+#[cfg(not(unix))]
+fn apply_process_limits(_cmd: &mut Command, limits: &ProcessLimits) -> Result<()> {
+    if limits.is_empty() {
+        return Ok(());
+    }
+    Err(ClaudeError::InvalidConfig(
+        "process limits are only supported on Unix".to_string(),
+    ))
+}
+
+#[cfg(not(unix))]
+fn apply_process_isolation(_cmd: &mut Command, _options: &ClaudeAgentOptions) {}
+
+/// Send `signal` to the CLI, or to its whole process group when it leads one
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: libc::c_int, group: bool) {
+    let target = if group {
+        -(pid as libc::pid_t)
+    } else {
+        pid as libc::pid_t
+    };
+    // SAFETY: kill(2) has no memory-safety preconditions. The CLI has not been reaped
+    // yet, or its group still has members, so the ID cannot have been reused
+    unsafe {
+        libc::kill(target, signal);
+    }
+}
+
+/// Stop `process`, escalating from waiting to `SIGTERM` to `SIGKILL` per `policy`
+///
+/// With `group` set, signals go to the CLI's whole process group, and any descendants
+/// still running once the CLI has exited are killed.
+async fn stop_process(
+    process: &mut Child,
+    policy: &ShutdownPolicy,
+    group: bool,
+) -> Result<(ShutdownStage, Option<ExitStatus>)> {
+    let pid = process.id();
+    let outcome = escalate(process, policy, pid, group).await;
+
+    #[cfg(unix)]
+    if group && let Some(pid) = pid {
+        send_signal(pid, libc::SIGKILL, true);
+    }
+
+    outcome
+}
+
+/// Wait, then signal, per `policy` until the CLI exits or the last deadline passes
+async fn escalate(
+    process: &mut Child,
+    policy: &ShutdownPolicy,
+    pid: Option<u32>,
+    group: bool,
+) -> Result<(ShutdownStage, Option<ExitStatus>)> {
+    if let Some(status) = wait_with_deadline(process, policy.stdin_grace).await? {
+        return Ok((ShutdownStage::Graceful, Some(status)));
+    }
+
+    #[cfg(unix)]
+    if let Some(pid) = pid {
+        send_signal(pid, libc::SIGTERM, group);
+        if let Some(status) = wait_with_deadline(process, policy.term_timeout).await? {
+            return Ok((ShutdownStage::Terminated, Some(status)));
+        }
+        if group {
+            send_signal(pid, libc::SIGKILL, true);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (pid, group);
+
+    let _ = process.start_kill();
+    match wait_with_deadline(process, policy.kill_timeout).await? {
+        Some(status) => Ok((ShutdownStage::Killed, Some(status))),
+        None => Ok((ShutdownStage::Unresponsive, None)),
+    }
+}
+
+/// Wait for `process` to exit, returning `None` if `deadline` passes first
+async fn wait_with_deadline(process: &mut Child, deadline: Duration) -> Result<Option<ExitStatus>> {
+    match tokio::time::timeout(deadline, process.wait()).await {
+        Ok(Ok(status)) => Ok(Some(status)),
+        Ok(Err(e)) => Err(ClaudeError::Process(ProcessError::new(
+            format!("Failed to wait for process: {}", e),
+            None,
+            None,
+        ))),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Outcome of [`read_bounded_line`], with the number of bytes consumed
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum BoundedLine {
+    /// End of output
+    Eof,
+    /// A complete line (or the unterminated last line) is in the buffer
+    Line(usize),
+    /// The line was longer than the limit and has been discarded
+    TooLong(usize),
+}
+
+/// Read one line into `buf`, holding at most `limit` bytes of it in memory
+///
+/// An oversized line is consumed up to its newline without being buffered, so one
+/// huge message cannot exhaust memory or break framing for the lines after it.
+pub(crate) async fn read_bounded_line<R>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    limit: usize,
+) -> std::io::Result<BoundedLine>
+where
+    R: AsyncBufRead + Unpin,
+{
+    buf.clear();
+    let mut consumed = 0;
+    let mut too_long = false;
+
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Ok(match (consumed, too_long) {
+                (0, _) => BoundedLine::Eof,
+                (_, true) => BoundedLine::TooLong(consumed),
+                (_, false) => BoundedLine::Line(consumed),
+            });
+        }
+
+        let newline = available.iter().position(|&b| b == b'\n');
+        let chunk = newline.map_or(available.len(), |i| i + 1);
+        if !too_long {
+            let content = buf.len() + chunk - usize::from(newline.is_some());
+            if content > limit {
+                too_long = true;
+                buf.clear();
+            } else {
+                buf.extend_from_slice(&available[..chunk]);
+            }
+        }
+        reader.consume(chunk);
+        consumed += chunk;
+
+        if newline.is_some() {
+            return Ok(if too_long {
+                BoundedLine::TooLong(consumed)
+            } else {
+                BoundedLine::Line(consumed)
+            });
+        }
+    }
+}
+
+/// Decode a line read by [`read_bounded_line`]; blank lines and EOF yield None
+///
+/// Oversized and non-UTF-8 lines become errors the caller can skip, like stray
+/// non-JSON output.
+pub(crate) fn decode_line(
+    read: &BoundedLine,
+    line: &[u8],
+    limit: usize,
+) -> Option<Result<serde_json::Value>> {
+    match read {
+        BoundedLine::Eof => None,
+        BoundedLine::TooLong(bytes) => {
+            Some(Err(BufferSizeExceededError::new(*bytes, limit).into()))
+        }
+        BoundedLine::Line(_) => match std::str::from_utf8(line) {
+            Ok(text) => parse_json_line(text),
+            Err(e) => Some(Err(ClaudeError::JsonDecode(JsonDecodeError::new(
+                format!("Invalid UTF-8 in CLI output: {}", e),
+                String::from_utf8_lossy(line),
+            )))),
+        },
+    }
+}
+
+/// Decode one line of CLI output; blank lines yield `None`
+pub(crate) fn parse_json_line(line: &str) -> Option<Result<serde_json::Value>> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    Some(
+        serde_json::from_str::<serde_json::Value>(trimmed).map_err(|e| {
+            ClaudeError::JsonDecode(JsonDecodeError::new(
+                format!("Failed to parse JSON: {}", e),
+                trimmed.to_string(),
+            ))
+        }),
+    )
+}
+
 #[async_trait]
 impl Transport for SubprocessTransport {
     async fn connect(&self) -> Result<()> {
@@ -669,7 +969,7 @@ impl Transport for SubprocessTransport {
         let env = self.build_env();
 
         // Build command (hidden console window on Windows)
-        let mut cmd = create_async_hidden_command(&self.cli_path);
+        let mut cmd = create_async_hidden_command(self.cli_path());
         cmd.args(&args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
@@ -680,6 +980,9 @@ impl Transport for SubprocessTransport {
             cmd.current_dir(cwd);
         }
 
+        apply_process_isolation(&mut cmd, &self.options);
+        apply_process_limits(&mut cmd, &self.options.process_limits)?;
+
         // Spawn process
         let mut child = cmd.spawn().map_err(|e| {
             ClaudeError::Process(ProcessError::new(
@@ -688,6 +991,7 @@ impl Transport for SubprocessTransport {
                 None,
             ))
         })?;
+        let _ = self.started_at.set(SystemTime::now());
 
         // Take stdin and stdout
         let stdin = child.stdin.take().ok_or_else(|| {
@@ -775,51 +1079,40 @@ impl Transport for SubprocessTransport {
     fn read_messages(&self) -> Pin<Box<dyn Stream<Item = Result<serde_json::Value>> + Send + '_>> {
         let stdout = Arc::clone(&self.stdout);
         let max_buffer_size = self.max_buffer_size;
+        let max_output_bytes = self.max_output_bytes;
 
         Box::pin(async_stream::stream! {
             let mut stdout_guard = stdout.lock().await;
             if let Some(ref mut reader) = *stdout_guard {
-                let mut line = String::new();
-                let mut buffer_size = 0;
+                let mut line = Vec::new();
+                let mut output_bytes: u64 = 0;
 
                 loop {
-                    line.clear();
-                    match reader.read_line(&mut line).await {
-                        Ok(0) => {
-                            // EOF
-                            break;
-                        }
-                        Ok(n) => {
-                            buffer_size += n;
-                            if buffer_size > max_buffer_size {
-                                yield Err(ClaudeError::Transport(format!(
-                                    "Buffer size exceeded maximum of {} bytes",
-                                    max_buffer_size
-                                )));
-                                break;
-                            }
-
-                            let trimmed = line.trim();
-                            if trimmed.is_empty() {
-                                continue;
-                            }
-
-                            match serde_json::from_str::<serde_json::Value>(trimmed) {
-                                Ok(json) => {
-                                    yield Ok(json);
-                                }
-                                Err(e) => {
-                                    yield Err(ClaudeError::JsonDecode(JsonDecodeError::new(
-                                        format!("Failed to parse JSON: {}", e),
-                                        trimmed.to_string(),
-                                    )));
-                                }
-                            }
-                        }
+                    let read = match read_bounded_line(reader, &mut line, max_buffer_size).await {
+                        Ok(read) => read,
                         Err(e) => {
                             yield Err(ClaudeError::Transport(format!("Failed to read line: {}", e)));
                             break;
                         }
+                    };
+
+                    let bytes = match read {
+                        BoundedLine::Eof => break,
+                        BoundedLine::Line(bytes) | BoundedLine::TooLong(bytes) => bytes,
+                    };
+                    output_bytes += bytes as u64;
+                    if let Some(budget) = max_output_bytes
+                        && output_bytes > budget
+                    {
+                        yield Err(ClaudeError::Transport(format!(
+                            "Output budget exceeded maximum of {} bytes",
+                            budget
+                        )));
+                        break;
+                    }
+
+                    if let Some(result) = decode_line(&read, &line, max_buffer_size) {
+                        yield result;
                     }
                 }
             }
@@ -827,37 +1120,67 @@ impl Transport for SubprocessTransport {
     }
 
     async fn close(&self) -> Result<()> {
-        // Close stdin
+        self.shutdown().await.map(|_| ())
+    }
+
+    async fn shutdown(&self) -> Result<ShutdownReport> {
+        let started = Instant::now();
+
+        // Close stdin so the CLI can exit on its own
         if let Some(mut stdin) = self.stdin.lock().await.take() {
             let _ = stdin.shutdown().await;
         }
 
-        // Wait for process to exit
         // Take the process out of the mutex and drop the guard before awaiting
         let process_opt = self.process.lock().unwrap().take();
-        if let Some(mut process) = process_opt {
-            let status = process.wait().await.map_err(|e| {
-                ClaudeError::Process(ProcessError::new(
-                    format!("Failed to wait for process: {}", e),
-                    None,
-                    None,
-                ))
-            })?;
-
-            // Note: Claude CLI may exit with non-zero status (e.g., exit code 1) even after
-            // successfully completing a query. This is normal behavior - the Result message
-            // is the authoritative indicator of success/failure, not the exit code.
-            // We log a debug warning but don't fail here.
-            if !status.success() {
-                warn!(
-                    "Claude CLI exited with non-zero status (exit code {:?}). This is often normal.",
-                    status.code()
-                );
+        let report = match process_opt {
+            Some(mut process) => {
+                let (stage, exit_status) = stop_process(
+                    &mut process,
+                    &self.options.shutdown_policy,
+                    self.options.process_isolation.owns_group(),
+                )
+                .await?;
+                ShutdownReport {
+                    stage,
+                    exit_status,
+                    elapsed: started.elapsed(),
+                }
             }
+            None => ShutdownReport::default(),
+        };
+
+        // Note: Claude CLI may exit with non-zero status (e.g., exit code 1) even after
+        // successfully completing a query. This is normal behavior - the Result message
+        // is the authoritative indicator of success/failure, not the exit code.
+        // We log a debug warning but don't fail here.
+        if let Some(status) = report.exit_status
+            && !status.success()
+        {
+            warn!(
+                "Claude CLI exited with non-zero status (exit code {:?}). This is often normal.",
+                status.code()
+            );
+        }
+        if report.stage == ShutdownStage::Unresponsive {
+            warn!(
+                "Claude CLI did not exit within {:?} of SIGKILL",
+                self.options.shutdown_policy.kill_timeout
+            );
         }
 
         self.ready.store(false, Ordering::SeqCst);
-        Ok(())
+        Ok(report)
+    }
+
+    fn process_info(&self) -> Option<ProcessInfo> {
+        let pid = self.process.lock().ok()?.as_ref()?.id()?;
+        Some(ProcessInfo {
+            pid,
+            started_at: *self.started_at.get()?,
+            cli_path: self.cli_path().to_path_buf(),
+            cli_version: self.cli_version.get().cloned(),
+        })
     }
 
     fn is_ready(&self) -> bool {
@@ -880,7 +1203,54 @@ impl Drop for SubprocessTransport {
         if let Ok(mut guard) = self.process.lock()
             && let Some(mut process) = guard.take()
         {
+            #[cfg(unix)]
+            if self.options.process_isolation.owns_group()
+                && let Some(pid) = process.id()
+            {
+                send_signal(pid, libc::SIGKILL, true);
+            }
             let _ = process.start_kill();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_bounded_line_skips_only_the_oversized_line() {
+        let input = format!("{{\"a\":1}}\n{}\n{{\"b\":2}}", "x".repeat(40));
+        let mut reader = BufReader::with_capacity(4, input.as_bytes());
+        let mut buf = Vec::new();
+
+        let first = read_bounded_line(&mut reader, &mut buf, 16).await.unwrap();
+        assert_eq!(first, BoundedLine::Line(8));
+        assert_eq!(buf, b"{\"a\":1}\n");
+
+        let second = read_bounded_line(&mut reader, &mut buf, 16).await.unwrap();
+        assert_eq!(second, BoundedLine::TooLong(41));
+
+        let third = read_bounded_line(&mut reader, &mut buf, 16).await.unwrap();
+        assert_eq!(third, BoundedLine::Line(7));
+        assert_eq!(buf, b"{\"b\":2}");
+
+        let end = read_bounded_line(&mut reader, &mut buf, 16).await.unwrap();
+        assert_eq!(end, BoundedLine::Eof);
+    }
+
+    #[tokio::test]
+    async fn test_read_bounded_line_limit_excludes_newline() {
+        let mut reader = BufReader::new("12345\n123456\n".as_bytes());
+        let mut buf = Vec::new();
+
+        assert_eq!(
+            read_bounded_line(&mut reader, &mut buf, 5).await.unwrap(),
+            BoundedLine::Line(6)
+        );
+        assert_eq!(
+            read_bounded_line(&mut reader, &mut buf, 5).await.unwrap(),
+            BoundedLine::TooLong(7)
+        );
+    }
+}
@@ -5,6 +5,7 @@ use futures::stream::Stream;
 use std::pin::Pin;
 
 use crate::errors::Result;
+use crate::types::process::{ProcessInfo, ShutdownReport};
 
 /// Transport trait for communicating with Claude Code CLI
 ///
@@ -25,6 +26,24 @@ pub trait Transport: Send + Sync {
     /// Close the transport
     async fn close(&self) -> Result<()>;
 
+    /// Close the transport and report how the underlying process stopped
+    ///
+    /// Transports without a process report [`ShutdownStage::NoProcess`](crate::ShutdownStage::NoProcess).
+    async fn shutdown(&self) -> Result<ShutdownReport> {
+        self.close().await?;
+        Ok(ShutdownReport::default())
+    }
+
+    /// The underlying CLI process, if there is one and it is running
+    fn process_info(&self) -> Option<ProcessInfo> {
+        None
+    }
+
+    /// PID of the underlying CLI process, if there is one and it is running
+    fn process_id(&self) -> Option<u32> {
+        self.process_info().map(|info| info.pid)
+    }
+
     /// Check if the transport is ready
     #[allow(dead_code)]
     fn is_ready(&self) -> bool;
@@ -233,9 +233,12 @@
 //! - [Plugin Guide](https://github.com/yourusername/claude-agent-sdk-rs/blob/master/PLUGIN_GUIDE.md) - Plugin development
 //! - [Examples](https://github.com/yourusername/claude-agent-sdk-rs/tree/master/examples) - 22 working examples
 
+pub mod cli;
 pub mod client;
 pub mod errors;
 mod internal;
+pub mod profile;
+pub mod project_config;
 pub mod query;
 #[cfg(feature = "testing")]
 pub mod testing;
@@ -243,18 +246,33 @@ pub mod types;
 pub mod version;
 
 // Re-export commonly used types
-pub use errors::{ClaudeError, ImageValidationError, Result};
+pub use errors::{
+    BufferSizeExceededError, ClaudeError, CliVersionError, ImageValidationError,
+    OptionsValidationError, PluginError, ProfileError, Result,
+};
 pub use types::{
+    agents::{load_agent_file, load_agents_dir, validate_agent_tools},
     config::*,
     efficiency::{EfficiencyConfig, ExecutionMetrics, MetricsSummary},
     hooks::*,
+    invocation::CliInvocation,
     mcp::{
-        McpServerConfig, McpServers, SdkMcpServer, SdkMcpTool, ToolHandler, ToolResult,
-        ToolResultContent as McpToolResultContent, create_sdk_mcp_server,
+        CancellationSignal, McpLogLevel, McpNotificationSink, McpRequestContext, McpServerConfig,
+        McpServers, McpStdioServerConfig, SdkMcpServer, SdkMcpTool, ToolContext, ToolHandler,
+        ToolResult, ToolResultContent as McpToolResultContent, create_sdk_mcp_server,
+    },
+    mcp_proxy::{
+        McpArgumentRewriter, McpCallObserver, McpProxyCall, McpProxyHooks, McpToolFilter,
+        StdioMcpProxy, create_stdio_mcp_proxy,
     },
     messages::*,
     permissions::*,
     plugin::*,
+    process::{
+        ClientStatus, ProcessInfo, ProcessIsolation, ProcessLimits, ResourceUsage, ShutdownPolicy,
+        ShutdownReport, ShutdownStage,
+    },
+    validation::{IssueSeverity, OptionIssue, OptionIssueKind},
 };
 
 // Re-export public API
@@ -0,0 +1,1025 @@
+//! Serializable agent profiles loaded from TOML or JSON
+//!
+//! [`ClaudeAgentOptions`] holds callbacks and cannot be (de)serialized. [`AgentProfile`]
+//! mirrors all of its data fields so that options can live in configuration files;
+//! callbacks (`can_use_tool`, `hooks`, `stderr_callback`) and in-process SDK MCP servers
+//! are attached after conversion.
+//!
+//! A profile file holds any number of named profiles under `profiles`. Top-level keys
+//! are the defaults every profile starts from: tables are merged key by key and other
+//! values are replaced. A file without `profiles` is a single, default profile.
+//!
+//! ```toml
+//! model = "claude-sonnet-4-5"
+//! permission_mode = "acceptEdits"
+//!
+//! [env]
+//! ANTHROPIC_API_KEY = "${ANTHROPIC_API_KEY}"
+//!
+//! [profiles.reviewer]
+//! max_turns = 5
+//! allowed_tools = ["Read", "Grep"]
+//!
+//! [profiles.reviewer.mcp_servers.github]
+//! type = "http"
+//! url = "https://api.githubcopilot.com/mcp/"
+//! headers = { Authorization = "Bearer ${GITHUB_TOKEN}" }
+//! ```
+//!
+//! Strings may reference environment variables as `${VAR}`, or `${VAR:-fallback}` to use
+//! a fallback when the variable is unset or empty; `$$` is a literal `$`. References are
+//! resolved when a profile is selected, so only the selected profile needs its variables.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use claude_agent_sdk_rs::ClaudeAgentOptions;
+//! use claude_agent_sdk_rs::profile::AgentProfiles;
+//!
+//! # fn main() -> claude_agent_sdk_rs::Result<()> {
+//! let profiles = AgentProfiles::load("agents.toml")?;
+//! let options = ClaudeAgentOptions {
+//!     stderr_callback: Some(Arc::new(|line| eprintln!("{line}"))),
+//!     ..profiles.profile("reviewer")?.into_options()?
+//! };
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::cli::CliVersion;
+use crate::errors::ProfileError;
+use crate::types::config::{
+    AgentDefinition, ClaudeAgentOptions, PermissionMode, SandboxSettings, SdkBeta, SettingSource,
+    SystemPrompt, Tools,
+};
+use crate::types::efficiency::EfficiencyConfig;
+use crate::types::mcp::{
+    McpHttpServerConfig, McpServerConfig, McpServers, McpSseServerConfig, McpStdioServerConfig,
+};
+use crate::types::plugin::SdkPluginConfig;
+use crate::types::process::{ProcessIsolation, ProcessLimits, ShutdownPolicy};
+
+/// Key holding the named profiles in a profile file
+const PROFILES_KEY: &str = "profiles";
+
+/// Name used in errors for the top-level profile
+const DEFAULT_PROFILE: &str = "default";
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+/// Serializable mirror of the data fields of [`ClaudeAgentOptions`]
+///
+/// Field names match [`ClaudeAgentOptions`]; unknown fields are rejected.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AgentProfile {
+    /// Model to use
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Fallback model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_model: Option<String>,
+    /// Beta features to enable
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub betas: Vec<SdkBeta>,
+    /// Maximum tokens for thinking blocks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_thinking_tokens: Option<u32>,
+    /// Maximum number of turns
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_turns: Option<u32>,
+    /// Maximum spending limit in USD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_budget_usd: Option<f64>,
+    /// System prompt, as text or a `{type = "preset", preset = "claude_code"}` table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<SystemPrompt>,
+    /// Base set of tools, as a list or a preset table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Tools>,
+    /// Allowed tools
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_tools: Vec<String>,
+    /// Disallowed tools
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub disallowed_tools: Vec<String>,
+    /// Permission mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionMode>,
+    /// Permission prompt tool name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission_prompt_tool_name: Option<String>,
+    /// MCP servers by name
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub mcp_servers: HashMap<String, ProfileMcpServer>,
+    /// Path to an MCP servers configuration file, instead of `mcp_servers`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mcp_config: Option<PathBuf>,
+    /// Continue the most recent conversation
+    #[serde(skip_serializing_if = "is_false")]
+    pub continue_conversation: bool,
+    /// Session ID to resume
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resume: Option<String>,
+    /// Fork the resumed session
+    #[serde(skip_serializing_if = "is_false")]
+    pub fork_session: bool,
+    /// Working directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// Custom path to the Claude Code CLI
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cli_path: Option<PathBuf>,
+    /// Settings file path or inline JSON
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<String>,
+    /// Additional directories to add
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub add_dirs: Vec<PathBuf>,
+    /// Environment variables for the CLI
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Extra CLI arguments
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub extra_args: HashMap<String, ExtraArg>,
+    /// Maximum size of a single line of CLI output, in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_buffer_size: Option<usize>,
+    /// Maximum cumulative CLI output per session, in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<u64>,
+    /// Shutdown deadlines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shutdown_policy: Option<ProfileShutdownPolicy>,
+    /// Process group of the CLI: `inherit`, `process_group` or `session`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_isolation: Option<ProcessIsolation>,
+    /// Kill the CLI when this process dies (Linux only)
+    #[serde(skip_serializing_if = "is_false")]
+    pub kill_on_parent_death: bool,
+    /// Resource limits for the CLI process
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_limits: Option<ProfileProcessLimits>,
+    /// User identifier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Include partial messages in the stream
+    #[serde(skip_serializing_if = "is_false")]
+    pub include_partial_messages: bool,
+    /// Enable verbose CLI output; `None` keeps the default (`true`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verbose: Option<bool>,
+    /// Custom agent definitions by name
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub agents: HashMap<String, AgentDefinition>,
+    /// Setting sources to load: `user`, `project`, `local`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub setting_sources: Option<Vec<SettingSource>>,
+    /// Sandbox configuration, using the CLI's camelCase keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxSettings>,
+    /// Plugins to load, e.g. `{type = "local", path = "./plugin"}`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<SdkPluginConfig>,
+    /// Structured output format
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<Value>,
+    /// Enable file checkpointing
+    #[serde(skip_serializing_if = "is_false")]
+    pub enable_file_checkpointing: bool,
+    /// Skip the CLI version check
+    #[serde(skip_serializing_if = "is_false")]
+    pub skip_version_check: bool,
+    /// Lowest acceptable CLI version, e.g. `"2.0.0"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_cli_version: Option<CliVersion>,
+    /// Highest acceptable CLI version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cli_version: Option<CliVersion>,
+    /// Built-in efficiency hooks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub efficiency: Option<ProfileEfficiency>,
+}
+
+/// An external MCP server; in-process SDK servers are attached programmatically
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ProfileMcpServer {
+    /// Stdio-based MCP server
+    Stdio(McpStdioServerConfig),
+    /// SSE-based MCP server
+    Sse(McpSseServerConfig),
+    /// HTTP-based MCP server
+    Http(McpHttpServerConfig),
+}
+
+impl From<ProfileMcpServer> for McpServerConfig {
+    fn from(server: ProfileMcpServer) -> Self {
+        match server {
+            ProfileMcpServer::Stdio(config) => McpServerConfig::Stdio(config),
+            ProfileMcpServer::Sse(config) => McpServerConfig::Sse(config),
+            ProfileMcpServer::Http(config) => McpServerConfig::Http(config),
+        }
+    }
+}
+
+/// Value of an extra CLI argument
+///
+/// TOML has no null, so a flag without a value is written as `true`; `false` omits it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ExtraArg {
+    /// Pass the flag without a value when `true`
+    Flag(bool),
+    /// Pass the flag with this value
+    Value(String),
+}
+
+/// [`ShutdownPolicy`] with durations in milliseconds; unset fields keep their defaults
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileShutdownPolicy {
+    /// Time to wait for the CLI to exit after stdin is closed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdin_grace_ms: Option<u64>,
+    /// Time to wait after `SIGTERM`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub term_timeout_ms: Option<u64>,
+    /// Time to wait after `SIGKILL`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kill_timeout_ms: Option<u64>,
+}
+
+impl From<ProfileShutdownPolicy> for ShutdownPolicy {
+    fn from(profile: ProfileShutdownPolicy) -> Self {
+        let defaults = ShutdownPolicy::default();
+        let or_default = |ms: Option<u64>, default| ms.map_or(default, Duration::from_millis);
+        ShutdownPolicy {
+            stdin_grace: or_default(profile.stdin_grace_ms, defaults.stdin_grace),
+            term_timeout: or_default(profile.term_timeout_ms, defaults.term_timeout),
+            kill_timeout: or_default(profile.kill_timeout_ms, defaults.kill_timeout),
+        }
+    }
+}
+
+impl From<&ShutdownPolicy> for ProfileShutdownPolicy {
+    fn from(policy: &ShutdownPolicy) -> Self {
+        let millis = |d: Duration| Some(d.as_millis() as u64);
+        Self {
+            stdin_grace_ms: millis(policy.stdin_grace),
+            term_timeout_ms: millis(policy.term_timeout),
+            kill_timeout_ms: millis(policy.kill_timeout),
+        }
+    }
+}
+
+/// [`ProcessLimits`] with the CPU time in seconds
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileProcessLimits {
+    /// Maximum virtual address space in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_address_space: Option<u64>,
+    /// Maximum CPU time in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cpu_time_secs: Option<u64>,
+    /// Maximum number of open file descriptors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_open_files: Option<u64>,
+    /// Nice level from -20 to 19
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i32>,
+    /// Existing cgroup v2 directory to place the process in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cgroup: Option<PathBuf>,
+}
+
+impl From<ProfileProcessLimits> for ProcessLimits {
+    fn from(profile: ProfileProcessLimits) -> Self {
+        ProcessLimits {
+            max_address_space: profile.max_address_space,
+            max_cpu_time: profile.max_cpu_time_secs.map(Duration::from_secs),
+            max_open_files: profile.max_open_files,
+            nice: profile.nice,
+            cgroup: profile.cgroup,
+        }
+    }
+}
+
+impl From<&ProcessLimits> for ProfileProcessLimits {
+    fn from(limits: &ProcessLimits) -> Self {
+        Self {
+            max_address_space: limits.max_address_space,
+            max_cpu_time_secs: limits.max_cpu_time.map(|d| d.as_secs_f64().ceil() as u64),
+            max_open_files: limits.max_open_files,
+            nice: limits.nice,
+            cgroup: limits.cgroup.clone(),
+        }
+    }
+}
+
+/// Settings of [`EfficiencyConfig`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileEfficiency {
+    /// Inject working directory reminder in UserPromptSubmit hook
+    pub inject_cwd_reminder: bool,
+    /// Inject efficiency tips in Stop hook
+    pub inject_stop_tips: bool,
+    /// Track execution metrics and provide specific warnings
+    pub track_metrics: bool,
+    /// Working directory to remind about (uses cwd from options if not set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+}
+
+impl From<ProfileEfficiency> for EfficiencyConfig {
+    fn from(profile: ProfileEfficiency) -> Self {
+        let mut config = EfficiencyConfig::default();
+        if profile.track_metrics {
+            config = config.enable_metrics();
+        }
+        config.inject_cwd_reminder = profile.inject_cwd_reminder;
+        config.inject_stop_tips = profile.inject_stop_tips;
+        config.cwd = profile.cwd;
+        config
+    }
+}
+
+impl From<&EfficiencyConfig> for ProfileEfficiency {
+    fn from(config: &EfficiencyConfig) -> Self {
+        Self {
+            inject_cwd_reminder: config.inject_cwd_reminder,
+            inject_stop_tips: config.inject_stop_tips,
+            track_metrics: config.track_metrics,
+            cwd: config.cwd.clone(),
+        }
+    }
+}
+
+impl AgentProfile {
+    /// Load a profile file and resolve one profile, `None` selecting the defaults
+    ///
+    /// Shorthand for [`AgentProfiles::load`] followed by [`AgentProfiles::resolve`].
+    pub fn load(path: impl AsRef<Path>, name: Option<&str>) -> Result<Self, ProfileError> {
+        AgentProfiles::load(path)?.resolve(name)
+    }
+
+    /// Convert into [`ClaudeAgentOptions`]
+    ///
+    /// Callbacks and SDK MCP servers are unset; assign them on the result. Fails if
+    /// both `mcp_servers` and `mcp_config` are set. Resolved profiles are checked by
+    /// [`AgentProfiles::resolve`], which names the profile; a profile built in code is
+    /// reported as `default`.
+    pub fn into_options(self) -> Result<ClaudeAgentOptions, ProfileError> {
+        self.check(DEFAULT_PROFILE)?;
+        let mcp_servers = match self.mcp_config {
+            Some(path) => McpServers::Path(path),
+            None if self.mcp_servers.is_empty() => McpServers::Empty,
+            None => McpServers::Dict(
+                self.mcp_servers
+                    .into_iter()
+                    .map(|(name, server)| (name, server.into()))
+                    .collect(),
+            ),
+        };
+        let extra_args = self
+            .extra_args
+            .into_iter()
+            .filter_map(|(flag, value)| match value {
+                ExtraArg::Flag(true) => Some((flag, None)),
+                ExtraArg::Flag(false) => None,
+                ExtraArg::Value(value) => Some((flag, Some(value))),
+            })
+            .collect();
+
+        Ok(ClaudeAgentOptions {
+            tools: self.tools,
+            allowed_tools: self.allowed_tools,
+            system_prompt: self.system_prompt,
+            mcp_servers,
+            permission_mode: self.permission_mode,
+            continue_conversation: self.continue_conversation,
+            resume: self.resume,
+            max_turns: self.max_turns,
+            disallowed_tools: self.disallowed_tools,
+            model: self.model,
+            fallback_model: self.fallback_model,
+            betas: self.betas,
+            max_budget_usd: self.max_budget_usd,
+            max_thinking_tokens: self.max_thinking_tokens,
+            permission_prompt_tool_name: self.permission_prompt_tool_name,
+            cwd: self.cwd,
+            cli_path: self.cli_path,
+            settings: self.settings,
+            add_dirs: self.add_dirs,
+            env: self.env,
+            extra_args,
+            max_buffer_size: self.max_buffer_size,
+            max_output_bytes: self.max_output_bytes,
+            shutdown_policy: self.shutdown_policy.map(Into::into).unwrap_or_default(),
+            process_isolation: self.process_isolation.unwrap_or_default(),
+            kill_on_parent_death: self.kill_on_parent_death,
+            process_limits: self.process_limits.map(Into::into).unwrap_or_default(),
+            user: self.user,
+            include_partial_messages: self.include_partial_messages,
+            verbose: self.verbose.unwrap_or(true),
+            fork_session: self.fork_session,
+            agents: (!self.agents.is_empty()).then_some(self.agents),
+            setting_sources: self.setting_sources,
+            sandbox: self.sandbox,
+            plugins: self.plugins,
+            output_format: self.output_format,
+            enable_file_checkpointing: self.enable_file_checkpointing,
+            skip_version_check: self.skip_version_check,
+            min_cli_version: self.min_cli_version,
+            max_cli_version: self.max_cli_version,
+            efficiency: self.efficiency.map(Into::into),
+            ..Default::default()
+        })
+    }
+
+    /// Reject field combinations the schema cannot express, reporting them for `name`
+    fn check(&self, name: &str) -> Result<(), ProfileError> {
+        if !self.mcp_servers.is_empty() && self.mcp_config.is_some() {
+            return Err(ProfileError::Invalid {
+                profile: name.to_string(),
+                message: "`mcp_servers` and `mcp_config` cannot both be set".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Capture the data fields of existing options
+    ///
+    /// Callbacks and SDK MCP servers are dropped.
+    pub fn from_options(options: &ClaudeAgentOptions) -> Self {
+        let (mcp_servers, mcp_config) = match &options.mcp_servers {
+            McpServers::Empty => (HashMap::new(), None),
+            McpServers::Path(path) => (HashMap::new(), Some(path.clone())),
+            McpServers::Dict(servers) => {
+                let servers = servers
+                    .iter()
+                    .filter_map(|(name, server)| {
+                        let server = match server {
+                            McpServerConfig::Stdio(c) => ProfileMcpServer::Stdio(c.clone()),
+                            McpServerConfig::Sse(c) => ProfileMcpServer::Sse(c.clone()),
+                            McpServerConfig::Http(c) => ProfileMcpServer::Http(c.clone()),
+                            McpServerConfig::Sdk(_) => return None,
+                        };
+                        Some((name.clone(), server))
+                    })
+                    .collect();
+                (servers, None)
+            }
+        };
+        let extra_args = options
+            .extra_args
+            .iter()
+            .map(|(flag, value)| {
+                let value = match value {
+                    Some(value) => ExtraArg::Value(value.clone()),
+                    None => ExtraArg::Flag(true),
+                };
+                (flag.clone(), value)
+            })
+            .collect();
+
+        Self {
+            model: options.model.clone(),
+            fallback_model: options.fallback_model.clone(),
+            betas: options.betas.clone(),
+            max_thinking_tokens: options.max_thinking_tokens,
+            max_turns: options.max_turns,
+            max_budget_usd: options.max_budget_usd,
+            system_prompt: options.system_prompt.clone(),
+            tools: options.tools.clone(),
+            allowed_tools: options.allowed_tools.clone(),
+            disallowed_tools: options.disallowed_tools.clone(),
+            permission_mode: options.permission_mode,
+            permission_prompt_tool_name: options.permission_prompt_tool_name.clone(),
+            mcp_servers,
+            mcp_config,
+            continue_conversation: options.continue_conversation,
+            resume: options.resume.clone(),
+            fork_session: options.fork_session,
+            cwd: options.cwd.clone(),
+            cli_path: options.cli_path.clone(),
+            settings: options.settings.clone(),
+            add_dirs: options.add_dirs.clone(),
+            env: options.env.clone(),
+            extra_args,
+            max_buffer_size: options.max_buffer_size,
+            max_output_bytes: options.max_output_bytes,
+            shutdown_policy: (options.shutdown_policy != ShutdownPolicy::default())
+                .then(|| (&options.shutdown_policy).into()),
+            process_isolation: (options.process_isolation != ProcessIsolation::default())
+                .then_some(options.process_isolation),
+            kill_on_parent_death: options.kill_on_parent_death,
+            process_limits: (!options.process_limits.is_empty())
+                .then(|| (&options.process_limits).into()),
+            user: options.user.clone(),
+            include_partial_messages: options.include_partial_messages,
+            verbose: (!options.verbose).then_some(false),
+            agents: options.agents.clone().unwrap_or_default(),
+            setting_sources: options.setting_sources.clone(),
+            sandbox: options.sandbox.clone(),
+            plugins: options.plugins.clone(),
+            output_format: options.output_format.clone(),
+            enable_file_checkpointing: options.enable_file_checkpointing,
+            skip_version_check: options.skip_version_check,
+            min_cli_version: options.min_cli_version,
+            max_cli_version: options.max_cli_version,
+            efficiency: options.efficiency.as_ref().map(Into::into),
+        }
+    }
+}
+
+/// The contents of a profile file: defaults plus named profiles
+///
+/// Profiles are kept as raw documents until [`resolve`](Self::resolve)d, which merges
+/// them over the defaults, expands `${VAR}` references and checks the schema.
+#[derive(Debug, Clone, Default)]
+pub struct AgentProfiles {
+    defaults: Map<String, Value>,
+    profiles: BTreeMap<String, Value>,
+}
+
+impl AgentProfiles {
+    /// Load a profile file, choosing the format from the `.toml` or `.json` extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProfileError> {
+        let path = path.as_ref();
+        let is_toml = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => true,
+            Some("json") => false,
+            _ => {
+                return Err(ProfileError::UnsupportedFormat {
+                    path: path.to_path_buf(),
+                });
+            }
+        };
+        let content = std::fs::read_to_string(path).map_err(|source| ProfileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        if is_toml {
+            Self::from_toml_str(&content)
+        } else {
+            Self::from_json_str(&content)
+        }
+    }
+
+    /// Parse a TOML profile document
+    pub fn from_toml_str(content: &str) -> Result<Self, ProfileError> {
+        let document: Value =
+            toml::from_str(content).map_err(|e| ProfileError::Parse(e.to_string()))?;
+        Self::from_document(document)
+    }
+
+    /// Parse a JSON profile document
+    pub fn from_json_str(content: &str) -> Result<Self, ProfileError> {
+        let document: Value =
+            serde_json::from_str(content).map_err(|e| ProfileError::Parse(e.to_string()))?;
+        Self::from_document(document)
+    }
+
+    fn from_document(document: Value) -> Result<Self, ProfileError> {
+        let Value::Object(mut defaults) = document else {
+            return Err(ProfileError::Parse(
+                "the document must be a table of settings".to_string(),
+            ));
+        };
+        let profiles = match defaults.remove(PROFILES_KEY) {
+            None => BTreeMap::new(),
+            Some(Value::Object(profiles)) => profiles.into_iter().collect(),
+            Some(_) => {
+                return Err(ProfileError::Parse(format!(
+                    "`{}` must be a table of named profiles",
+                    PROFILES_KEY
+                )));
+            }
+        };
+        if let Some((name, _)) = profiles.iter().find(|(_, profile)| !profile.is_object()) {
+            return Err(ProfileError::Parse(format!(
+                "profile '{}' must be a table",
+                name
+            )));
+        }
+        Ok(Self { defaults, profiles })
+    }
+
+    /// Names of the profiles in the file, sorted
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Whether a profile with this name exists
+    pub fn contains(&self, name: &str) -> bool {
+        self.profiles.contains_key(name)
+    }
+
+    /// Resolve the top-level defaults alone, reading variables from the environment
+    pub fn default_profile(&self) -> Result<AgentProfile, ProfileError> {
+        self.resolve(None)
+    }
+
+    /// Resolve a named profile, reading variables from the environment
+    pub fn profile(&self, name: &str) -> Result<AgentProfile, ProfileError> {
+        self.resolve(Some(name))
+    }
+
+    /// Resolve a named profile, or the defaults for `None`, reading variables from the
+    /// environment
+    pub fn resolve(&self, name: Option<&str>) -> Result<AgentProfile, ProfileError> {
+        self.resolve_with(name, |variable| std::env::var(variable).ok())
+    }
+
+    /// Resolve a profile, looking variables up with `lookup` instead of the environment
+    pub fn resolve_with(
+        &self,
+        name: Option<&str>,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<AgentProfile, ProfileError> {
+        let mut document = Value::Object(self.defaults.clone());
+        if let Some(name) = name {
+            let profile = self
+                .profiles
+                .get(name)
+                .ok_or_else(|| ProfileError::UnknownProfile {
+                    name: name.to_string(),
+                    available: self.profiles.keys().cloned().collect(),
+                })?;
+            merge(&mut document, profile.clone());
+        }
+        interpolate(&mut document, &mut String::new(), &lookup)?;
+        let name = name.unwrap_or(DEFAULT_PROFILE);
+        let profile: AgentProfile =
+            serde_json::from_value(document).map_err(|e| ProfileError::Invalid {
+                profile: name.to_string(),
+                message: e.to_string(),
+            })?;
+        profile.check(name)?;
+        Ok(profile)
+    }
+}
+
+/// Merge `overlay` into `base`: tables key by key, anything else replaced
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Expand `${VAR}` references in every string of `value`
+///
+/// `location` tracks the path to the current value for error messages.
+fn interpolate(
+    value: &mut Value,
+    location: &mut String,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<(), ProfileError> {
+    let len = location.len();
+    match value {
+        Value::String(s) => *s = expand(s, location, lookup)?,
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                location.push_str(&format!("[{}]", i));
+                interpolate(item, location, lookup)?;
+                location.truncate(len);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                if !location.is_empty() {
+                    location.push('.');
+                }
+                location.push_str(key);
+                interpolate(item, location, lookup)?;
+                location.truncate(len);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn expand(
+    s: &str,
+    location: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<String, ProfileError> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        if let Some(tail) = after.strip_prefix('$') {
+            out.push('$');
+            rest = tail;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end = body.find('}').ok_or_else(|| {
+                ProfileError::Parse(format!("unterminated `${{` at {}", location))
+            })?;
+            let (variable, fallback) = match body[..end].split_once(":-") {
+                Some((variable, fallback)) => (variable, Some(fallback)),
+                None => (&body[..end], None),
+            };
+            let resolved = match fallback {
+                Some(fallback) => lookup(variable)
+                    .filter(|v| !v.is_empty())
+                    .unwrap_or_else(|| fallback.to_string()),
+                None => lookup(variable).ok_or_else(|| ProfileError::MissingVariable {
+                    variable: variable.to_string(),
+                    location: location.to_string(),
+                })?,
+            };
+            out.push_str(&resolved);
+            rest = &body[end + 1..];
+        } else {
+            out.push('$');
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    const PROFILES: &str = r#"
+model = "claude-sonnet-4-5"
+permission_mode = "acceptEdits"
+allowed_tools = ["Read"]
+
+[env]
+API_KEY = "${API_KEY}"
+REGION = "${REGION:-us-east-1}"
+
+[profiles.reviewer]
+max_turns = 5
+allowed_tools = ["Read", "Grep"]
+extra_args = { "replay-user-messages" = true, debug = false, "log-level" = "warn" }
+
+[profiles.reviewer.env]
+REVIEW = "on"
+
+[profiles.reviewer.mcp_servers.github]
+type = "http"
+url = "https://example.com/mcp"
+headers = { Authorization = "Bearer ${GITHUB_TOKEN}" }
+
+[profiles.reviewer.mcp_servers.files]
+type = "stdio"
+command = "mcp-files"
+args = ["--root", "$${HOME}"]
+
+[profiles.sandboxed]
+process_isolation = "session"
+min_cli_version = "2.0.0"
+shutdown_policy = { stdin_grace_ms = 500 }
+process_limits = { max_cpu_time_secs = 60, nice = 5 }
+plugins = [{ type = "local", path = "./plugin" }]
+sandbox = { enabled = true, excludedCommands = ["git"] }
+agents.tester = { description = "Runs tests", prompt = "Run the tests", model = "haiku" }
+"#;
+
+    #[test]
+    fn test_named_profile_merges_defaults_and_interpolates() {
+        let profiles = AgentProfiles::from_toml_str(PROFILES).unwrap();
+        assert_eq!(
+            profiles.names().collect::<Vec<_>>(),
+            ["reviewer", "sandboxed"]
+        );
+
+        let lookup = env(&[("API_KEY", "secret"), ("GITHUB_TOKEN", "gh")]);
+        let profile = profiles.resolve_with(Some("reviewer"), &lookup).unwrap();
+        assert_eq!(profile.model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(profile.max_turns, Some(5));
+        assert_eq!(profile.allowed_tools, ["Read", "Grep"]);
+        assert_eq!(profile.env["API_KEY"], "secret");
+        assert_eq!(profile.env["REGION"], "us-east-1");
+        assert_eq!(profile.env["REVIEW"], "on");
+
+        let options = profile.into_options().unwrap();
+        assert_eq!(options.permission_mode, Some(PermissionMode::AcceptEdits));
+        assert_eq!(options.extra_args.len(), 2);
+        assert_eq!(options.extra_args["replay-user-messages"], None);
+        assert_eq!(options.extra_args["log-level"].as_deref(), Some("warn"));
+        let McpServers::Dict(servers) = &options.mcp_servers else {
+            panic!("expected MCP servers");
+        };
+        let McpServerConfig::Http(github) = &servers["github"] else {
+            panic!("expected an HTTP server");
+        };
+        assert_eq!(
+            github.headers.as_ref().unwrap()["Authorization"],
+            "Bearer gh"
+        );
+        let McpServerConfig::Stdio(files) = &servers["files"] else {
+            panic!("expected a stdio server");
+        };
+        assert_eq!(files.args.as_ref().unwrap()[1], "${HOME}");
+    }
+
+    #[test]
+    fn test_process_and_agent_settings_convert() {
+        let profiles = AgentProfiles::from_toml_str(PROFILES).unwrap();
+        let profile = profiles
+            .resolve_with(Some("sandboxed"), env(&[("API_KEY", "k")]))
+            .unwrap();
+        let options = profile.into_options().unwrap();
+
+        assert_eq!(options.process_isolation, ProcessIsolation::Session);
+        assert_eq!(options.min_cli_version, Some(CliVersion::new(2, 0, 0)));
+        assert_eq!(
+            options.shutdown_policy.stdin_grace,
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            options.shutdown_policy.term_timeout,
+            ShutdownPolicy::default().term_timeout
+        );
+        assert_eq!(
+            options.process_limits.max_cpu_time,
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(options.process_limits.nice, Some(5));
+        assert_eq!(options.plugins, [SdkPluginConfig::local("./plugin")]);
+        let sandbox = options.sandbox.as_ref().unwrap();
+        assert_eq!(sandbox.enabled, Some(true));
+        assert_eq!(
+            sandbox.excluded_commands.as_deref(),
+            Some(&["git".to_string()][..])
+        );
+        assert_eq!(
+            options.agents.as_ref().unwrap()["tester"].prompt,
+            "Run the tests"
+        );
+        assert!(options.validate().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let profiles = AgentProfiles::from_toml_str(PROFILES).unwrap();
+
+        let err = profiles
+            .resolve_with(Some("missing"), env(&[]))
+            .unwrap_err();
+        assert!(matches!(
+            &err,
+            ProfileError::UnknownProfile { available, .. } if available.len() == 2
+        ));
+
+        let err = profiles.resolve_with(Some("reviewer"), env(&[("API_KEY", "k")]));
+        assert!(matches!(
+            err,
+            Err(ProfileError::MissingVariable { variable, location })
+                if variable == "GITHUB_TOKEN"
+                    && location == "mcp_servers.github.headers.Authorization"
+        ));
+
+        let typo = AgentProfiles::from_toml_str("[profiles.a]\nmax_turn = 3").unwrap();
+        assert!(matches!(
+            typo.resolve_with(Some("a"), env(&[])),
+            Err(ProfileError::Invalid { profile, .. }) if profile == "a"
+        ));
+
+        assert!(matches!(
+            AgentProfiles::from_toml_str("profiles = 3"),
+            Err(ProfileError::Parse(_))
+        ));
+        assert!(matches!(
+            AgentProfiles::load("agents.yaml"),
+            Err(ProfileError::UnsupportedFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_load_fixture() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/profiles/agents.toml");
+        let reviewer = AgentProfile::load(&path, Some("reviewer")).unwrap();
+        assert_eq!(reviewer.permission_mode, Some(PermissionMode::Plan));
+        assert_eq!(reviewer.setting_sources, Some(vec![SettingSource::Project]));
+        assert!(matches!(
+            reviewer.system_prompt,
+            Some(SystemPrompt::Preset(_))
+        ));
+
+        let writer = AgentProfile::load(&path, Some("writer")).unwrap();
+        assert_eq!(writer.max_budget_usd, Some(2.5));
+        assert!(writer.into_options().unwrap().validate().is_empty());
+    }
+
+    #[test]
+    fn test_expand() {
+        let lookup = env(&[("A", "1"), ("EMPTY", "")]);
+        let expand = |s: &str| expand(s, "x", &lookup);
+        assert_eq!(expand("${A}-${A}").unwrap(), "1-1");
+        assert_eq!(expand("${EMPTY:-d} ${B:-} $A $$").unwrap(), "d  $A $");
+        assert_eq!(expand("${EMPTY}").unwrap(), "");
+        assert!(matches!(expand("${A"), Err(ProfileError::Parse(_))));
+    }
+
+    #[test]
+    fn test_json_profile_round_trips_options() {
+        let options = ClaudeAgentOptions::builder()
+            .model("claude-opus-4")
+            .max_turns(3)
+            .extra_args(HashMap::from([("verbose-debug".to_string(), None)]))
+            .process_isolation(ProcessIsolation::ProcessGroup)
+            .build();
+        let profile = AgentProfile::from_options(&options);
+        let json = serde_json::to_string(&profile).unwrap();
+        assert!(!json.contains("null"));
+
+        let profiles = AgentProfiles::from_json_str(&json).unwrap();
+        let restored = profiles.default_profile().unwrap().into_options().unwrap();
+        assert_eq!(restored.model, options.model);
+        assert_eq!(restored.max_turns, Some(3));
+        assert_eq!(restored.extra_args, options.extra_args);
+        assert_eq!(restored.process_isolation, ProcessIsolation::ProcessGroup);
+        assert!(restored.verbose);
+
+        let toml = toml::to_string(&profile).unwrap();
+        let from_toml = AgentProfiles::from_toml_str(&toml).unwrap();
+        assert_eq!(from_toml.default_profile().unwrap().max_turns, Some(3));
+    }
+
+    #[test]
+    fn test_mcp_servers_and_config_conflict() {
+        let profile = AgentProfile {
+            mcp_config: Some(PathBuf::from("mcp.json")),
+            mcp_servers: HashMap::from([(
+                "a".to_string(),
+                ProfileMcpServer::Sse(McpSseServerConfig {
+                    url: "u".to_string(),
+                    headers: None,
+                }),
+            )]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            profile.into_options(),
+            Err(ProfileError::Invalid { .. })
+        ));
+
+        let profiles = AgentProfiles::from_json_str(
+            r#"{"profiles": {"both": {"mcp_config": "mcp.json", "mcp_servers": {"a": {"type": "sse", "url": "u"}}}}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            profiles.profile("both"),
+            Err(ProfileError::Invalid { ref profile, .. }) if profile == "both"
+        ));
+    }
+
+    #[test]
+    fn test_verbose_defaults_to_true() {
+        let profiles = AgentProfiles::from_toml_str("[profiles.quiet]\nverbose = false\n").unwrap();
+        assert!(
+            profiles
+                .default_profile()
+                .unwrap()
+                .into_options()
+                .unwrap()
+                .verbose
+        );
+
+        let quiet = profiles.profile("quiet").unwrap().into_options().unwrap();
+        assert!(!quiet.verbose);
+        assert_eq!(AgentProfile::from_options(&quiet).verbose, Some(false));
+        assert_eq!(
+            AgentProfile::from_options(&ClaudeAgentOptions::default()).verbose,
+            None
+        );
+    }
+}
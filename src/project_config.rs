@@ -0,0 +1,828 @@
+//! Inspection of the filesystem configuration loaded by the CLI
+//!
+//! [`SettingSource`] tells the CLI which `.claude` configuration layers to load, but the
+//! CLI reads them itself. [`ProjectConfig`] parses the same files so applications can show
+//! the effective configuration before a run and detect conflicts with programmatic
+//! [`ClaudeAgentOptions`].
+//!
+//! | Source    | Settings                        | Agents / commands / skills | Memory                 |
+//! |-----------|---------------------------------|----------------------------|------------------------|
+//! | `User`    | `~/.claude/settings.json`       | `~/.claude/{agents,commands,skills}` | `~/.claude/CLAUDE.md` |
+//! | `Project` | `.claude/settings.json`         | `.claude/{agents,commands,skills}`   | `CLAUDE.md`, `.claude/CLAUDE.md` |
+//! | `Local`   | `.claude/settings.local.json`   | -                          | `CLAUDE.local.md`      |
+//!
+//! Layers are merged with the CLI's precedence (`Local` > `Project` > `User`): scalar
+//! settings are overridden by higher layers, permission rule lists are unioned.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use claude_agent_sdk_rs::{ClaudeAgentOptions, SettingSource};
+//! use claude_agent_sdk_rs::project_config::ProjectConfig;
+//!
+//! # fn main() -> claude_agent_sdk_rs::Result<()> {
+//! let options = ClaudeAgentOptions::builder()
+//!     .setting_sources(vec![SettingSource::User, SettingSource::Project])
+//!     .model("claude-sonnet-4-5")
+//!     .build();
+//!
+//! let config = ProjectConfig::load_for_options(&options)?;
+//! println!("effective permissions: {:?}", config.settings.permissions);
+//! for conflict in config.conflicts(&options) {
+//!     println!("warning: {}", conflict);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::errors::{ClaudeError, Result};
+use crate::types::agents::{NameList, split_frontmatter};
+use crate::types::config::{AgentDefinition, ClaudeAgentOptions, PermissionMode, SettingSource};
+
+/// Layers the CLI loads when no `--setting-sources` flag is passed
+const CLI_DEFAULT_SOURCES: [SettingSource; 3] = [
+    SettingSource::User,
+    SettingSource::Project,
+    SettingSource::Local,
+];
+
+/// Contents of a `settings.json` file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeSettings {
+    /// Default model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Permission rules
+    #[serde(default)]
+    pub permissions: PermissionSettings,
+    /// Environment variables set for the session
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Hook configuration, keyed by event name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<serde_json::Value>,
+    /// Settings not modelled above
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The `permissions` section of `settings.json`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionSettings {
+    /// Rules for tools that are allowed without prompting
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Rules for tools that are always denied
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Rules for tools that always prompt
+    #[serde(default)]
+    pub ask: Vec<String>,
+    /// Extra working directories
+    #[serde(default)]
+    pub additional_directories: Vec<String>,
+    /// Default permission mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_mode: Option<PermissionMode>,
+}
+
+impl ClaudeSettings {
+    /// Merge a higher-precedence layer into this one
+    pub fn merge(&mut self, higher: ClaudeSettings) {
+        if higher.model.is_some() {
+            self.model = higher.model;
+        }
+
+        let perms = &mut self.permissions;
+        union_into(&mut perms.allow, higher.permissions.allow);
+        union_into(&mut perms.deny, higher.permissions.deny);
+        union_into(&mut perms.ask, higher.permissions.ask);
+        union_into(
+            &mut perms.additional_directories,
+            higher.permissions.additional_directories,
+        );
+        if higher.permissions.default_mode.is_some() {
+            perms.default_mode = higher.permissions.default_mode;
+        }
+
+        self.env.extend(higher.env);
+
+        // Hooks from every layer run, so matchers are concatenated per event
+        match (&mut self.hooks, higher.hooks) {
+            (Some(serde_json::Value::Object(lower)), Some(serde_json::Value::Object(upper))) => {
+                for (event, matchers) in upper {
+                    match (lower.get_mut(&event), matchers) {
+                        (
+                            Some(serde_json::Value::Array(existing)),
+                            serde_json::Value::Array(more),
+                        ) => existing.extend(more),
+                        (_, matchers) => {
+                            lower.insert(event, matchers);
+                        }
+                    }
+                }
+            }
+            (lower, Some(upper)) => *lower = Some(upper),
+            (_, None) => {}
+        }
+
+        self.extra.extend(higher.extra);
+    }
+}
+
+fn union_into(target: &mut Vec<String>, items: Vec<String>) {
+    for item in items {
+        if !target.contains(&item) {
+            target.push(item);
+        }
+    }
+}
+
+/// A single settings file that was found and parsed
+#[derive(Debug, Clone)]
+pub struct SettingsLayer {
+    /// Which source the file belongs to
+    pub source: SettingSource,
+    /// Path of the file
+    pub path: PathBuf,
+    /// Parsed contents
+    pub settings: ClaudeSettings,
+}
+
+/// An agent loaded from a Markdown file
+#[derive(Debug, Clone)]
+pub struct LoadedAgent {
+    /// Source the agent was loaded from
+    pub source: SettingSource,
+    /// Path of the agent file
+    pub path: PathBuf,
+    /// Parsed definition
+    pub definition: AgentDefinition,
+}
+
+/// A custom slash command from a `commands/*.md` file
+#[derive(Debug, Clone)]
+pub struct SlashCommand {
+    /// Command name without the leading `/` (nested directories are joined with `:`)
+    pub name: String,
+    /// Source the command was loaded from
+    pub source: SettingSource,
+    /// Path of the command file
+    pub path: PathBuf,
+    /// Description from the frontmatter
+    pub description: Option<String>,
+    /// Tools the command may use
+    pub allowed_tools: Option<Vec<String>>,
+    /// Hint shown for the command's arguments
+    pub argument_hint: Option<String>,
+    /// Model override
+    pub model: Option<String>,
+    /// Prompt template
+    pub body: String,
+}
+
+/// A skill from a `skills/<name>/SKILL.md` file
+#[derive(Debug, Clone)]
+pub struct Skill {
+    /// Skill name (the frontmatter `name`, or the directory name)
+    pub name: String,
+    /// Source the skill was loaded from
+    pub source: SettingSource,
+    /// Path of the `SKILL.md` file
+    pub path: PathBuf,
+    /// Description from the frontmatter
+    pub description: Option<String>,
+    /// Tools the skill may use
+    pub allowed_tools: Option<Vec<String>>,
+    /// Skill instructions
+    pub body: String,
+}
+
+/// A `CLAUDE.md` memory file
+#[derive(Debug, Clone)]
+pub struct MemoryFile {
+    /// Source the file belongs to
+    pub source: SettingSource,
+    /// Path of the file
+    pub path: PathBuf,
+    /// File contents
+    pub content: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct MarkdownFrontmatter {
+    name: Option<String>,
+    description: Option<String>,
+    allowed_tools: Option<NameList>,
+    argument_hint: Option<String>,
+    model: Option<String>,
+}
+
+/// Filesystem configuration as the CLI would load it
+#[derive(Debug, Clone, Default)]
+pub struct ProjectConfig {
+    /// Settings files that were found, lowest precedence first
+    pub layers: Vec<SettingsLayer>,
+    /// Merged settings
+    pub settings: ClaudeSettings,
+    /// Agents by name (project agents override user agents)
+    pub agents: HashMap<String, LoadedAgent>,
+    /// Slash commands by name (project commands override user commands)
+    pub commands: HashMap<String, SlashCommand>,
+    /// Skills by name (project skills override user skills)
+    pub skills: HashMap<String, Skill>,
+    /// Memory files, lowest precedence first
+    pub memory: Vec<MemoryFile>,
+}
+
+impl ProjectConfig {
+    /// Load the configuration for the given sources
+    ///
+    /// `cwd` is the project directory and `home` the user's home directory (user-level
+    /// files are skipped when `None`). Missing files are ignored; malformed files are
+    /// reported as [`ClaudeError::InvalidConfig`].
+    pub fn load(cwd: &Path, home: Option<&Path>, sources: &[SettingSource]) -> Result<Self> {
+        let mut config = ProjectConfig::default();
+
+        // Load in precedence order so later layers override earlier ones
+        for source in [
+            SettingSource::User,
+            SettingSource::Project,
+            SettingSource::Local,
+        ] {
+            if !sources.contains(&source) {
+                continue;
+            }
+            let claude_dir = match source {
+                SettingSource::User => match home {
+                    Some(home) => home.join(".claude"),
+                    None => continue,
+                },
+                SettingSource::Project | SettingSource::Local => cwd.join(".claude"),
+            };
+
+            let settings_file = match source {
+                SettingSource::Local => "settings.local.json",
+                _ => "settings.json",
+            };
+            config.load_settings(source, &claude_dir.join(settings_file))?;
+
+            if source != SettingSource::Local {
+                config.load_agents(source, &claude_dir.join("agents"))?;
+                config.load_commands(source, &claude_dir.join("commands"), "")?;
+                config.load_skills(source, &claude_dir.join("skills"))?;
+            }
+
+            let memory_files = match source {
+                SettingSource::User => vec![claude_dir.join("CLAUDE.md")],
+                SettingSource::Project => vec![cwd.join("CLAUDE.md"), claude_dir.join("CLAUDE.md")],
+                SettingSource::Local => vec![cwd.join("CLAUDE.local.md")],
+            };
+            for path in memory_files {
+                if path.is_file() {
+                    let content = std::fs::read_to_string(&path)?;
+                    config.memory.push(MemoryFile {
+                        source,
+                        path,
+                        content,
+                    });
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Load the configuration the CLI would see for these options
+    ///
+    /// Uses `options.cwd` (or the current directory), `$HOME` and
+    /// `options.setting_sources`. When no setting sources are set, no `--setting-sources`
+    /// flag is passed and the CLI loads all of its default layers, so all of them are
+    /// loaded here too.
+    pub fn load_for_options(options: &ClaudeAgentOptions) -> Result<Self> {
+        let cwd = match options.cwd {
+            Some(ref cwd) => cwd.clone(),
+            None => std::env::current_dir()?,
+        };
+        let home = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE"))
+            .ok()
+            .map(PathBuf::from);
+        Self::load(&cwd, home.as_deref(), &Self::sources_for(options))
+    }
+
+    /// Setting sources the CLI loads for these options
+    fn sources_for(options: &ClaudeAgentOptions) -> Vec<SettingSource> {
+        options
+            .setting_sources
+            .clone()
+            .unwrap_or_else(|| CLI_DEFAULT_SOURCES.to_vec())
+    }
+
+    /// Report where programmatic options disagree with the filesystem configuration
+    pub fn conflicts(&self, options: &ClaudeAgentOptions) -> Vec<ConfigConflict> {
+        let mut conflicts = Vec::new();
+
+        if let (Some(option_model), Some(settings_model)) = (&options.model, &self.settings.model)
+            && option_model != settings_model
+        {
+            conflicts.push(ConfigConflict {
+                field: "model".to_string(),
+                source: self.source_of(|s| s.model.is_some()),
+                settings_value: settings_model.clone(),
+                options_value: option_model.clone(),
+                resolution: ConflictResolution::OptionsWin,
+            });
+        }
+
+        if let (Some(option_mode), Some(settings_mode)) = (
+            options.permission_mode,
+            self.settings.permissions.default_mode,
+        ) && option_mode != settings_mode
+        {
+            conflicts.push(ConfigConflict {
+                field: "permissions.defaultMode".to_string(),
+                source: self.source_of(|s| s.permissions.default_mode.is_some()),
+                settings_value: mode_name(settings_mode),
+                options_value: mode_name(option_mode),
+                resolution: ConflictResolution::OptionsWin,
+            });
+        }
+
+        for tool in &options.allowed_tools {
+            if self.settings.permissions.deny.contains(tool) {
+                conflicts.push(ConfigConflict {
+                    field: "permissions.deny".to_string(),
+                    source: self.source_of(|s| s.permissions.deny.contains(tool)),
+                    settings_value: tool.clone(),
+                    options_value: format!("allowed_tools contains {}", tool),
+                    resolution: ConflictResolution::SettingsWin,
+                });
+            }
+        }
+
+        for tool in &options.disallowed_tools {
+            if self.settings.permissions.allow.contains(tool) {
+                conflicts.push(ConfigConflict {
+                    field: "permissions.allow".to_string(),
+                    source: self.source_of(|s| s.permissions.allow.contains(tool)),
+                    settings_value: tool.clone(),
+                    options_value: format!("disallowed_tools contains {}", tool),
+                    resolution: ConflictResolution::OptionsWin,
+                });
+            }
+        }
+
+        let mut env_keys: Vec<&String> = options.env.keys().collect();
+        env_keys.sort();
+        for key in env_keys {
+            if let Some(settings_value) = self.settings.env.get(key)
+                && settings_value != &options.env[key]
+            {
+                conflicts.push(ConfigConflict {
+                    field: format!("env.{}", key),
+                    source: self.source_of(|s| s.env.contains_key(key)),
+                    settings_value: settings_value.clone(),
+                    options_value: options.env[key].clone(),
+                    resolution: ConflictResolution::OptionsWin,
+                });
+            }
+        }
+
+        if let Some(ref agents) = options.agents {
+            let mut names: Vec<&String> = agents.keys().collect();
+            names.sort();
+            for name in names {
+                if let Some(loaded) = self.agents.get(name) {
+                    conflicts.push(ConfigConflict {
+                        field: format!("agents.{}", name),
+                        source: Some(loaded.source),
+                        settings_value: loaded.path.display().to_string(),
+                        options_value: "programmatic agent definition".to_string(),
+                        resolution: ConflictResolution::OptionsWin,
+                    });
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    /// The highest-precedence layer for which `predicate` holds
+    fn source_of(&self, predicate: impl Fn(&ClaudeSettings) -> bool) -> Option<SettingSource> {
+        self.layers
+            .iter()
+            .rev()
+            .find(|layer| predicate(&layer.settings))
+            .map(|layer| layer.source)
+    }
+
+    fn load_settings(&mut self, source: SettingSource, path: &Path) -> Result<()> {
+        if !path.is_file() {
+            return Ok(());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let settings: ClaudeSettings = serde_json::from_str(&content).map_err(|e| {
+            ClaudeError::InvalidConfig(format!("{}: invalid settings: {}", path.display(), e))
+        })?;
+        self.settings.merge(settings.clone());
+        self.layers.push(SettingsLayer {
+            source,
+            path: path.to_path_buf(),
+            settings,
+        });
+        Ok(())
+    }
+
+    fn load_agents(&mut self, source: SettingSource, dir: &Path) -> Result<()> {
+        for path in markdown_files(dir)? {
+            let (name, definition) = crate::types::agents::load_agent_file(&path)?;
+            self.agents.insert(
+                name,
+                LoadedAgent {
+                    source,
+                    path,
+                    definition,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn load_commands(&mut self, source: SettingSource, dir: &Path, prefix: &str) -> Result<()> {
+        if !dir.is_dir() {
+            return Ok(());
+        }
+        let mut subdirs: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.is_dir())
+            .collect();
+        subdirs.sort();
+
+        for path in markdown_files(dir)? {
+            let stem = file_stem(&path);
+            let (frontmatter, body) = parse_markdown(&path)?;
+            let name = format!("{}{}", prefix, stem);
+            self.commands.insert(
+                name.clone(),
+                SlashCommand {
+                    name,
+                    source,
+                    path,
+                    description: frontmatter.description,
+                    allowed_tools: frontmatter.allowed_tools.map(Into::into),
+                    argument_hint: frontmatter.argument_hint,
+                    model: frontmatter.model,
+                    body,
+                },
+            );
+        }
+
+        for subdir in subdirs {
+            let nested = format!("{}{}:", prefix, file_stem(&subdir));
+            self.load_commands(source, &subdir, &nested)?;
+        }
+        Ok(())
+    }
+
+    fn load_skills(&mut self, source: SettingSource, dir: &Path) -> Result<()> {
+        if !dir.is_dir() {
+            return Ok(());
+        }
+        let mut skill_dirs: Vec<PathBuf> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.join("SKILL.md").is_file())
+            .collect();
+        skill_dirs.sort();
+
+        for skill_dir in skill_dirs {
+            let path = skill_dir.join("SKILL.md");
+            let (frontmatter, body) = parse_markdown(&path)?;
+            let name = frontmatter.name.unwrap_or_else(|| file_stem(&skill_dir));
+            self.skills.insert(
+                name.clone(),
+                Skill {
+                    name,
+                    source,
+                    path,
+                    description: frontmatter.description,
+                    allowed_tools: frontmatter.allowed_tools.map(Into::into),
+                    body,
+                },
+            );
+        }
+        Ok(())
+    }
+}
+
+/// How a [`ConfigConflict`] is resolved by the CLI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// The programmatic option takes effect
+    OptionsWin,
+    /// The filesystem setting takes effect
+    SettingsWin,
+}
+
+/// A disagreement between [`ClaudeAgentOptions`] and the filesystem configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigConflict {
+    /// Setting that conflicts (e.g. `model`, `permissions.deny`, `env.API_URL`)
+    pub field: String,
+    /// Layer that provides the setting
+    pub source: Option<SettingSource>,
+    /// Value from the filesystem configuration
+    pub settings_value: String,
+    /// Value from the options
+    pub options_value: String,
+    /// Which side takes effect
+    pub resolution: ConflictResolution,
+}
+
+impl fmt::Display for ConfigConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let source = match self.source {
+            Some(SettingSource::User) => "user settings",
+            Some(SettingSource::Project) => "project settings",
+            Some(SettingSource::Local) => "local settings",
+            None => "settings",
+        };
+        let winner = match self.resolution {
+            ConflictResolution::OptionsWin => "options take precedence",
+            ConflictResolution::SettingsWin => "settings take precedence",
+        };
+        write!(
+            f,
+            "{}: {} has '{}' but options have '{}' ({})",
+            self.field, source, self.settings_value, self.options_value, winner
+        )
+    }
+}
+
+fn mode_name(mode: PermissionMode) -> String {
+    serde_json::to_value(mode)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn markdown_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Parse optional frontmatter; files without frontmatter are all body
+fn parse_markdown(path: &Path) -> Result<(MarkdownFrontmatter, String)> {
+    let content = std::fs::read_to_string(path)?;
+    match split_frontmatter(&content) {
+        Some((frontmatter, body)) => {
+            let parsed = if frontmatter.trim().is_empty() {
+                MarkdownFrontmatter::default()
+            } else {
+                serde_norway::from_str(frontmatter).map_err(|e| {
+                    ClaudeError::InvalidConfig(format!(
+                        "{}: invalid frontmatter: {}",
+                        path.display(),
+                        e
+                    ))
+                })?
+            };
+            Ok((parsed, body.trim().to_string()))
+        }
+        None => Ok((MarkdownFrontmatter::default(), content.trim().to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixture {
+        root: tempfile::TempDir,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            Self {
+                root: tempfile::tempdir().unwrap(),
+            }
+        }
+
+        fn home(&self) -> PathBuf {
+            self.root.path().join("home")
+        }
+
+        fn project(&self) -> PathBuf {
+            self.root.path().join("project")
+        }
+
+        fn write(&self, relative: &str, content: &str) {
+            let path = self.root.path().join(relative);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+    }
+
+    const ALL: [SettingSource; 3] = [
+        SettingSource::User,
+        SettingSource::Project,
+        SettingSource::Local,
+    ];
+
+    fn sample() -> Fixture {
+        let fx = Fixture::new();
+        fx.write(
+            "home/.claude/settings.json",
+            r#"{"model": "opus", "permissions": {"allow": ["Read"], "deny": ["WebFetch"]},
+                "env": {"LOG": "info"}, "hooks": {"PreToolUse": [{"matcher": "Bash"}]}}"#,
+        );
+        fx.write(
+            "project/.claude/settings.json",
+            r#"{"model": "sonnet", "permissions": {"allow": ["Read", "Bash(git:*)"], "defaultMode": "acceptEdits"},
+                "hooks": {"PreToolUse": [{"matcher": "Write"}]}, "includeCoAuthoredBy": false}"#,
+        );
+        fx.write(
+            "project/.claude/settings.local.json",
+            r#"{"permissions": {"deny": ["Bash(rm:*)"]}, "env": {"LOG": "debug"}}"#,
+        );
+        fx.write(
+            "home/.claude/agents/reviewer.md",
+            "---\ndescription: User reviewer\n---\nReview.\n",
+        );
+        fx.write(
+            "project/.claude/agents/reviewer.md",
+            "---\ndescription: Project reviewer\ntools: Read\n---\nReview strictly.\n",
+        );
+        fx.write(
+            "project/.claude/commands/deploy.md",
+            "---\ndescription: Deploy the app\nallowed-tools: Bash(make:*)\nargument-hint: <env>\n---\nDeploy to $ARGUMENTS\n",
+        );
+        fx.write(
+            "project/.claude/commands/frontend/component.md",
+            "Create a component\n",
+        );
+        fx.write(
+            "project/.claude/skills/pdf/SKILL.md",
+            "---\nname: pdf-tools\ndescription: Work with PDFs\n---\nUse pdftotext.\n",
+        );
+        fx.write("project/CLAUDE.md", "# Project rules\n");
+        fx.write("project/CLAUDE.local.md", "# My notes\n");
+        fx
+    }
+
+    #[test]
+    fn test_merge_precedence() {
+        let fx = sample();
+        let config = ProjectConfig::load(&fx.project(), Some(&fx.home()), &ALL).unwrap();
+
+        assert_eq!(config.layers.len(), 3);
+        let settings = &config.settings;
+        assert_eq!(settings.model.as_deref(), Some("sonnet"));
+        assert_eq!(settings.permissions.allow, vec!["Read", "Bash(git:*)"]);
+        assert_eq!(settings.permissions.deny, vec!["WebFetch", "Bash(rm:*)"]);
+        assert_eq!(
+            settings.permissions.default_mode,
+            Some(PermissionMode::AcceptEdits)
+        );
+        assert_eq!(settings.env["LOG"], "debug");
+        assert_eq!(
+            settings.hooks.as_ref().unwrap()["PreToolUse"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(settings.extra["includeCoAuthoredBy"], false);
+    }
+
+    #[test]
+    fn test_loads_agents_commands_skills_and_memory() {
+        let fx = sample();
+        let config = ProjectConfig::load(&fx.project(), Some(&fx.home()), &ALL).unwrap();
+
+        let reviewer = &config.agents["reviewer"];
+        assert_eq!(reviewer.source, SettingSource::Project);
+        assert_eq!(reviewer.definition.description, "Project reviewer");
+
+        let deploy = &config.commands["deploy"];
+        assert_eq!(deploy.description.as_deref(), Some("Deploy the app"));
+        assert_eq!(deploy.allowed_tools, Some(vec!["Bash(make:*)".to_string()]));
+        assert_eq!(deploy.argument_hint.as_deref(), Some("<env>"));
+        assert_eq!(deploy.body, "Deploy to $ARGUMENTS");
+        assert_eq!(
+            config.commands["frontend:component"].body,
+            "Create a component"
+        );
+
+        assert_eq!(
+            config.skills["pdf-tools"].description.as_deref(),
+            Some("Work with PDFs")
+        );
+
+        let memory: Vec<SettingSource> = config.memory.iter().map(|m| m.source).collect();
+        assert_eq!(memory, vec![SettingSource::Project, SettingSource::Local]);
+    }
+
+    #[test]
+    fn test_respects_sources() {
+        let fx = sample();
+        let config =
+            ProjectConfig::load(&fx.project(), Some(&fx.home()), &[SettingSource::User]).unwrap();
+        assert_eq!(config.settings.model.as_deref(), Some("opus"));
+        assert_eq!(
+            config.agents["reviewer"].definition.description,
+            "User reviewer"
+        );
+        assert!(config.commands.is_empty());
+
+        let none = ProjectConfig::load(&fx.project(), Some(&fx.home()), &[]).unwrap();
+        assert!(none.layers.is_empty());
+        assert_eq!(none.settings, ClaudeSettings::default());
+    }
+
+    #[test]
+    fn test_unset_sources_use_cli_defaults() {
+        let options = ClaudeAgentOptions::default();
+        assert_eq!(ProjectConfig::sources_for(&options), ALL.to_vec());
+
+        let options = ClaudeAgentOptions::builder()
+            .setting_sources(vec![])
+            .build();
+        assert!(ProjectConfig::sources_for(&options).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_settings_reported() {
+        let fx = Fixture::new();
+        fx.write("project/.claude/settings.json", "{not json");
+        let err = ProjectConfig::load(&fx.project(), None, &ALL).unwrap_err();
+        assert!(
+            matches!(err, ClaudeError::InvalidConfig(ref msg) if msg.contains("settings.json"))
+        );
+    }
+
+    #[test]
+    fn test_conflicts_with_options() {
+        let fx = sample();
+        let config = ProjectConfig::load(&fx.project(), Some(&fx.home()), &ALL).unwrap();
+        let options = ClaudeAgentOptions::builder()
+            .model("haiku")
+            .permission_mode(PermissionMode::Plan)
+            .allowed_tools(vec!["WebFetch".to_string()])
+            .disallowed_tools(vec!["Read".to_string()])
+            .env(HashMap::from([("LOG".to_string(), "trace".to_string())]))
+            .agents(HashMap::from([(
+                "reviewer".to_string(),
+                AgentDefinition::builder()
+                    .description("d")
+                    .prompt("p")
+                    .build(),
+            )]))
+            .build();
+
+        let conflicts = config.conflicts(&options);
+        let fields: Vec<&str> = conflicts.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "model",
+                "permissions.defaultMode",
+                "permissions.deny",
+                "permissions.allow",
+                "env.LOG",
+                "agents.reviewer"
+            ]
+        );
+
+        assert_eq!(conflicts[0].source, Some(SettingSource::Project));
+        assert_eq!(conflicts[1].settings_value, "acceptEdits");
+        assert_eq!(conflicts[2].source, Some(SettingSource::User));
+        assert_eq!(conflicts[2].resolution, ConflictResolution::SettingsWin);
+        assert_eq!(conflicts[4].source, Some(SettingSource::Local));
+        assert!(conflicts[0].to_string().contains("project settings"));
+
+        let quiet = ClaudeAgentOptions::builder().model("sonnet").build();
+        assert!(config.conflicts(&quiet).is_empty());
+    }
+}
@@ -3,15 +3,17 @@
 //! This module provides functions for simple, stateless queries to Claude Code CLI.
 //! For bidirectional streaming with conversation management, use [`ClaudeClient`](crate::ClaudeClient).
 
-use crate::errors::Result;
+use crate::errors::{ClaudeError, Result};
 use crate::internal::client::InternalClient;
 use crate::internal::message_parser::MessageParser;
 use crate::internal::transport::subprocess::QueryPrompt;
 use crate::internal::transport::{SubprocessTransport, Transport};
 use crate::types::config::ClaudeAgentOptions;
 use crate::types::messages::{Message, UserContentBlock};
+use crate::types::plugin::load_all;
 use futures::stream::{Stream, StreamExt};
 use std::pin::Pin;
+use tracing::warn;
 
 // =============================================================================
 // Internal helper functions (DRY principle)
@@ -37,6 +39,15 @@ fn create_message_stream(
                         }
                     }
                 }
+                Err(ClaudeError::JsonDecode(e)) => {
+                    // Stray non-JSON output (e.g. a CLI warning) must not end the query
+                    warn!("Skipping undecodable line from CLI: {}", e);
+                }
+                Err(e @ ClaudeError::BufferSizeExceeded(_)) => {
+                    // The oversized line was skipped; report it and keep reading
+                    warn!("{}", e);
+                    yield Err(e);
+                }
                 Err(e) => {
                     yield Err(e);
                     break;
@@ -55,6 +66,8 @@ async fn setup_streaming_transport(
     options: Option<ClaudeAgentOptions>,
 ) -> Result<SubprocessTransport> {
     let opts = options.unwrap_or_default();
+    opts.ensure_valid_for_one_shot()?;
+    load_all(&opts.plugins, opts.cwd.as_deref()).await?;
     let transport = SubprocessTransport::new(query_prompt, opts)?;
     transport.connect().await?;
     Ok(transport)
@@ -69,6 +82,15 @@ async fn setup_streaming_transport(
 /// This function is ideal for simple, stateless queries where you don't need
 /// bidirectional communication or conversation management.
 ///
+/// # Errors
+///
+/// Returns an error if:
+/// - Claude CLI cannot be found or started
+/// - The query execution fails
+/// - A line of CLI output exceeds `max_buffer_size`
+///   ([`ClaudeError::BufferSizeExceeded`]). The session still runs to the end, but the
+///   collected messages would be incomplete; use [`query_stream`] to keep the rest.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -101,7 +123,7 @@ pub async fn query(
     let query_prompt = QueryPrompt::Text(prompt.into());
     let opts = options.unwrap_or_default();
 
-    let client = InternalClient::new(query_prompt, opts)?;
+    let client = InternalClient::new(query_prompt, opts).await?;
     client.execute().await
 }
 
@@ -163,6 +185,7 @@ pub async fn query_stream(
 /// - The content vector is empty (must include at least one text or image block)
 /// - Claude CLI cannot be found or started
 /// - The query execution fails
+/// - A line of CLI output exceeds `max_buffer_size`, as for [`query`]
 ///
 /// # Examples
 ///
@@ -202,7 +225,7 @@ pub async fn query_with_content(
     let query_prompt = QueryPrompt::Content(content_blocks);
     let opts = options.unwrap_or_default();
 
-    let client = InternalClient::new(query_prompt, opts)?;
+    let client = InternalClient::new(query_prompt, opts).await?;
     client.execute().await
 }
 
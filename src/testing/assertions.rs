@@ -0,0 +1,429 @@
+//! Fluent assertions over a captured session
+//!
+//! [`SessionAssertions`] collects what a test observed — the messages the client
+//! received, the data it wrote, and optionally hook and permission recordings — and
+//! checks agent behavior against it. Every failing assertion prints the full
+//! transcript.
+//!
+//! ```no_run
+//! # async fn example(mut client: claude_agent_sdk_rs::testing::MockClient) {
+//! use claude_agent_sdk_rs::testing::{HookEvent, HookRecorder};
+//! use futures::StreamExt;
+//!
+//! let hooks = HookRecorder::new();
+//! client.query("Fix the build").await.unwrap();
+//! let messages: Vec<_> = client
+//!     .receive_response()
+//!     .filter_map(|m| async { m.ok() })
+//!     .collect()
+//!     .await;
+//!
+//! client
+//!     .assert_session(messages)
+//!     .hooks(hooks.invocations().await)
+//!     .tools_called_in_order(&["Read", "Edit"])
+//!     .tool_not_called("Bash")
+//!     .hook_fired(HookEvent::PreToolUse)
+//!     .result_is_success()
+//!     .total_cost_below(0.05);
+//! # }
+//! ```
+
+use std::fmt::Write as _;
+
+use super::recorders::{HookInvocation, PermissionDecision};
+use super::transport::WrittenMessage;
+use crate::types::config::PermissionMode;
+use crate::types::hooks::HookEvent;
+use crate::types::messages::{ContentBlock, Message, ResultMessage, ToolUseBlock};
+use crate::types::permissions::PermissionResult;
+
+/// Assertions over the messages, writes, hooks and permission decisions of a session
+///
+/// Each assertion panics with the full transcript on failure and returns `&Self`, so
+/// checks can be chained.
+#[derive(Debug, Clone, Default)]
+pub struct SessionAssertions {
+    messages: Vec<Message>,
+    writes: Vec<serde_json::Value>,
+    hooks: Vec<HookInvocation>,
+    permissions: Vec<PermissionDecision>,
+}
+
+impl SessionAssertions {
+    /// Create assertions over the messages the client received
+    pub fn new(messages: impl IntoIterator<Item = Message>) -> Self {
+        Self {
+            messages: messages.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Add the data the client wrote to the transport
+    ///
+    /// Writes that are not valid JSON are ignored.
+    pub fn writes(mut self, written: &[WrittenMessage]) -> Self {
+        self.writes
+            .extend(written.iter().filter_map(|w| w.parsed.clone()));
+        self
+    }
+
+    /// Add hook invocations, e.g. from [`HookRecorder::invocations`](super::HookRecorder::invocations)
+    pub fn hooks(mut self, invocations: Vec<HookInvocation>) -> Self {
+        self.hooks.extend(invocations);
+        self
+    }
+
+    /// Add permission decisions, e.g. from
+    /// [`PermissionRecorder::decisions`](super::PermissionRecorder::decisions)
+    pub fn permissions(mut self, decisions: Vec<PermissionDecision>) -> Self {
+        self.permissions.extend(decisions);
+        self
+    }
+
+    /// Tool uses requested by the assistant, in order
+    pub fn tool_calls(&self) -> Vec<&ToolUseBlock> {
+        self.messages
+            .iter()
+            .filter_map(|m| match m {
+                Message::Assistant(assistant) => Some(&assistant.message.content),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse(tool) => Some(tool),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The last result message, if any
+    pub fn result(&self) -> Option<&ResultMessage> {
+        self.messages.iter().rev().find_map(|m| match m {
+            Message::Result(result) => Some(result),
+            _ => None,
+        })
+    }
+
+    // === Tools ===
+
+    /// Assert the assistant requested `tool` at least once
+    #[track_caller]
+    pub fn tool_called(&self, tool: &str) -> &Self {
+        self.check(
+            self.tool_calls().iter().any(|t| t.name == tool),
+            format!("expected tool '{}' to be called", tool),
+        )
+    }
+
+    /// Assert the assistant never requested `tool`
+    #[track_caller]
+    pub fn tool_not_called(&self, tool: &str) -> &Self {
+        self.check(
+            !self.tool_calls().iter().any(|t| t.name == tool),
+            format!("expected tool '{}' not to be called", tool),
+        )
+    }
+
+    /// Assert `tools` were requested in this order
+    ///
+    /// Other tool calls may come in between; use
+    /// [`tools_called_exactly`](Self::tools_called_exactly) for a strict match.
+    #[track_caller]
+    pub fn tools_called_in_order(&self, tools: &[&str]) -> &Self {
+        let calls = self.tool_calls();
+        let mut remaining = calls.iter();
+        let in_order = tools
+            .iter()
+            .all(|tool| remaining.any(|call| call.name == *tool));
+        self.check(
+            in_order,
+            format!(
+                "expected tools {:?} in order, got {:?}",
+                tools,
+                calls.iter().map(|t| &t.name).collect::<Vec<_>>()
+            ),
+        )
+    }
+
+    /// Assert exactly `tools` were requested, in this order
+    #[track_caller]
+    pub fn tools_called_exactly(&self, tools: &[&str]) -> &Self {
+        let calls: Vec<&str> = self.tool_calls().iter().map(|t| t.name.as_str()).collect();
+        self.check(
+            calls == tools,
+            format!("expected tool calls {:?}, got {:?}", tools, calls),
+        )
+    }
+
+    /// Assert `tool` was requested with input matching `predicate`
+    #[track_caller]
+    pub fn tool_called_with<F>(&self, tool: &str, predicate: F) -> &Self
+    where
+        F: Fn(&serde_json::Value) -> bool,
+    {
+        self.check(
+            self.tool_calls()
+                .iter()
+                .any(|t| t.name == tool && predicate(&t.input)),
+            format!("expected tool '{}' to be called with matching input", tool),
+        )
+    }
+
+    // === Hooks ===
+
+    /// Assert a hook fired for `event`
+    #[track_caller]
+    pub fn hook_fired(&self, event: HookEvent) -> &Self {
+        self.check(
+            self.hooks.iter().any(|h| h.event == event),
+            format!("expected {:?} hook to fire", event),
+        )
+    }
+
+    /// Assert no hook fired for `event`
+    #[track_caller]
+    pub fn hook_not_fired(&self, event: HookEvent) -> &Self {
+        self.check(
+            !self.hooks.iter().any(|h| h.event == event),
+            format!("expected {:?} hook not to fire", event),
+        )
+    }
+
+    /// Assert a hook fired for `event` with an invocation matching `predicate`
+    #[track_caller]
+    pub fn hook_fired_with<F>(&self, event: HookEvent, predicate: F) -> &Self
+    where
+        F: Fn(&HookInvocation) -> bool,
+    {
+        self.check(
+            self.hooks.iter().any(|h| h.event == event && predicate(h)),
+            format!("expected {:?} hook to fire with matching input", event),
+        )
+    }
+
+    // === Permissions ===
+
+    /// Assert permission was requested for `tool` and denied
+    #[track_caller]
+    pub fn permission_denied(&self, tool: &str) -> &Self {
+        self.check(
+            self.permissions
+                .iter()
+                .any(|d| d.tool_name == tool && matches!(d.result, PermissionResult::Deny(_))),
+            format!("expected permission for '{}' to be denied", tool),
+        )
+    }
+
+    /// Assert permission was requested for `tool` and allowed
+    #[track_caller]
+    pub fn permission_allowed(&self, tool: &str) -> &Self {
+        self.check(
+            self.permissions
+                .iter()
+                .any(|d| d.tool_name == tool && matches!(d.result, PermissionResult::Allow(_))),
+            format!("expected permission for '{}' to be allowed", tool),
+        )
+    }
+
+    // === Result ===
+
+    /// Assert the session ended with a successful result
+    #[track_caller]
+    pub fn result_is_success(&self) -> &Self {
+        self.check(
+            self.result().is_some_and(|r| !r.is_error),
+            "expected a successful result".to_string(),
+        )
+    }
+
+    /// Assert the session ended with an error result
+    #[track_caller]
+    pub fn result_is_error(&self) -> &Self {
+        self.check(
+            self.result().is_some_and(|r| r.is_error),
+            "expected an error result".to_string(),
+        )
+    }
+
+    /// Assert the reported total cost is below `usd`
+    ///
+    /// Uses `total_cost_usd` of the last result message, which the CLI reports
+    /// cumulatively. Fails when no cost was reported.
+    #[track_caller]
+    pub fn total_cost_below(&self, usd: f64) -> &Self {
+        let cost = self.result().and_then(|r| r.total_cost_usd);
+        self.check(
+            cost.is_some_and(|cost| cost < usd),
+            format!("expected total cost below ${}, got {:?}", usd, cost),
+        )
+    }
+
+    // === Control requests ===
+
+    /// Assert the client sent a control request with this `subtype`
+    #[track_caller]
+    pub fn control_request_sent(&self, subtype: &str) -> &Self {
+        self.check(
+            self.control_requests()
+                .any(|request| request["subtype"] == subtype),
+            format!("expected a '{}' control request", subtype),
+        )
+    }
+
+    /// Assert the client sent a `set_permission_mode` request for `mode`
+    #[track_caller]
+    pub fn permission_mode_set(&self, mode: PermissionMode) -> &Self {
+        let mode = serde_json::to_value(mode).expect("PermissionMode serialization");
+        self.check(
+            self.control_requests().any(|request| {
+                request["subtype"] == "set_permission_mode" && request["mode"] == mode
+            }),
+            format!("expected set_permission_mode to {}", mode),
+        )
+    }
+
+    /// The full transcript printed by failing assertions
+    pub fn transcript(&self) -> String {
+        let mut out = String::from("--- received ---\n");
+        for message in &self.messages {
+            let json = serde_json::to_string(message).unwrap_or_else(|e| e.to_string());
+            let _ = writeln!(out, "<- {}", json);
+        }
+        out.push_str("--- written ---\n");
+        for write in &self.writes {
+            let _ = writeln!(out, "-> {}", write);
+        }
+        if !self.hooks.is_empty() {
+            out.push_str("--- hooks ---\n");
+            for hook in &self.hooks {
+                let _ = writeln!(
+                    out,
+                    "{:?} tool={:?} input={}",
+                    hook.event,
+                    hook.tool_name,
+                    hook.input
+                        .as_ref()
+                        .map(|i| i.to_string())
+                        .unwrap_or_default()
+                );
+            }
+        }
+        if !self.permissions.is_empty() {
+            out.push_str("--- permissions ---\n");
+            for decision in &self.permissions {
+                let verdict = match decision.result {
+                    PermissionResult::Allow(_) => "allow",
+                    PermissionResult::Deny(_) => "deny",
+                };
+                let _ = writeln!(
+                    out,
+                    "{} {} input={}",
+                    verdict, decision.tool_name, decision.input
+                );
+            }
+        }
+        out
+    }
+
+    fn control_requests(&self) -> impl Iterator<Item = &serde_json::Value> {
+        self.writes
+            .iter()
+            .filter(|w| w["type"] == "control_request")
+            .map(|w| &w["request"])
+    }
+
+    #[track_caller]
+    fn check(&self, ok: bool, message: String) -> &Self {
+        if !ok {
+            panic!("{}\n{}", message, self.transcript());
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::builders::{AssistantMessageBuilder, ResultMessageBuilder};
+    use crate::types::permissions::PermissionResultDeny;
+
+    fn session() -> SessionAssertions {
+        SessionAssertions::new([
+            AssistantMessageBuilder::new()
+                .tool_use("Read", serde_json::json!({"file_path": "/a"}))
+                .build(),
+            AssistantMessageBuilder::new()
+                .tool_use("Grep", serde_json::json!({"pattern": "x"}))
+                .tool_use("Edit", serde_json::json!({"file_path": "/a"}))
+                .build(),
+            ResultMessageBuilder::default().cost_usd(0.01).build(),
+        ])
+    }
+
+    #[test]
+    fn test_tool_assertions() {
+        session()
+            .tool_called("Read")
+            .tool_not_called("Bash")
+            .tools_called_in_order(&["Read", "Edit"])
+            .tools_called_exactly(&["Read", "Grep", "Edit"])
+            .tool_called_with("Edit", |input| input["file_path"] == "/a")
+            .result_is_success()
+            .total_cost_below(0.02);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected tools [\"Edit\", \"Read\"] in order")]
+    fn test_out_of_order_tools_fail_with_transcript() {
+        session().tools_called_in_order(&["Edit", "Read"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "<- {\"type\":\"result\"")]
+    fn test_failure_prints_transcript() {
+        session().total_cost_below(0.001);
+    }
+
+    #[test]
+    fn test_hooks_permissions_and_control_requests() {
+        let now = std::time::Instant::now();
+        let assertions = SessionAssertions::new([])
+            .hooks(vec![HookInvocation {
+                event: HookEvent::PreToolUse,
+                tool_name: Some("Bash".to_string()),
+                input: Some(serde_json::json!({"command": "rm -rf /"})),
+                output: None,
+                timestamp: now,
+            }])
+            .permissions(vec![PermissionDecision {
+                tool_name: "Bash".to_string(),
+                input: serde_json::json!({}),
+                context: Default::default(),
+                result: PermissionResult::Deny(PermissionResultDeny {
+                    message: "no".to_string(),
+                    interrupt: false,
+                }),
+                timestamp: now,
+            }])
+            .writes(&[WrittenMessage {
+                data: String::new(),
+                parsed: Some(serde_json::json!({
+                    "type": "control_request",
+                    "request_id": "req_1",
+                    "request": {"subtype": "set_permission_mode", "mode": "acceptEdits"},
+                })),
+                timestamp: now,
+            }]);
+
+        assertions
+            .hook_fired(HookEvent::PreToolUse)
+            .hook_not_fired(HookEvent::Stop)
+            .hook_fired_with(HookEvent::PreToolUse, |h| {
+                h.input.as_ref().is_some_and(|i| i["command"] == "rm -rf /")
+            })
+            .permission_denied("Bash")
+            .control_request_sent("set_permission_mode")
+            .permission_mode_set(PermissionMode::AcceptEdits);
+    }
+}
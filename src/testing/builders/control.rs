@@ -0,0 +1,49 @@
+//! Builder for control cancel requests
+
+use crate::types::messages::Message;
+
+/// Builder for `control_cancel_request` messages
+///
+/// The CLI sends these to withdraw a control request it issued earlier, such as a
+/// permission prompt that is no longer needed.
+pub struct ControlCancelRequestBuilder {
+    request_id: String,
+}
+
+impl ControlCancelRequestBuilder {
+    /// Create a builder cancelling `request_id`
+    pub fn new(request_id: impl Into<String>) -> Self {
+        Self {
+            request_id: request_id.into(),
+        }
+    }
+
+    /// Build the message
+    pub fn build(self) -> Message {
+        serde_json::from_value(self.build_json()).expect("Cancel request should deserialize")
+    }
+
+    /// Build as JSON value
+    pub fn build_json(self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "control_cancel_request",
+            "request_id": self.request_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_cancel_request() {
+        let msg = ControlCancelRequestBuilder::new("req_7").build();
+
+        if let Message::ControlCancelRequest(value) = msg {
+            assert_eq!(value["request_id"], "req_7");
+        } else {
+            panic!("Expected control cancel request");
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Builder for hook inputs
+
+use crate::types::hooks::{
+    HookInput, PostToolUseHookInput, PreCompactHookInput, PreToolUseHookInput, StopHookInput,
+    SubagentStopHookInput, UserPromptSubmitHookInput,
+};
+
+/// Builder for [`HookInput`] values
+///
+/// Holds the fields common to every hook event; each event method finishes the
+/// input for that event.
+///
+/// ```
+/// use claude_agent_sdk_rs::testing::builders::HookInputBuilder;
+///
+/// let input = HookInputBuilder::new()
+///     .permission_mode("acceptEdits")
+///     .pre_tool_use("Bash", serde_json::json!({"command": "ls"}));
+/// ```
+pub struct HookInputBuilder {
+    session_id: String,
+    transcript_path: String,
+    cwd: String,
+    permission_mode: Option<String>,
+}
+
+impl HookInputBuilder {
+    /// Create a builder with test defaults
+    pub fn new() -> Self {
+        Self {
+            session_id: "test-session".to_string(),
+            transcript_path: "/tmp/transcript.jsonl".to_string(),
+            cwd: "/tmp".to_string(),
+            permission_mode: None,
+        }
+    }
+
+    /// Set the session ID
+    pub fn session_id(mut self, id: impl Into<String>) -> Self {
+        self.session_id = id.into();
+        self
+    }
+
+    /// Set the transcript path
+    pub fn transcript_path(mut self, path: impl Into<String>) -> Self {
+        self.transcript_path = path.into();
+        self
+    }
+
+    /// Set the working directory
+    pub fn cwd(mut self, cwd: impl Into<String>) -> Self {
+        self.cwd = cwd.into();
+        self
+    }
+
+    /// Set the permission mode
+    pub fn permission_mode(mut self, mode: impl Into<String>) -> Self {
+        self.permission_mode = Some(mode.into());
+        self
+    }
+
+    /// Build a `PreToolUse` input
+    pub fn pre_tool_use(
+        self,
+        tool_name: impl Into<String>,
+        tool_input: serde_json::Value,
+    ) -> HookInput {
+        HookInput::PreToolUse(PreToolUseHookInput {
+            session_id: self.session_id,
+            transcript_path: self.transcript_path,
+            cwd: self.cwd,
+            permission_mode: self.permission_mode,
+            tool_name: tool_name.into(),
+            tool_input,
+        })
+    }
+
+    /// Build a `PostToolUse` input
+    pub fn post_tool_use(
+        self,
+        tool_name: impl Into<String>,
+        tool_input: serde_json::Value,
+        tool_response: serde_json::Value,
+    ) -> HookInput {
+        HookInput::PostToolUse(PostToolUseHookInput {
+            session_id: self.session_id,
+            transcript_path: self.transcript_path,
+            cwd: self.cwd,
+            permission_mode: self.permission_mode,
+            tool_name: tool_name.into(),
+            tool_input,
+            tool_response,
+        })
+    }
+
+    /// Build a `UserPromptSubmit` input
+    pub fn user_prompt_submit(self, prompt: impl Into<String>) -> HookInput {
+        HookInput::UserPromptSubmit(UserPromptSubmitHookInput {
+            session_id: self.session_id,
+            transcript_path: self.transcript_path,
+            cwd: self.cwd,
+            permission_mode: self.permission_mode,
+            prompt: prompt.into(),
+        })
+    }
+
+    /// Build a `Stop` input
+    pub fn stop(self, stop_hook_active: bool) -> HookInput {
+        HookInput::Stop(StopHookInput {
+            session_id: self.session_id,
+            transcript_path: self.transcript_path,
+            cwd: self.cwd,
+            permission_mode: self.permission_mode,
+            stop_hook_active,
+        })
+    }
+
+    /// Build a `SubagentStop` input
+    pub fn subagent_stop(self, stop_hook_active: bool) -> HookInput {
+        HookInput::SubagentStop(SubagentStopHookInput {
+            session_id: self.session_id,
+            transcript_path: self.transcript_path,
+            cwd: self.cwd,
+            permission_mode: self.permission_mode,
+            stop_hook_active,
+        })
+    }
+
+    /// Build a `PreCompact` input; `trigger` is `"manual"` or `"auto"`
+    pub fn pre_compact(
+        self,
+        trigger: impl Into<String>,
+        custom_instructions: Option<String>,
+    ) -> HookInput {
+        HookInput::PreCompact(PreCompactHookInput {
+            session_id: self.session_id,
+            transcript_path: self.transcript_path,
+            cwd: self.cwd,
+            permission_mode: self.permission_mode,
+            trigger: trigger.into(),
+            custom_instructions,
+        })
+    }
+}
+
+impl Default for HookInputBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pre_tool_use_wire_format() {
+        let input = HookInputBuilder::new()
+            .session_id("s1")
+            .pre_tool_use("Bash", serde_json::json!({"command": "ls"}));
+
+        let json = serde_json::to_value(&input).unwrap();
+        assert_eq!(json["hook_event_name"], "PreToolUse");
+        assert_eq!(json["session_id"], "s1");
+        assert_eq!(json["tool_input"]["command"], "ls");
+    }
+
+    #[test]
+    fn test_event_variants() {
+        let builder = || HookInputBuilder::new().cwd("/work");
+        assert!(matches!(
+            builder().post_tool_use("Read", serde_json::json!({}), serde_json::json!("ok")),
+            HookInput::PostToolUse(_)
+        ));
+        assert!(matches!(
+            builder().user_prompt_submit("hi"),
+            HookInput::UserPromptSubmit(_)
+        ));
+        assert!(matches!(builder().stop(false), HookInput::Stop(_)));
+        assert!(matches!(
+            builder().subagent_stop(true),
+            HookInput::SubagentStop(_)
+        ));
+        assert!(matches!(
+            builder().pre_compact("manual", None),
+            HookInput::PreCompact(_)
+        ));
+    }
+}
@@ -1,11 +1,19 @@
 //! Message builders for testing
 
 mod assistant;
+mod control;
+mod hook;
 mod result;
+mod stream;
 mod system;
 mod tool;
+mod user;
 
 pub use assistant::AssistantMessageBuilder;
+pub use control::ControlCancelRequestBuilder;
+pub use hook::HookInputBuilder;
 pub use result::ResultMessageBuilder;
+pub use stream::StreamEventBuilder;
 pub use system::SystemMessageBuilder;
 pub use tool::ToolResultBuilder;
+pub use user::UserMessageBuilder;
@@ -0,0 +1,413 @@
+//! Builder for StreamEvent sequences
+
+use crate::types::messages::{AssistantMessage, ContentBlock, Message};
+
+/// Default number of characters per text, thinking or JSON delta
+const DEFAULT_CHUNK_SIZE: usize = 8;
+
+/// Builder for partial-message `StreamEvent` sequences
+///
+/// Events can be added one at a time, or generated from a complete assistant
+/// message with [`assistant`](Self::assistant), which produces the same
+/// `message_start` → `content_block_*` → `message_delta` → `message_stop` run the
+/// CLI emits with `include_partial_messages`. Deltas are split every
+/// [`chunk_size`](Self::chunk_size) characters and event UUIDs are derived from
+/// [`uuid_seed`](Self::uuid_seed) and the event's position, so the output is
+/// deterministic.
+///
+/// ```
+/// use claude_agent_sdk_rs::testing::builders::{AssistantMessageBuilder, StreamEventBuilder};
+/// use claude_agent_sdk_rs::Message;
+///
+/// let Message::Assistant(assistant) = AssistantMessageBuilder::new().text("Hello!").build() else {
+///     unreachable!()
+/// };
+/// let events = StreamEventBuilder::from_assistant(&assistant).build();
+/// assert_eq!(events.len(), 6);
+/// ```
+pub struct StreamEventBuilder {
+    events: Vec<serde_json::Value>,
+    session_id: String,
+    parent_tool_use_id: Option<String>,
+    chunk_size: usize,
+    uuid_seed: u64,
+}
+
+impl StreamEventBuilder {
+    /// Create an empty builder
+    pub fn new() -> Self {
+        Self {
+            events: Vec::new(),
+            session_id: "test-session".to_string(),
+            parent_tool_use_id: None,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            uuid_seed: 0,
+        }
+    }
+
+    /// Create a builder holding the full event run for `message`
+    pub fn from_assistant(message: &AssistantMessage) -> Self {
+        Self::new().assistant(message)
+    }
+
+    /// Set the session ID on every event
+    pub fn session_id(mut self, id: impl Into<String>) -> Self {
+        self.session_id = id.into();
+        self
+    }
+
+    /// Set the parent tool use ID on every event
+    pub fn parent_tool_use_id(mut self, id: impl Into<String>) -> Self {
+        self.parent_tool_use_id = Some(id.into());
+        self
+    }
+
+    /// Set how many characters each generated delta carries
+    ///
+    /// Applies to deltas added after this call.
+    pub fn chunk_size(mut self, chars: usize) -> Self {
+        self.chunk_size = chars.max(1);
+        self
+    }
+
+    /// Set the seed event UUIDs are derived from
+    ///
+    /// The event at position `n` gets `Uuid::from_u64_pair(seed, n + 1)`, so streams
+    /// built with different seeds never share UUIDs.
+    pub fn uuid_seed(mut self, seed: u64) -> Self {
+        self.uuid_seed = seed;
+        self
+    }
+
+    /// Add a raw event body
+    pub fn event(mut self, event: serde_json::Value) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Add a `message_start` event
+    pub fn message_start(self, id: impl Into<String>, model: impl Into<String>) -> Self {
+        self.message_start_with_usage(id.into(), model.into(), None)
+    }
+
+    /// Add a `content_block_start` event
+    pub fn content_block_start(self, index: usize, content_block: serde_json::Value) -> Self {
+        self.event(serde_json::json!({
+            "type": "content_block_start",
+            "index": index,
+            "content_block": content_block,
+        }))
+    }
+
+    /// Add `text_delta` events for `text`, split into chunks
+    pub fn text_delta(self, index: usize, text: &str) -> Self {
+        self.deltas(
+            index,
+            text,
+            |chunk| serde_json::json!({"type": "text_delta", "text": chunk}),
+        )
+    }
+
+    /// Add `input_json_delta` events for a tool's JSON input, split into chunks
+    pub fn input_json_delta(self, index: usize, partial_json: &str) -> Self {
+        self.deltas(
+            index,
+            partial_json,
+            |chunk| serde_json::json!({"type": "input_json_delta", "partial_json": chunk}),
+        )
+    }
+
+    /// Add `thinking_delta` events for `thinking`, split into chunks
+    pub fn thinking_delta(self, index: usize, thinking: &str) -> Self {
+        self.deltas(
+            index,
+            thinking,
+            |chunk| serde_json::json!({"type": "thinking_delta", "thinking": chunk}),
+        )
+    }
+
+    /// Add a `signature_delta` event
+    pub fn signature_delta(self, index: usize, signature: impl Into<String>) -> Self {
+        self.event(serde_json::json!({
+            "type": "content_block_delta",
+            "index": index,
+            "delta": {"type": "signature_delta", "signature": signature.into()},
+        }))
+    }
+
+    /// Add a `content_block_stop` event
+    pub fn content_block_stop(self, index: usize) -> Self {
+        self.event(serde_json::json!({"type": "content_block_stop", "index": index}))
+    }
+
+    /// Add a `message_delta` event carrying the stop reason
+    pub fn message_delta(self, stop_reason: impl Into<String>) -> Self {
+        self.message_delta_with_usage(stop_reason.into(), None)
+    }
+
+    /// Add a `message_stop` event
+    pub fn message_stop(self) -> Self {
+        self.event(serde_json::json!({"type": "message_stop"}))
+    }
+
+    /// Add a complete text block at `index`: start, chunked deltas and stop
+    pub fn text_block(self, index: usize, text: &str) -> Self {
+        self.content_block_start(index, serde_json::json!({"type": "text", "text": ""}))
+            .text_delta(index, text)
+            .content_block_stop(index)
+    }
+
+    /// Add the full event run for a complete assistant message
+    ///
+    /// Takes the session and parent tool use IDs from the message when present.
+    pub fn assistant(mut self, message: &AssistantMessage) -> Self {
+        if let Some(session_id) = &message.session_id {
+            self.session_id = session_id.clone();
+        }
+        if let Some(parent) = &message.parent_tool_use_id {
+            self.parent_tool_use_id = Some(parent.clone());
+        }
+
+        let inner = &message.message;
+        let mut builder = self.message_start_with_usage(
+            inner.id.clone().unwrap_or_else(|| "msg_test".to_string()),
+            inner
+                .model
+                .clone()
+                .unwrap_or_else(|| "claude-sonnet-4-5".to_string()),
+            inner.usage.clone(),
+        );
+
+        for (index, block) in inner.content.iter().enumerate() {
+            builder = match block {
+                ContentBlock::Text(text) => builder.text_block(index, &text.text),
+                ContentBlock::Thinking(thinking) => {
+                    let builder = builder
+                        .content_block_start(
+                            index,
+                            serde_json::json!({"type": "thinking", "thinking": "", "signature": ""}),
+                        )
+                        .thinking_delta(index, &thinking.thinking);
+                    let builder = if thinking.signature.is_empty() {
+                        builder
+                    } else {
+                        builder.signature_delta(index, thinking.signature.clone())
+                    };
+                    builder.content_block_stop(index)
+                }
+                ContentBlock::ToolUse(tool) => builder
+                    .content_block_start(
+                        index,
+                        serde_json::json!({
+                            "type": "tool_use",
+                            "id": tool.id,
+                            "name": tool.name,
+                            "input": {},
+                        }),
+                    )
+                    .input_json_delta(index, &tool.input.to_string())
+                    .content_block_stop(index),
+                other => builder
+                    .content_block_start(
+                        index,
+                        serde_json::to_value(other).expect("Content block serialization"),
+                    )
+                    .content_block_stop(index),
+            };
+        }
+
+        builder
+            .message_delta_with_usage(
+                inner
+                    .stop_reason
+                    .clone()
+                    .unwrap_or_else(|| "end_turn".to_string()),
+                inner.usage.clone(),
+            )
+            .message_stop()
+    }
+
+    /// Build the events as messages
+    pub fn build(self) -> Vec<Message> {
+        self.build_json()
+            .into_iter()
+            .map(|value| serde_json::from_value(value).expect("Stream event should deserialize"))
+            .collect()
+    }
+
+    /// Build the events as JSON values
+    pub fn build_json(self) -> Vec<serde_json::Value> {
+        self.events
+            .into_iter()
+            .zip(1..)
+            .map(|(event, position)| {
+                serde_json::json!({
+                    "type": "stream_event",
+                    "uuid": uuid::Uuid::from_u64_pair(self.uuid_seed, position).to_string(),
+                    "session_id": self.session_id,
+                    "event": event,
+                    "parent_tool_use_id": self.parent_tool_use_id,
+                })
+            })
+            .collect()
+    }
+
+    fn message_start_with_usage(
+        self,
+        id: String,
+        model: String,
+        usage: Option<serde_json::Value>,
+    ) -> Self {
+        self.event(serde_json::json!({
+            "type": "message_start",
+            "message": {
+                "id": id,
+                "type": "message",
+                "role": "assistant",
+                "model": model,
+                "content": [],
+                "stop_reason": null,
+                "stop_sequence": null,
+                "usage": usage.unwrap_or_else(|| serde_json::json!({"input_tokens": 0, "output_tokens": 0})),
+            },
+        }))
+    }
+
+    fn message_delta_with_usage(
+        self,
+        stop_reason: String,
+        usage: Option<serde_json::Value>,
+    ) -> Self {
+        let output_tokens = usage
+            .as_ref()
+            .and_then(|u| u["output_tokens"].as_u64())
+            .unwrap_or(0);
+        self.event(serde_json::json!({
+            "type": "message_delta",
+            "delta": {"stop_reason": stop_reason, "stop_sequence": null},
+            "usage": {"output_tokens": output_tokens},
+        }))
+    }
+
+    fn deltas(
+        mut self,
+        index: usize,
+        text: &str,
+        delta: impl Fn(&str) -> serde_json::Value,
+    ) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        for chunk in chars.chunks(self.chunk_size) {
+            let chunk: String = chunk.iter().collect();
+            self.events.push(serde_json::json!({
+                "type": "content_block_delta",
+                "index": index,
+                "delta": delta(&chunk),
+            }));
+        }
+        self
+    }
+}
+
+impl Default for StreamEventBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::builders::AssistantMessageBuilder;
+
+    fn assistant() -> AssistantMessage {
+        match AssistantMessageBuilder::new()
+            .thinking("Let me check the file")
+            .text("Reading it now. ✓")
+            .tool_use_with_id(
+                "toolu_1",
+                "Read",
+                serde_json::json!({"file_path": "/tmp/a.txt"}),
+            )
+            .session_id("s1")
+            .build()
+        {
+            Message::Assistant(assistant) => assistant,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_expansion_reassembles_to_original() {
+        let events = StreamEventBuilder::new()
+            .chunk_size(4)
+            .assistant(&assistant())
+            .build_json();
+
+        let types: Vec<&str> = events
+            .iter()
+            .map(|e| e["event"]["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(types.first(), Some(&"message_start"));
+        assert_eq!(&types[types.len() - 2..], ["message_delta", "message_stop"]);
+        assert!(events.iter().all(|e| e["session_id"] == "s1"));
+
+        let mut text = String::new();
+        let mut thinking = String::new();
+        let mut json = String::new();
+        for event in &events {
+            let delta = &event["event"]["delta"];
+            match delta["type"].as_str() {
+                Some("text_delta") => text.push_str(delta["text"].as_str().unwrap()),
+                Some("thinking_delta") => thinking.push_str(delta["thinking"].as_str().unwrap()),
+                Some("input_json_delta") => json.push_str(delta["partial_json"].as_str().unwrap()),
+                _ => {}
+            }
+        }
+        assert_eq!(text, "Reading it now. ✓");
+        assert_eq!(thinking, "Let me check the file");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!({"file_path": "/tmp/a.txt"})
+        );
+    }
+
+    #[test]
+    fn test_manual_events_build_as_stream_events() {
+        let messages = StreamEventBuilder::new()
+            .message_start("msg_1", "claude-sonnet-4-5")
+            .text_block(0, "Hi")
+            .message_delta("end_turn")
+            .message_stop()
+            .build();
+
+        assert_eq!(messages.len(), 6);
+        assert!(
+            messages
+                .iter()
+                .all(|m| matches!(m, Message::StreamEvent(_)))
+        );
+    }
+
+    #[test]
+    fn test_event_uuids_are_deterministic() {
+        let build = |seed| {
+            StreamEventBuilder::new()
+                .uuid_seed(seed)
+                .assistant(&assistant())
+                .build_json()
+        };
+        let uuids = |events: Vec<serde_json::Value>| -> Vec<String> {
+            events
+                .iter()
+                .map(|e| e["uuid"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let first = uuids(build(7));
+        assert_eq!(first, uuids(build(7)));
+        assert_eq!(first[0], "00000000-0000-0007-0000-000000000001");
+        let unique: std::collections::HashSet<_> = first.iter().collect();
+        assert_eq!(unique.len(), first.len());
+        assert!(uuids(build(8)).iter().all(|uuid| !first.contains(uuid)));
+    }
+}
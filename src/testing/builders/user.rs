@@ -0,0 +1,163 @@
+//! Builder for UserMessage
+
+use crate::types::messages::Message;
+
+/// Builder for UserMessage
+///
+/// Produces the wire format the CLI emits when it reports tool results back to the
+/// model: content blocks under `message.content`, which the SDK keeps in
+/// [`UserMessage::extra`](crate::types::messages::UserMessage::extra).
+pub struct UserMessageBuilder {
+    content: Vec<serde_json::Value>,
+    parent_tool_use_id: Option<String>,
+    session_id: String,
+    uuid: Option<String>,
+}
+
+impl UserMessageBuilder {
+    /// Create a new builder
+    pub fn new() -> Self {
+        Self {
+            content: Vec::new(),
+            parent_tool_use_id: None,
+            session_id: "test-session".to_string(),
+            uuid: Some(uuid::Uuid::new_v4().to_string()),
+        }
+    }
+
+    /// Add a text block
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.content
+            .push(serde_json::json!({"type": "text", "text": text.into()}));
+        self
+    }
+
+    /// Add a successful tool result with text content
+    pub fn tool_result(
+        mut self,
+        tool_use_id: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        self.content.push(serde_json::json!({
+            "type": "tool_result",
+            "tool_use_id": tool_use_id.into(),
+            "content": content.into(),
+            "is_error": false,
+        }));
+        self
+    }
+
+    /// Add a failed tool result
+    pub fn tool_error(
+        mut self,
+        tool_use_id: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        self.content.push(serde_json::json!({
+            "type": "tool_result",
+            "tool_use_id": tool_use_id.into(),
+            "content": message.into(),
+            "is_error": true,
+        }));
+        self
+    }
+
+    /// Add a tool result with structured content blocks
+    pub fn tool_result_blocks(
+        mut self,
+        tool_use_id: impl Into<String>,
+        blocks: Vec<serde_json::Value>,
+    ) -> Self {
+        self.content.push(serde_json::json!({
+            "type": "tool_result",
+            "tool_use_id": tool_use_id.into(),
+            "content": blocks,
+            "is_error": false,
+        }));
+        self
+    }
+
+    /// Set the parent tool use ID (for messages inside a subagent)
+    pub fn parent_tool_use_id(mut self, id: impl Into<String>) -> Self {
+        self.parent_tool_use_id = Some(id.into());
+        self
+    }
+
+    /// Set the session ID
+    pub fn session_id(mut self, id: impl Into<String>) -> Self {
+        self.session_id = id.into();
+        self
+    }
+
+    /// Set the message UUID (used for file checkpointing)
+    pub fn uuid(mut self, uuid: impl Into<String>) -> Self {
+        self.uuid = Some(uuid.into());
+        self
+    }
+
+    /// Build the message
+    pub fn build(self) -> Message {
+        serde_json::from_value(self.build_json()).expect("User message should deserialize")
+    }
+
+    /// Build as JSON value
+    pub fn build_json(self) -> serde_json::Value {
+        let mut value = serde_json::json!({
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": self.content,
+            },
+            "parent_tool_use_id": self.parent_tool_use_id,
+            "session_id": self.session_id,
+        });
+        if let Some(uuid) = self.uuid {
+            value["uuid"] = serde_json::json!(uuid);
+        }
+        value
+    }
+}
+
+impl Default for UserMessageBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_builder_tool_results() {
+        let json = UserMessageBuilder::new()
+            .tool_result("toolu_1", "file contents")
+            .tool_error("toolu_2", "File not found")
+            .session_id("s1")
+            .build_json();
+
+        let content = &json["message"]["content"];
+        assert_eq!(content[0]["tool_use_id"], "toolu_1");
+        assert_eq!(content[0]["is_error"], false);
+        assert_eq!(content[1]["is_error"], true);
+        assert_eq!(json["session_id"], "s1");
+    }
+
+    #[test]
+    fn test_user_builder_parses_like_cli_output() {
+        let msg = UserMessageBuilder::new()
+            .tool_result("toolu_1", "ok")
+            .uuid("user-uuid")
+            .build();
+
+        if let Message::User(user) = msg {
+            assert_eq!(user.uuid.as_deref(), Some("user-uuid"));
+            assert_eq!(
+                user.extra["message"]["content"][0]["tool_use_id"],
+                "toolu_1"
+            );
+        } else {
+            panic!("Expected user message");
+        }
+    }
+}
@@ -0,0 +1,523 @@
+//! VCR-style cassettes: record a live session once, replay it in later runs
+//!
+//! A cassette is a [`SessionSnapshot`] file managed automatically by
+//! [`ClaudeClient::with_cassette`](crate::ClaudeClient::with_cassette):
+//!
+//! - [`CassetteMode::Record`] talks to the real CLI and saves all traffic on disconnect
+//! - [`CassetteMode::Replay`] serves the recorded responses without a CLI, checking that
+//!   every write matches the recorded `Sent` message in order
+//! - [`CassetteMode::RecordIfMissing`] replays when the file exists and records otherwise
+//!
+//! The mode is usually taken from the `CLAUDE_CASSETTE_MODE` environment variable so the
+//! same test can refresh its fixture locally and replay it in CI:
+//!
+//! ```no_run
+//! use claude_agent_sdk_rs::testing::CassetteMode;
+//! use claude_agent_sdk_rs::{ClaudeAgentOptions, ClaudeClient};
+//!
+//! # async fn example() -> claude_agent_sdk_rs::Result<()> {
+//! let mut client = ClaudeClient::with_cassette(
+//!     ClaudeAgentOptions::default(),
+//!     "tests/cassettes/hello.json",
+//!     CassetteMode::from_env()?,
+//! );
+//! client.connect().await?;
+//! client.query("Hello").await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Secrets (API keys, tokens, authorization headers) are redacted before a cassette is
+//! written to disk.
+
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::stream::Stream;
+use serde_json::Value;
+use tokio::sync::Notify;
+
+use super::snapshot::{
+    MessageDirection, RecordedMessage, RecordingTransport, SnapshotPlayer, SnapshotRecorder,
+};
+use crate::errors::{ClaudeError, Result};
+use crate::internal::secrets::{API_KEY_PREFIX, is_secret_name};
+use crate::internal::transport::Transport;
+use crate::types::process::{ProcessInfo, ShutdownReport};
+
+/// Environment variable selecting the cassette mode
+pub const CASSETTE_MODE_ENV: &str = "CLAUDE_CASSETTE_MODE";
+
+/// Placeholder written in place of redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// How a cassette is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Run against the real CLI and overwrite the cassette
+    Record,
+    /// Serve the cassette; fail if it is missing or the session diverges
+    Replay,
+    /// Replay if the cassette exists, otherwise record it
+    RecordIfMissing,
+}
+
+impl CassetteMode {
+    /// Read the mode from `CLAUDE_CASSETTE_MODE`
+    ///
+    /// Accepts `record`, `replay` and `record_if_missing` (or `auto`). Defaults to
+    /// [`CassetteMode::RecordIfMissing`] when unset.
+    pub fn from_env() -> Result<Self> {
+        match std::env::var(CASSETTE_MODE_ENV) {
+            Ok(value) => value.parse(),
+            Err(_) => Ok(Self::RecordIfMissing),
+        }
+    }
+
+    /// Resolve [`CassetteMode::RecordIfMissing`] to a concrete mode for `path`
+    pub fn resolve(self, path: &Path) -> Self {
+        match self {
+            Self::RecordIfMissing if path.exists() => Self::Replay,
+            Self::RecordIfMissing => Self::Record,
+            other => other,
+        }
+    }
+}
+
+impl std::str::FromStr for CassetteMode {
+    type Err = ClaudeError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "record" => Ok(Self::Record),
+            "replay" => Ok(Self::Replay),
+            "record_if_missing" | "record-if-missing" | "auto" => Ok(Self::RecordIfMissing),
+            other => Err(ClaudeError::InvalidConfig(format!(
+                "Invalid {} value '{}': expected record, replay or record_if_missing",
+                CASSETTE_MODE_ENV, other
+            ))),
+        }
+    }
+}
+
+/// Replace secrets in a JSON value with [`REDACTED`]
+///
+/// Values under secret-looking keys (the same names
+/// [`CliInvocation`](crate::types::invocation::CliInvocation) redacts, such as
+/// `ANTHROPIC_API_KEY`, `Authorization` or `accessToken`) are replaced wholesale, and
+/// Anthropic API keys (`sk-ant-...`) embedded in any string are masked.
+pub fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                if is_secret_name(key) && !v.is_null() {
+                    *v = Value::String(REDACTED.to_string());
+                } else {
+                    redact_secrets(v);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        Value::String(s) if s.contains(API_KEY_PREFIX) => *s = mask_api_keys(s),
+        _ => {}
+    }
+}
+
+fn mask_api_keys(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find(API_KEY_PREFIX) {
+        out.push_str(&rest[..start]);
+        out.push_str(REDACTED);
+        let key = &rest[start..];
+        let end = key
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(key.len());
+        rest = &key[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Build the transport for a cassette in its resolved mode
+pub(crate) fn cassette_transport(
+    path: &Path,
+    mode: CassetteMode,
+    live: impl FnOnce() -> Result<Arc<dyn Transport>>,
+) -> Result<Arc<dyn Transport>> {
+    match mode.resolve(path) {
+        CassetteMode::Replay => Ok(Arc::new(ReplayTransport::load(path)?)),
+        _ => {
+            let recorder = SnapshotRecorder::new();
+            Ok(Arc::new(CassetteRecorder {
+                inner: recorder.wrap_transport(live()?),
+                recorder,
+                path: path.to_path_buf(),
+            }))
+        }
+    }
+}
+
+/// Records traffic through the live transport and saves the cassette on close
+struct CassetteRecorder {
+    inner: RecordingTransport,
+    recorder: SnapshotRecorder,
+    path: PathBuf,
+}
+
+#[async_trait]
+impl Transport for CassetteRecorder {
+    async fn connect(&self) -> Result<()> {
+        self.inner.connect().await
+    }
+
+    async fn write(&self, data: &str) -> Result<()> {
+        self.inner.write(data).await
+    }
+
+    fn read_messages(&self) -> Pin<Box<dyn Stream<Item = Result<Value>> + Send + '_>> {
+        self.inner.read_messages()
+    }
+
+    async fn close(&self) -> Result<()> {
+        self.shutdown().await.map(|_| ())
+    }
+
+    async fn shutdown(&self) -> Result<ShutdownReport> {
+        let result = self.inner.shutdown().await;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        self.recorder.save(&self.path).await?;
+        result
+    }
+
+    fn process_info(&self) -> Option<ProcessInfo> {
+        self.inner.process_info()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.inner.is_ready()
+    }
+
+    async fn end_input(&self) -> Result<()> {
+        self.inner.end_input().await
+    }
+}
+
+struct ReplayState {
+    /// Index of the next message to consider for reading
+    read_pos: usize,
+    /// Index after the last matched `Sent` message
+    write_pos: usize,
+    /// Recorded SDK request IDs mapped to the live ones
+    request_ids: std::collections::HashMap<String, String>,
+}
+
+/// Serves a recorded session and verifies the SDK's writes against it
+///
+/// Received messages are released only once every `Sent` message recorded before
+/// them has been written, so the replay follows the original turn order. Request IDs
+/// are ignored when matching and remapped in replayed responses.
+pub struct ReplayTransport {
+    messages: Vec<RecordedMessage>,
+    state: Mutex<ReplayState>,
+    progress: Notify,
+    connected: AtomicBool,
+}
+
+impl ReplayTransport {
+    /// Load a cassette from disk
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let player = SnapshotPlayer::load(path).map_err(|e| {
+            ClaudeError::InvalidConfig(format!("Cannot load cassette {}: {}", path.display(), e))
+        })?;
+        Ok(Self::new(player.snapshot().messages.clone()))
+    }
+
+    /// Create from recorded messages
+    pub fn new(messages: Vec<RecordedMessage>) -> Self {
+        Self {
+            messages,
+            state: Mutex::new(ReplayState {
+                read_pos: 0,
+                write_pos: 0,
+                request_ids: Default::default(),
+            }),
+            progress: Notify::new(),
+            connected: AtomicBool::new(false),
+        }
+    }
+
+    /// Recorded `Sent` messages that have not been written yet
+    pub fn unmatched_writes(&self) -> Vec<Value> {
+        let write_pos = self.state.lock().unwrap().write_pos;
+        self.messages[write_pos..]
+            .iter()
+            .filter(|m| m.direction == MessageDirection::Sent)
+            .map(|m| m.content.clone())
+            .collect()
+    }
+
+    fn next_sent(&self, from: usize) -> Option<usize> {
+        (from..self.messages.len()).find(|&i| self.messages[i].direction == MessageDirection::Sent)
+    }
+
+    /// Pop the next received message if all writes recorded before it have happened
+    fn poll_next(&self) -> Option<Option<Value>> {
+        let mut state = self.state.lock().unwrap();
+        while state.read_pos < self.messages.len()
+            && self.messages[state.read_pos].direction == MessageDirection::Sent
+        {
+            state.read_pos += 1;
+        }
+        if state.read_pos >= self.messages.len() {
+            return Some(None);
+        }
+
+        let blocking_write = self
+            .next_sent(state.write_pos)
+            .is_some_and(|sent| sent < state.read_pos);
+        if blocking_write {
+            return None;
+        }
+
+        let mut message = self.messages[state.read_pos].content.clone();
+        state.read_pos += 1;
+        if let Some(recorded) = message["response"]["request_id"].as_str()
+            && let Some(live) = state.request_ids.get(recorded)
+        {
+            message["response"]["request_id"] = Value::String(live.clone());
+        }
+        Some(Some(message))
+    }
+}
+
+#[async_trait]
+impl Transport for ReplayTransport {
+    async fn connect(&self) -> Result<()> {
+        self.connected.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn write(&self, data: &str) -> Result<()> {
+        let actual: Value = serde_json::from_str(data)
+            .map_err(|e| ClaudeError::Transport(format!("Cassette replay: invalid JSON: {}", e)))?;
+
+        {
+            let mut state = self.state.lock().unwrap();
+            let index = self.next_sent(state.write_pos).ok_or_else(|| {
+                ClaudeError::Transport(format!(
+                    "Cassette divergence: unexpected write after the recorded session ended\n{}",
+                    serde_json::to_string_pretty(&actual).unwrap_or_default()
+                ))
+            })?;
+
+            let expected = &self.messages[index].content;
+            let differences = diff_messages(expected, &actual);
+            if !differences.is_empty() {
+                return Err(ClaudeError::Transport(format!(
+                    "Cassette divergence at recorded message {}:\n{}",
+                    index,
+                    differences.join("\n")
+                )));
+            }
+
+            if let (Some(recorded), Some(live)) = (
+                expected["request_id"].as_str(),
+                actual["request_id"].as_str(),
+            ) {
+                state
+                    .request_ids
+                    .insert(recorded.to_string(), live.to_string());
+            }
+            state.write_pos = index + 1;
+        }
+
+        self.progress.notify_waiters();
+        Ok(())
+    }
+
+    fn read_messages(&self) -> Pin<Box<dyn Stream<Item = Result<Value>> + Send + '_>> {
+        Box::pin(async_stream::stream! {
+            loop {
+                if !self.connected.load(Ordering::SeqCst) {
+                    break;
+                }
+                let notified = self.progress.notified();
+                match self.poll_next() {
+                    Some(Some(message)) => yield Ok(message),
+                    Some(None) => break,
+                    None => notified.await,
+                }
+            }
+        })
+    }
+
+    async fn close(&self) -> Result<()> {
+        self.connected.store(false, Ordering::SeqCst);
+        self.progress.notify_waiters();
+        Ok(())
+    }
+
+    fn is_ready(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    async fn end_input(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Compare a recorded message with a live one, ignoring request IDs
+///
+/// Returns one line per differing JSON path.
+fn diff_messages(expected: &Value, actual: &Value) -> Vec<String> {
+    let mut expected = expected.clone();
+    let mut actual = actual.clone();
+    redact_secrets(&mut actual);
+    for value in [&mut expected, &mut actual] {
+        if let Some(map) = value.as_object_mut() {
+            map.remove("request_id");
+        }
+        if let Some(map) = value.get_mut("response").and_then(Value::as_object_mut) {
+            map.remove("request_id");
+        }
+    }
+
+    let mut differences = Vec::new();
+    diff_values("$", &expected, &actual, &mut differences);
+    differences
+}
+
+fn diff_values(path: &str, expected: &Value, actual: &Value, out: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(e), Value::Object(a)) => {
+            let mut keys: Vec<&String> = e.keys().chain(a.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = format!("{}.{}", path, key);
+                match (e.get(key), a.get(key)) {
+                    (Some(ev), Some(av)) => diff_values(&child, ev, av, out),
+                    (Some(ev), None) => out.push(format!("- {}: {}", child, ev)),
+                    (None, Some(av)) => out.push(format!("+ {}: {}", child, av)),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(e), Value::Array(a)) if e.len() == a.len() => {
+            for (i, (ev, av)) in e.iter().zip(a).enumerate() {
+                diff_values(&format!("{}[{}]", path, i), ev, av, out);
+            }
+        }
+        _ if expected != actual => {
+            out.push(format!("- {}: {}", path, expected));
+            out.push(format!("+ {}: {}", path, actual));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use serde_json::json;
+
+    fn recorded(direction: MessageDirection, content: Value) -> RecordedMessage {
+        RecordedMessage {
+            offset_ms: 0,
+            direction,
+            content,
+        }
+    }
+
+    #[test]
+    fn test_mode_parsing() {
+        assert_eq!(
+            "record".parse::<CassetteMode>().unwrap(),
+            CassetteMode::Record
+        );
+        assert_eq!(
+            "AUTO".parse::<CassetteMode>().unwrap(),
+            CassetteMode::RecordIfMissing
+        );
+        assert!("bogus".parse::<CassetteMode>().is_err());
+        assert_eq!(
+            CassetteMode::RecordIfMissing.resolve(Path::new("/nonexistent/cassette.json")),
+            CassetteMode::Record
+        );
+    }
+
+    #[test]
+    fn test_redact_secrets() {
+        let mut value = json!({
+            "env": {"ANTHROPIC_API_KEY": "sk-ant-abc123", "GITHUB_TOKEN": "ghp_1", "PATH": "/usr/bin"},
+            "headers": [{"Authorization": "Bearer xyz", "X-Auth-Token": "t"}],
+            "oauth": {"accessToken": "at", "expiresAt": 1},
+            "text": "key is sk-ant-api03-XYZ_9, keep this",
+            "usage": {"input_tokens": 10}
+        });
+        redact_secrets(&mut value);
+
+        assert_eq!(value["env"]["ANTHROPIC_API_KEY"], REDACTED);
+        assert_eq!(value["env"]["GITHUB_TOKEN"], REDACTED);
+        assert_eq!(value["env"]["PATH"], "/usr/bin");
+        assert_eq!(value["headers"][0]["Authorization"], REDACTED);
+        assert_eq!(value["headers"][0]["X-Auth-Token"], REDACTED);
+        assert_eq!(value["oauth"]["accessToken"], REDACTED);
+        assert_eq!(value["oauth"]["expiresAt"], 1);
+        assert_eq!(value["text"], "key is [REDACTED], keep this");
+        assert_eq!(value["usage"]["input_tokens"], 10);
+    }
+
+    #[tokio::test]
+    async fn test_replay_remaps_request_ids() {
+        let transport = ReplayTransport::new(vec![
+            recorded(
+                MessageDirection::Sent,
+                json!({"type": "control_request", "request_id": "req_0_old", "request": {"subtype": "initialize"}}),
+            ),
+            recorded(
+                MessageDirection::Received,
+                json!({"type": "control_response", "response": {"subtype": "success", "request_id": "req_0_old", "response": {}}}),
+            ),
+        ]);
+        transport.connect().await.unwrap();
+        transport
+            .write(r#"{"type":"control_request","request_id":"req_0_new","request":{"subtype":"initialize"}}"#)
+            .await
+            .unwrap();
+
+        let messages: Vec<Value> = transport
+            .read_messages()
+            .map(|m| m.unwrap())
+            .collect()
+            .await;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["response"]["request_id"], "req_0_new");
+        assert!(transport.unmatched_writes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replay_reports_divergence() {
+        let transport = ReplayTransport::new(vec![recorded(
+            MessageDirection::Sent,
+            json!({"type": "user", "message": {"role": "user", "content": "Hello"}}),
+        )]);
+        transport.connect().await.unwrap();
+
+        let err = transport
+            .write(r#"{"type":"user","message":{"role":"user","content":"Goodbye"}}"#)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(r#"- $.message.content: "Hello""#));
+        assert!(err.contains(r#"+ $.message.content: "Goodbye""#));
+    }
+}
@@ -1,9 +1,12 @@
 //! MockClient wrapper for testing
 
+use std::path::Path;
 use std::sync::Arc;
 
 use super::Transport;
+use super::assertions::SessionAssertions;
 use super::scenario::Scenario;
+use super::scenario::ScenarioError;
 use super::transport::{MockTransport, WrittenMessage};
 use crate::client::ClaudeClient;
 use crate::types::config::ClaudeAgentOptions;
@@ -16,6 +19,8 @@ pub struct MockClient {
     transport: Arc<MockTransport>,
     /// Options used to create the client
     options: ClaudeAgentOptions,
+    /// Scenario the transport replays, for write expectations
+    scenario: Option<Scenario>,
 }
 
 impl MockClient {
@@ -24,9 +29,14 @@ impl MockClient {
         Self::from_scenario_with_options(scenario, ClaudeAgentOptions::default())
     }
 
+    /// Create from a YAML or JSON scenario file (see [`Scenario::load`])
+    pub fn from_scenario_file(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        Ok(Self::from_scenario(Scenario::load(path)?))
+    }
+
     /// Create from scenario with custom options
     pub fn from_scenario_with_options(scenario: Scenario, options: ClaudeAgentOptions) -> Self {
-        let transport = Arc::new(MockTransport::from_scenario(scenario));
+        let transport = Arc::new(MockTransport::from_scenario(scenario.clone()));
         let client = ClaudeClient::with_transport(
             Arc::clone(&transport) as Arc<dyn crate::internal::transport::Transport>,
             options.clone(),
@@ -36,6 +46,7 @@ impl MockClient {
             client,
             transport,
             options,
+            scenario: Some(scenario),
         }
     }
 
@@ -51,6 +62,7 @@ impl MockClient {
             client,
             transport,
             options,
+            scenario: None,
         }
     }
 
@@ -156,6 +168,34 @@ impl MockClient {
         );
     }
 
+    /// Assert the scenario's expected writes happened, in order
+    ///
+    /// Does nothing for clients created with [`from_transport`](Self::from_transport).
+    pub fn assert_expected_writes(&self) {
+        let Some(ref scenario) = self.scenario else {
+            return;
+        };
+        let written = self.transport.written_messages();
+        if let Err(e) = scenario.verify_writes(&written) {
+            panic!(
+                "{}; written messages: {:?}",
+                e,
+                written.iter().map(|w| &w.data).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    /// Start fluent assertions over `messages` and everything written so far
+    ///
+    /// Add hook and permission recordings with [`SessionAssertions::hooks`] and
+    /// [`SessionAssertions::permissions`].
+    pub fn assert_session(
+        &self,
+        messages: impl IntoIterator<Item = crate::types::messages::Message>,
+    ) -> SessionAssertions {
+        SessionAssertions::new(messages).writes(&self.transport.written_messages())
+    }
+
     /// Assert exact number of writes
     pub fn assert_write_count(&self, expected: usize) {
         let written = self.transport.written_messages();
@@ -0,0 +1,619 @@
+//! Fake Claude Code CLI for end-to-end tests
+//!
+//! The `claude-fake` binary (built with the `testing` feature) stands in for the real
+//! `claude` executable. Point [`ClaudeAgentOptions::cli_path`](crate::ClaudeAgentOptions)
+//! at it to exercise the real subprocess path — argument building, environment, stdin/stdout
+//! framing, stderr and exit codes — without network access.
+//!
+//! The fake is configured through environment variables, which can be passed with
+//! `ClaudeAgentOptions::env`:
+//!
+//! | Variable | Effect |
+//! |----------|--------|
+//! | `CLAUDE_FAKE_SCRIPT` | Path to a [`Scenario`] (JSON or YAML) or [`SessionSnapshot`] JSON file to replay |
+//! | `CLAUDE_FAKE_ARGV_OUT` | Write `{"args", "cwd", "env"}` as JSON to this path on startup |
+//! | `CLAUDE_FAKE_STDIN_OUT` | Append every line read from stdin to this path |
+//! | `CLAUDE_FAKE_VERSION` | Output of `--version` (default `2.0.45 (Claude Code)`) |
+//! | `CLAUDE_FAKE_STDERR` | Text written to stderr on startup |
+//! | `CLAUDE_FAKE_EXIT_CODE` | Exit code once the session ends (default `0`) |
+//! | `CLAUDE_FAKE_SPEED` | Delay multiplier for scripted timing (default `1.0`, `0` = instant) |
+//! | `CLAUDE_FAKE_LINGER` | `eof`: keep running after stdin closes; `term`: also ignore `SIGTERM` |
+//! | `CLAUDE_FAKE_SPAWN_OUT` | Start a long-running `sleep` child and write its PID to this path |
+//!
+//! # Replay semantics
+//!
+//! - `on_connect` messages are written immediately.
+//! - Each exchange waits for the next `user` message on stdin (or for a line containing
+//!   its `trigger`), then writes its responses.
+//! - Responses of type `control_request` are sent to the SDK and the fake waits for the
+//!   matching `control_response` before continuing. A `hook_callback` request without a
+//!   `callback_id` is routed to the first callback the SDK registered for the input's
+//!   `hook_event_name`.
+//! - Control requests from the SDK (`initialize`, `interrupt`, `set_model`, ...) are
+//!   answered with `success`. Snapshots replay their recorded answers.
+//! - Without `--input-format stream-json`, the prompt is read from stdin until EOF and only
+//!   the first exchange is replayed.
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use super::scenario::Scenario;
+use super::snapshot::{MessageDirection, SessionSnapshot};
+use super::transport::MessageTiming;
+
+/// Path of the scenario or snapshot to replay
+pub const SCRIPT_ENV: &str = "CLAUDE_FAKE_SCRIPT";
+/// Path to write the received arguments, working directory and environment to
+pub const ARGV_OUT_ENV: &str = "CLAUDE_FAKE_ARGV_OUT";
+/// Path to append received stdin lines to
+pub const STDIN_OUT_ENV: &str = "CLAUDE_FAKE_STDIN_OUT";
+/// Version reported by `--version`
+pub const VERSION_ENV: &str = "CLAUDE_FAKE_VERSION";
+/// Text written to stderr on startup
+pub const STDERR_ENV: &str = "CLAUDE_FAKE_STDERR";
+/// Exit code once the session ends
+pub const EXIT_CODE_ENV: &str = "CLAUDE_FAKE_EXIT_CODE";
+/// Delay multiplier for scripted timing
+pub const SPEED_ENV: &str = "CLAUDE_FAKE_SPEED";
+/// Keep running after the session ends, to exercise shutdown escalation
+pub const LINGER_ENV: &str = "CLAUDE_FAKE_LINGER";
+/// Path to write the PID of a spawned descendant to, to exercise process-group cleanup
+pub const SPAWN_OUT_ENV: &str = "CLAUDE_FAKE_SPAWN_OUT";
+
+/// New enough for every [`CliFeature`](crate::cli::CliFeature), so tests using plugins,
+/// file checkpointing or JSON schema output need not override the version
+const DEFAULT_VERSION: &str = "2.0.45 (Claude Code)";
+
+/// A single replay step
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    /// Write a message after a delay
+    Emit {
+        value: serde_json::Value,
+        delay_ms: u64,
+    },
+    /// Wait for the next `user` message
+    AwaitUser,
+    /// Wait for a stdin line containing the pattern
+    AwaitWrite(String),
+    /// Send a control request and wait for the SDK's response
+    Request(serde_json::Value),
+}
+
+/// A replay script compiled from a scenario or snapshot
+#[derive(Debug, Default)]
+struct FakeScript {
+    steps: Vec<Step>,
+    /// Recorded answers to SDK control requests, by subtype
+    canned_replies: HashMap<String, VecDeque<serde_json::Value>>,
+}
+
+impl FakeScript {
+    fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        if matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("yaml" | "yml")
+        ) {
+            let scenario = Scenario::load(path).map_err(|e| e.to_string())?;
+            return Ok(Self::from_scenario(&scenario));
+        }
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let value: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+        if value.get("messages").is_some() && value.get("version").is_some() {
+            let snapshot: SessionSnapshot =
+                serde_json::from_value(value).map_err(|e| format!("invalid snapshot: {}", e))?;
+            Ok(Self::from_snapshot(&snapshot))
+        } else {
+            // Scenario files use the documented format; a `Scenario` serialized with
+            // serde (as tests write them) is accepted too
+            let scenario = match Scenario::from_json_str(&content) {
+                Ok(scenario) => scenario,
+                Err(e) => serde_json::from_value(value).map_err(|_| e.to_string())?,
+            };
+            Ok(Self::from_scenario(&scenario))
+        }
+    }
+
+    fn from_scenario(scenario: &Scenario) -> Self {
+        let mut steps = Vec::new();
+        for message in &scenario.on_connect {
+            push_scheduled(&mut steps, &message.value, &message.timing);
+        }
+        for exchange in &scenario.exchanges {
+            steps.push(match exchange.trigger_pattern {
+                Some(ref pattern) => Step::AwaitWrite(pattern.clone()),
+                None => Step::AwaitUser,
+            });
+            for message in &exchange.responses {
+                push_scheduled(&mut steps, &message.value, &message.timing);
+            }
+        }
+        Self {
+            steps,
+            canned_replies: HashMap::new(),
+        }
+    }
+
+    fn from_snapshot(snapshot: &SessionSnapshot) -> Self {
+        let mut script = Self::default();
+        let mut sent_subtypes: HashMap<String, String> = HashMap::new();
+        let mut last_offset = 0;
+
+        for message in &snapshot.messages {
+            let content = &message.content;
+            let msg_type = content.get("type").and_then(|t| t.as_str());
+            match (message.direction, msg_type) {
+                (MessageDirection::Sent, Some("user")) => script.steps.push(Step::AwaitUser),
+                (MessageDirection::Sent, Some("control_request")) => {
+                    if let (Some(id), Some(subtype)) = (
+                        content["request_id"].as_str(),
+                        content["request"]["subtype"].as_str(),
+                    ) {
+                        sent_subtypes.insert(id.to_string(), subtype.to_string());
+                    }
+                }
+                (MessageDirection::Sent, _) => {}
+                (MessageDirection::Received, Some("control_response")) => {
+                    let subtype = content["response"]["request_id"]
+                        .as_str()
+                        .and_then(|id| sent_subtypes.get(id));
+                    if let Some(subtype) = subtype {
+                        script
+                            .canned_replies
+                            .entry(subtype.clone())
+                            .or_default()
+                            .push_back(content["response"]["response"].clone());
+                    }
+                }
+                (MessageDirection::Received, Some("control_request")) => {
+                    script.steps.push(Step::Request(content["request"].clone()));
+                }
+                (MessageDirection::Received, _) => {
+                    script.steps.push(Step::Emit {
+                        value: content.clone(),
+                        delay_ms: message.offset_ms.saturating_sub(last_offset),
+                    });
+                    last_offset = message.offset_ms;
+                }
+            }
+        }
+        script
+    }
+}
+
+fn push_scheduled(steps: &mut Vec<Step>, value: &serde_json::Value, timing: &MessageTiming) {
+    let delay_ms = match timing {
+        MessageTiming::Immediate => 0,
+        MessageTiming::Delayed { base_ms, .. } => *base_ms,
+        MessageTiming::AfterWrite { pattern } => {
+            steps.push(Step::AwaitWrite(pattern.clone()));
+            0
+        }
+    };
+    if value.get("type").and_then(|t| t.as_str()) == Some("control_request") {
+        steps.push(Step::Request(
+            value
+                .get("request")
+                .cloned()
+                .unwrap_or_else(|| value.clone()),
+        ));
+    } else {
+        steps.push(Step::Emit {
+            value: value.clone(),
+            delay_ms,
+        });
+    }
+}
+
+/// A line read from stdin
+struct Incoming {
+    raw: String,
+    value: Option<serde_json::Value>,
+}
+
+struct FakeSession {
+    script: FakeScript,
+    streaming: bool,
+    speed: f64,
+    stdin_rx: mpsc::UnboundedReceiver<String>,
+    stdin_log: Option<std::fs::File>,
+    /// Lines read while waiting for something else
+    pending: VecDeque<Incoming>,
+    /// Hook registrations from `initialize`
+    hooks: serde_json::Value,
+    request_counter: u64,
+    prompt_consumed: bool,
+}
+
+impl FakeSession {
+    fn write_line(&self, value: &serde_json::Value) {
+        let mut stdout = std::io::stdout().lock();
+        let _ = writeln!(stdout, "{}", value);
+        let _ = stdout.flush();
+    }
+
+    /// Read the next stdin line, answering SDK control requests along the way
+    async fn recv(&mut self) -> Option<Incoming> {
+        let raw = self.stdin_rx.recv().await?;
+        Some(self.accept(raw))
+    }
+
+    fn accept(&mut self, raw: String) -> Incoming {
+        if let Some(ref mut log) = self.stdin_log {
+            let _ = writeln!(log, "{}", raw);
+        }
+        let value: Option<serde_json::Value> = serde_json::from_str(&raw).ok();
+        if let Some(ref v) = value
+            && v.get("type").and_then(|t| t.as_str()) == Some("control_request")
+        {
+            self.answer_control_request(v);
+        }
+        Incoming { raw, value }
+    }
+
+    /// Process lines that are already available without blocking
+    fn drain(&mut self) {
+        while let Ok(raw) = self.stdin_rx.try_recv() {
+            let incoming = self.accept(raw);
+            self.pending.push_back(incoming);
+        }
+    }
+
+    async fn next_line(&mut self) -> Option<Incoming> {
+        match self.pending.pop_front() {
+            Some(incoming) => Some(incoming),
+            None => self.recv().await,
+        }
+    }
+
+    fn answer_control_request(&mut self, request: &serde_json::Value) {
+        let request_id = request["request_id"].clone();
+        let subtype = request["request"]["subtype"].as_str().unwrap_or_default();
+
+        if subtype == "initialize" {
+            self.hooks = request["request"]["hooks"].clone();
+        }
+
+        let canned = self
+            .script
+            .canned_replies
+            .get_mut(subtype)
+            .and_then(|replies| replies.pop_front());
+        let data = match (canned, subtype) {
+            (Some(data), _) => data,
+            (None, "initialize") => json!({"commands": [], "output_style": "default"}),
+            (None, _) => json!({}),
+        };
+
+        self.write_line(&json!({
+            "type": "control_response",
+            "response": {
+                "subtype": "success",
+                "request_id": request_id,
+                "response": data
+            }
+        }));
+    }
+
+    /// Wait for a user message; returns false when the session should end
+    async fn await_user(&mut self) -> bool {
+        if !self.streaming {
+            // One-shot mode: the prompt is everything on stdin
+            if self.prompt_consumed {
+                return false;
+            }
+            while self.next_line().await.is_some() {}
+            self.prompt_consumed = true;
+            return true;
+        }
+        while let Some(incoming) = self.next_line().await {
+            let is_user = incoming
+                .value
+                .as_ref()
+                .and_then(|v| v.get("type"))
+                .and_then(|t| t.as_str())
+                == Some("user");
+            if is_user {
+                return true;
+            }
+        }
+        false
+    }
+
+    async fn await_write(&mut self, pattern: &str) -> bool {
+        while let Some(incoming) = self.next_line().await {
+            if incoming.raw.contains(pattern) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Send a control request to the SDK and wait for its response
+    async fn request(&mut self, mut request: serde_json::Value) -> bool {
+        self.request_counter += 1;
+        let request_id = format!("req_fake_{}", self.request_counter);
+
+        if request["subtype"] == "hook_callback" && request.get("callback_id").is_none() {
+            let event = request["input"]["hook_event_name"]
+                .as_str()
+                .unwrap_or_default();
+            if let Some(id) = self.hooks[event][0]["hookCallbackIds"][0].as_str() {
+                request["callback_id"] = json!(id);
+            }
+        }
+
+        self.write_line(&json!({
+            "type": "control_request",
+            "request_id": request_id,
+            "request": request
+        }));
+
+        loop {
+            let Some(incoming) = self.recv().await else {
+                return false;
+            };
+            let answered = incoming.value.as_ref().is_some_and(|v| {
+                v["type"] == "control_response" && v["response"]["request_id"] == request_id
+            });
+            if answered {
+                return true;
+            }
+            self.pending.push_back(incoming);
+        }
+    }
+
+    async fn run(&mut self) {
+        let steps = std::mem::take(&mut self.script.steps);
+        for step in steps {
+            let keep_going = match step {
+                Step::Emit { value, delay_ms } => {
+                    let scaled = (delay_ms as f64 * self.speed) as u64;
+                    if scaled > 0 {
+                        tokio::time::sleep(Duration::from_millis(scaled)).await;
+                    }
+                    self.drain();
+                    self.write_line(&value);
+                    true
+                }
+                Step::AwaitUser => self.await_user().await,
+                Step::AwaitWrite(pattern) => self.await_write(&pattern).await,
+                Step::Request(request) => self.request(request).await,
+            };
+            if !keep_going {
+                return;
+            }
+        }
+
+        // Keep answering control requests until the SDK closes stdin
+        if self.streaming {
+            while self.recv().await.is_some() {}
+        }
+    }
+}
+
+/// Run the fake CLI with the process arguments and environment
+///
+/// Returns the process exit code.
+pub async fn run() -> i32 {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.iter().any(|a| a == "--version" || a == "-v") {
+        let version = std::env::var(VERSION_ENV).unwrap_or_else(|_| DEFAULT_VERSION.to_string());
+        println!("{}", version);
+        return 0;
+    }
+
+    if let Ok(message) = std::env::var(STDERR_ENV) {
+        eprintln!("{}", message);
+    }
+
+    let linger = std::env::var(LINGER_ENV).ok();
+    #[cfg(unix)]
+    if linger.as_deref() == Some("term") {
+        // SAFETY: installs the SIG_IGN disposition; no handler code runs
+        unsafe {
+            libc::signal(libc::SIGTERM, libc::SIG_IGN);
+        }
+    }
+
+    if let Ok(path) = std::env::var(ARGV_OUT_ENV) {
+        let env: HashMap<String, String> = std::env::vars().collect();
+        let cwd = std::env::current_dir()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let dump = json!({"args": args, "cwd": cwd, "env": env});
+        if let Err(e) = std::fs::write(&path, dump.to_string()) {
+            eprintln!("claude-fake: failed to write {}: {}", path, e);
+            return 2;
+        }
+    }
+
+    if let Ok(path) = std::env::var(SPAWN_OUT_ENV) {
+        // Stands in for a Bash command or MCP server the real CLI would start; it is
+        // deliberately never waited on
+        let child = match std::process::Command::new("sleep")
+            .arg("300")
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("claude-fake: failed to spawn child: {}", e);
+                return 2;
+            }
+        };
+        if let Err(e) = std::fs::write(&path, child.id().to_string()) {
+            eprintln!("claude-fake: failed to write {}: {}", path, e);
+            return 2;
+        }
+    }
+
+    let script = match std::env::var(SCRIPT_ENV) {
+        Ok(path) => match FakeScript::load(&path) {
+            Ok(script) => script,
+            Err(e) => {
+                eprintln!("claude-fake: failed to load {}: {}", path, e);
+                return 2;
+            }
+        },
+        Err(_) => FakeScript::default(),
+    };
+
+    let stdin_log = std::env::var(STDIN_OUT_ENV).ok().and_then(|path| {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .ok()
+    });
+
+    // Blocking reader thread: stdin is read line by line until EOF
+    let (stdin_tx, stdin_rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if stdin_tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut session = FakeSession {
+        script,
+        streaming: args
+            .windows(2)
+            .any(|w| w[0] == "--input-format" && w[1] == "stream-json"),
+        speed: std::env::var(SPEED_ENV)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(1.0),
+        stdin_rx,
+        stdin_log,
+        pending: VecDeque::new(),
+        hooks: serde_json::Value::Null,
+        request_counter: 0,
+        prompt_consumed: false,
+    };
+    session.run().await;
+
+    if linger.is_some() {
+        std::future::pending::<()>().await;
+    }
+
+    std::env::var(EXIT_CODE_ENV)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::builders::{AssistantMessageBuilder, ResultMessageBuilder};
+    use crate::testing::scenario::ScenarioBuilder;
+    use crate::testing::snapshot::RecordedMessage;
+
+    #[test]
+    fn test_default_version_supports_every_feature() {
+        let version = crate::cli::CliVersion::from_version_output(DEFAULT_VERSION).unwrap();
+        let capabilities = crate::cli::CliCapabilities::for_version(version);
+        for feature in crate::cli::CliFeature::ALL {
+            assert!(capabilities.supports(feature), "{:?}", feature);
+        }
+    }
+
+    #[test]
+    fn test_script_from_scenario() {
+        let scenario = ScenarioBuilder::new("s")
+            .exchange()
+            .respond(AssistantMessageBuilder::new().text("hi").build())
+            .then_result(ResultMessageBuilder::default().build())
+            .exchange()
+            .when_write_contains("again")
+            .respond(AssistantMessageBuilder::new().text("again").build())
+            .build();
+
+        let script = FakeScript::from_scenario(&scenario);
+        assert_eq!(script.steps.len(), 5);
+        assert_eq!(script.steps[0], Step::AwaitUser);
+        assert!(matches!(script.steps[1], Step::Emit { delay_ms: 100, .. }));
+        assert_eq!(script.steps[3], Step::AwaitWrite("again".to_string()));
+    }
+
+    #[test]
+    fn test_script_loads_yaml_scenario() {
+        let script = FakeScript::load(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/fixtures/scenarios/read_file.yaml"
+        ))
+        .unwrap();
+
+        assert!(matches!(script.steps[0], Step::Emit { delay_ms: 0, .. }));
+        assert_eq!(
+            script.steps[1],
+            Step::AwaitWrite("capital of France".to_string())
+        );
+        assert_eq!(script.steps.len(), 7);
+    }
+
+    #[test]
+    fn test_script_from_snapshot() {
+        let recorded = |offset_ms, direction, content| RecordedMessage {
+            offset_ms,
+            direction,
+            content,
+        };
+        let snapshot = SessionSnapshot {
+            version: 1,
+            recorded_at: String::new(),
+            sdk_version: String::new(),
+            cli_version: None,
+            options: serde_json::Value::Null,
+            messages: vec![
+                recorded(
+                    0,
+                    MessageDirection::Sent,
+                    json!({"type": "control_request", "request_id": "req_0", "request": {"subtype": "initialize"}}),
+                ),
+                recorded(
+                    5,
+                    MessageDirection::Received,
+                    json!({"type": "control_response", "response": {"subtype": "success", "request_id": "req_0", "response": {"commands": ["/x"]}}}),
+                ),
+                recorded(10, MessageDirection::Sent, json!({"type": "user"})),
+                recorded(
+                    30,
+                    MessageDirection::Received,
+                    json!({"type": "control_request", "request_id": "cli_1", "request": {"subtype": "hook_callback"}}),
+                ),
+                recorded(40, MessageDirection::Received, json!({"type": "result"})),
+            ],
+        };
+
+        let script = FakeScript::from_snapshot(&snapshot);
+        assert_eq!(
+            script.steps,
+            vec![
+                Step::AwaitUser,
+                Step::Request(json!({"subtype": "hook_callback"})),
+                Step::Emit {
+                    value: json!({"type": "result"}),
+                    delay_ms: 40
+                },
+            ]
+        );
+        assert_eq!(
+            script.canned_replies["initialize"][0],
+            json!({"commands": ["/x"]})
+        );
+    }
+}
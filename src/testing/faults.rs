@@ -0,0 +1,420 @@
+//! Deterministic fault injection for [`MockTransport`](super::MockTransport)
+//!
+//! A [`FaultPlan`] describes failures a real CLI process can produce: stdout closing
+//! (possibly mid-message), stray non-JSON lines, JSON arriving in several reads, slow
+//! output, broken stdin and the process exiting. Faulty output is written as raw bytes
+//! to a pipe and decoded by the same line reader the subprocess transport uses, so
+//! partial lines are reassembled by the SDK itself. Messages are counted in delivery
+//! order and writes in call order, and any randomness (split points, read delays)
+//! comes from the transport's seeded [`TimingConfig`](super::TimingConfig), so a plan
+//! always fails the same way.
+//!
+//! # Example
+//!
+//! ```
+//! use claude_agent_sdk_rs::testing::{FaultPlan, MockTransport};
+//!
+//! let transport = MockTransport::builder()
+//!     .message(serde_json::json!({"type": "system", "subtype": "init"}))
+//!     .faults(
+//!         FaultPlan::new()
+//!             .garbage_line(0, "Warning: config file not found")
+//!             .disconnect_mid_message(1),
+//!     )
+//!     .seed(42)
+//!     .build();
+//! ```
+
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use tokio::io::{AsyncWriteExt, BufReader};
+
+use crate::errors::{ClaudeError, Result};
+use crate::internal::transport::subprocess::{
+    BoundedLine, DEFAULT_MAX_BUFFER_SIZE, decode_line, read_bounded_line,
+};
+
+/// Bytes the simulated stdout pipe holds before the writer waits for the reader
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+/// A single scheduled failure
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    /// Close stdout once `after_messages` messages have been delivered
+    ///
+    /// With `mid_message`, the next message is cut off part-way through its line
+    /// before the stream ends.
+    Disconnect {
+        /// Number of complete messages delivered first
+        after_messages: usize,
+        /// Whether to emit a truncated message before closing
+        mid_message: bool,
+    },
+    /// Emit a non-JSON line just before message `before_message`
+    GarbageLine {
+        /// Index of the message the line precedes
+        before_message: usize,
+        /// The raw line
+        line: String,
+    },
+    /// Deliver message `message` in `chunks` separate reads
+    SplitMessage {
+        /// Index of the message to split
+        message: usize,
+        /// Number of reads (at least 2)
+        chunks: usize,
+    },
+    /// Delay every read by a seeded random amount in `min_ms..=max_ms`
+    SlowReads {
+        /// Minimum delay in milliseconds
+        min_ms: u64,
+        /// Maximum delay in milliseconds
+        max_ms: u64,
+    },
+    /// Fail every write after the first `after_writes` succeed
+    FailWrites {
+        /// Number of successful writes
+        after_writes: usize,
+    },
+    /// Exit the "process" right after write number `after_writes`
+    ///
+    /// The read stream ends with a process error and further writes fail, leaving
+    /// any control request sent in that write unanswered.
+    ProcessExit {
+        /// Number of writes accepted before exiting
+        after_writes: usize,
+        /// Exit code reported in the process error
+        exit_code: i32,
+    },
+}
+
+/// An ordered set of faults applied by a [`MockTransport`](super::MockTransport)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaultPlan {
+    faults: Vec<Fault>,
+}
+
+impl FaultPlan {
+    /// Create an empty plan
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a fault
+    pub fn fault(mut self, fault: Fault) -> Self {
+        self.faults.push(fault);
+        self
+    }
+
+    /// Close stdout cleanly after `messages` messages
+    pub fn disconnect_after(self, messages: usize) -> Self {
+        self.fault(Fault::Disconnect {
+            after_messages: messages,
+            mid_message: false,
+        })
+    }
+
+    /// Close stdout part-way through the message after the first `messages`
+    pub fn disconnect_mid_message(self, messages: usize) -> Self {
+        self.fault(Fault::Disconnect {
+            after_messages: messages,
+            mid_message: true,
+        })
+    }
+
+    /// Emit a non-JSON line before message `before_message`
+    pub fn garbage_line(self, before_message: usize, line: impl Into<String>) -> Self {
+        self.fault(Fault::GarbageLine {
+            before_message,
+            line: line.into(),
+        })
+    }
+
+    /// Deliver message `message` across `chunks` reads
+    pub fn split_message(self, message: usize, chunks: usize) -> Self {
+        self.fault(Fault::SplitMessage {
+            message,
+            chunks: chunks.max(2),
+        })
+    }
+
+    /// Delay each read by a seeded random amount
+    pub fn slow_reads(self, min_ms: u64, max_ms: u64) -> Self {
+        self.fault(Fault::SlowReads {
+            min_ms,
+            max_ms: max_ms.max(min_ms),
+        })
+    }
+
+    /// Fail writes after `writes` successful ones
+    pub fn fail_writes_after(self, writes: usize) -> Self {
+        self.fault(Fault::FailWrites {
+            after_writes: writes,
+        })
+    }
+
+    /// Exit with `exit_code` right after write number `writes`
+    pub fn exit_after_writes(self, writes: usize, exit_code: i32) -> Self {
+        self.fault(Fault::ProcessExit {
+            after_writes: writes,
+            exit_code,
+        })
+    }
+
+    /// The scheduled faults
+    pub fn faults(&self) -> &[Fault] {
+        &self.faults
+    }
+
+    /// Whether the plan is empty
+    pub fn is_empty(&self) -> bool {
+        self.faults.is_empty()
+    }
+
+    /// Number of writes allowed before writes start failing
+    pub(crate) fn write_limit(&self) -> Option<usize> {
+        self.faults
+            .iter()
+            .filter_map(|f| match f {
+                Fault::FailWrites { after_writes } => Some(*after_writes),
+                _ => None,
+            })
+            .min()
+    }
+
+    /// Exit code to report if the process exits after `writes` writes
+    pub(crate) fn exit_at(&self, writes: usize) -> Option<i32> {
+        self.faults.iter().find_map(|f| match f {
+            Fault::ProcessExit {
+                after_writes,
+                exit_code,
+            } if *after_writes == writes => Some(*exit_code),
+            _ => None,
+        })
+    }
+
+    fn disconnect(&self) -> Option<(usize, bool)> {
+        self.faults
+            .iter()
+            .filter_map(|f| match f {
+                Fault::Disconnect {
+                    after_messages,
+                    mid_message,
+                } => Some((*after_messages, *mid_message)),
+                _ => None,
+            })
+            .min()
+    }
+
+    fn slow_read_range(&self) -> Option<(u64, u64)> {
+        self.faults.iter().find_map(|f| match f {
+            Fault::SlowReads { min_ms, max_ms } => Some((*min_ms, *max_ms)),
+            _ => None,
+        })
+    }
+}
+
+/// Applies a [`FaultPlan`] to the read side of a mock transport
+pub(crate) struct FaultInjector {
+    plan: FaultPlan,
+    rng: StdRng,
+    speed_factor: f64,
+    delivered: usize,
+}
+
+impl FaultInjector {
+    pub(crate) fn new(plan: FaultPlan, seed: u64, speed_factor: f64) -> Self {
+        Self {
+            plan,
+            rng: StdRng::seed_from_u64(seed),
+            speed_factor,
+            delivered: 0,
+        }
+    }
+
+    /// Whether stdout should already be closed before the next message
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.plan
+            .disconnect()
+            .is_some_and(|(after, mid)| !mid && self.delivered >= after)
+    }
+
+    /// Turn one outgoing message into the items the SDK would read
+    ///
+    /// Returns the items and whether the stream ends after them.
+    pub(crate) async fn deliver(
+        &mut self,
+        value: serde_json::Value,
+    ) -> (Vec<Result<serde_json::Value>>, bool) {
+        let index = self.delivered;
+
+        if let Some((min_ms, max_ms)) = self.plan.slow_read_range() {
+            let delay = self.rng.gen_range(min_ms..=max_ms);
+            self.sleep(delay).await;
+        }
+
+        // Raw output for this message, written to a pipe the SDK's line reader consumes
+        let mut chunks: Vec<String> = self
+            .plan
+            .faults
+            .iter()
+            .filter_map(|fault| match fault {
+                Fault::GarbageLine {
+                    before_message,
+                    line,
+                } if *before_message == index => Some(format!("{}\n", line)),
+                _ => None,
+            })
+            .collect();
+
+        let line = format!("{}\n", value);
+
+        if let Some((after, true)) = self.plan.disconnect()
+            && after == index
+        {
+            // EOF in the middle of a line: the reader sees the truncated fragment,
+            // which always stops before the closing delimiter
+            let cut = self.cut_point(&line, 1, line.len().saturating_sub(3));
+            chunks.push(line[..cut].to_string());
+            return (self.read_through_pipe(chunks).await, true);
+        }
+
+        let split = self.plan.faults.iter().find_map(|f| match f {
+            Fault::SplitMessage { message, chunks } if *message == index => Some(*chunks),
+            _ => None,
+        });
+        let items = match split {
+            Some(split) => {
+                let mut cuts: Vec<usize> = (1..split)
+                    .map(|_| self.cut_point(&line, 1, line.len() - 1))
+                    .collect();
+                cuts.sort_unstable();
+                cuts.dedup();
+
+                let mut start = 0;
+                for end in cuts.into_iter().chain(std::iter::once(line.len())) {
+                    chunks.push(line[start..end].to_string());
+                    start = end;
+                }
+                self.read_through_pipe(chunks).await
+            }
+            None if chunks.is_empty() => vec![Ok(value)],
+            None => {
+                chunks.push(line);
+                self.read_through_pipe(chunks).await
+            }
+        };
+
+        self.delivered += 1;
+        (items, self.is_disconnected())
+    }
+
+    /// Write `chunks` to a pipe one read at a time and decode them as the CLI's stdout
+    ///
+    /// The writer pauses after every chunk, so the reader sees each fragment separately
+    /// and has to reassemble lines itself. The pipe is closed after the last chunk.
+    async fn read_through_pipe(&self, chunks: Vec<String>) -> Vec<Result<serde_json::Value>> {
+        let (mut writer, reader) = tokio::io::duplex(PIPE_CAPACITY);
+
+        let write = async move {
+            for chunk in chunks {
+                // The reader only goes away after an I/O error, which it reports
+                if writer.write_all(chunk.as_bytes()).await.is_err() {
+                    break;
+                }
+                self.sleep(1).await;
+                tokio::task::yield_now().await;
+            }
+        };
+        let read = async {
+            let mut reader = BufReader::new(reader);
+            let mut line = Vec::new();
+            let mut items = Vec::new();
+            loop {
+                match read_bounded_line(&mut reader, &mut line, DEFAULT_MAX_BUFFER_SIZE).await {
+                    Ok(BoundedLine::Eof) => break,
+                    Ok(read) => items.extend(decode_line(&read, &line, DEFAULT_MAX_BUFFER_SIZE)),
+                    Err(e) => {
+                        items.push(Err(ClaudeError::Transport(format!(
+                            "Failed to read line: {}",
+                            e
+                        ))));
+                        break;
+                    }
+                }
+            }
+            items
+        };
+
+        let ((), items) = tokio::join!(write, read);
+        items
+    }
+
+    /// Seeded byte offset in `min..=max`, moved back to a char boundary
+    fn cut_point(&mut self, s: &str, min: usize, max: usize) -> usize {
+        let mut cut = self.rng.gen_range(min..=max.max(min));
+        while cut > 0 && !s.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        cut
+    }
+
+    async fn sleep(&self, ms: u64) {
+        let delay = Duration::from_millis((ms as f64 * self.speed_factor) as u64);
+        if delay > Duration::ZERO {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_split_message_is_reassembled() {
+        let plan = FaultPlan::new()
+            .garbage_line(0, "Debugger attached.")
+            .split_message(0, 16);
+        let mut injector = FaultInjector::new(plan, 7, 0.0);
+        let value = json!({"type": "assistant", "text": "héllo wörld ✓"});
+
+        let (items, end) = injector.deliver(value.clone()).await;
+        assert!(!end);
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[0], Err(ClaudeError::JsonDecode(_))));
+        assert_eq!(items[1].as_ref().unwrap(), &value);
+    }
+
+    #[tokio::test]
+    async fn test_garbage_and_mid_message_disconnect() {
+        let plan = FaultPlan::new()
+            .garbage_line(1, "npm WARN deprecated")
+            .disconnect_mid_message(1);
+        let mut injector = FaultInjector::new(plan, 1, 0.0);
+
+        let (items, end) = injector.deliver(json!({"n": 0})).await;
+        assert_eq!(items.len(), 1);
+        assert!(!end);
+
+        let (items, end) = injector.deliver(json!({"n": 1, "pad": "xxxx"})).await;
+        assert!(end);
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[0], Err(ClaudeError::JsonDecode(_))));
+        assert!(matches!(items[1], Err(ClaudeError::JsonDecode(_))));
+    }
+
+    #[test]
+    fn test_write_faults() {
+        let plan = FaultPlan::new()
+            .fail_writes_after(3)
+            .exit_after_writes(1, 137);
+        assert_eq!(plan.write_limit(), Some(3));
+        assert_eq!(plan.exit_at(1), Some(137));
+        assert_eq!(plan.exit_at(2), None);
+    }
+}
@@ -0,0 +1,404 @@
+//! Golden transcripts: review agent interactions as checked-in text files
+//!
+//! A [`Transcript`] renders a message sequence — live [`Message`]s or a recorded
+//! [`SessionSnapshot`] — into a stable, line-oriented text format. Values that change
+//! between runs are replaced by numbered placeholders: UUIDs and session, request,
+//! message and tool use IDs become `<uuid-1>`, `<session-1>`, `<request-1>`,
+//! `<message-1>`, `<tool-1>` and so on (the same value always maps to the same
+//! placeholder), and durations and timestamps are elided.
+//!
+//! [`Transcript::assert_golden`] compares the rendering against a golden file and
+//! panics with a unified diff on mismatch. Set `CLAUDE_UPDATE_GOLDENS=1` to write the
+//! current rendering instead, then review the change with `git diff`:
+//!
+//! ```no_run
+//! use claude_agent_sdk_rs::testing::Transcript;
+//! # fn example(messages: Vec<claude_agent_sdk_rs::Message>) {
+//! Transcript::from_messages(&messages).assert_golden("tests/golden/read_file.golden");
+//! # }
+//! ```
+//!
+//! A rendered entry looks like:
+//!
+//! ```text
+//! <- assistant
+//!   model: "claude-sonnet-4-5"
+//!   text: "Let me read it."
+//!   tool_use Read <tool-1>: {"file_path":"/etc/hosts"}
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use serde_json::Value;
+
+use super::snapshot::{MessageDirection, SessionSnapshot};
+use crate::types::messages::Message;
+
+/// Environment variable that makes [`Transcript::assert_golden`] rewrite golden files
+pub const GOLDEN_UPDATE_ENV: &str = "CLAUDE_UPDATE_GOLDENS";
+
+/// Fields whose string values are replaced by a numbered placeholder of this kind
+const ID_FIELDS: &[(&str, &str)] = &[
+    ("session_id", "session"),
+    ("uuid", "uuid"),
+    ("request_id", "request"),
+    ("tool_use_id", "tool"),
+    ("parent_tool_use_id", "tool"),
+    ("callback_id", "callback"),
+];
+
+/// Fields that vary with wall-clock time and are elided
+const TIMING_FIELDS: &[&str] = &["duration_ms", "duration_api_ms", "timestamp", "offset_ms"];
+
+/// A normalized, human-readable rendering of a message sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transcript {
+    text: String,
+}
+
+impl Transcript {
+    /// Render messages received by the client
+    pub fn from_messages(messages: &[Message]) -> Self {
+        let mut renderer = Renderer::default();
+        for message in messages {
+            let value = serde_json::to_value(message).expect("Message serialization");
+            renderer.entry("<-", &value);
+        }
+        renderer.finish()
+    }
+
+    /// Render a recorded session, including what the SDK sent (`->`)
+    pub fn from_snapshot(snapshot: &SessionSnapshot) -> Self {
+        let mut renderer = Renderer::default();
+        for message in &snapshot.messages {
+            let arrow = match message.direction {
+                MessageDirection::Received => "<-",
+                MessageDirection::Sent => "->",
+            };
+            renderer.entry(arrow, &message.content);
+        }
+        renderer.finish()
+    }
+
+    /// The rendered text
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Compare against the golden file at `path`
+    ///
+    /// With `CLAUDE_UPDATE_GOLDENS` set to `1` or `true`, the file (and its parent
+    /// directories) is written instead. Otherwise a missing file or a mismatch panics,
+    /// the latter with a unified diff from the golden to the current rendering.
+    #[track_caller]
+    pub fn assert_golden(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        if update_requested() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .unwrap_or_else(|e| panic!("cannot create {}: {}", parent.display(), e));
+            }
+            std::fs::write(path, &self.text)
+                .unwrap_or_else(|e| panic!("cannot write golden {}: {}", path.display(), e));
+            return;
+        }
+
+        let expected = match std::fs::read_to_string(path) {
+            Ok(expected) => expected.replace("\r\n", "\n"),
+            Err(e) => panic!(
+                "cannot read golden {}: {}\nrerun with {}=1 to create it",
+                path.display(),
+                e,
+                GOLDEN_UPDATE_ENV
+            ),
+        };
+        if let Some(diff) = self.diff(&expected) {
+            panic!(
+                "transcript does not match golden {}\nrerun with {}=1 to accept the change\n\n{}",
+                path.display(),
+                GOLDEN_UPDATE_ENV,
+                diff
+            );
+        }
+    }
+
+    /// Unified diff from `expected` to this transcript, or `None` when they match
+    pub fn diff(&self, expected: &str) -> Option<String> {
+        if expected == self.text {
+            return None;
+        }
+        Some(
+            similar::TextDiff::from_lines(expected, self.text.as_str())
+                .unified_diff()
+                .context_radius(3)
+                .header("golden", "actual")
+                .to_string(),
+        )
+    }
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+fn update_requested() -> bool {
+    std::env::var(GOLDEN_UPDATE_ENV).is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+#[derive(Default)]
+struct Renderer {
+    out: String,
+    placeholders: HashMap<String, String>,
+    counters: HashMap<&'static str, usize>,
+}
+
+impl Renderer {
+    fn entry(&mut self, arrow: &str, value: &Value) {
+        let value = self.normalize(None, value);
+        let Some(fields) = value.as_object() else {
+            let _ = writeln!(self.out, "{} {}\n", arrow, value);
+            return;
+        };
+
+        let kind = fields.get("type").and_then(Value::as_str).unwrap_or("?");
+        let subtype = match kind {
+            "control_request" => value["request"]["subtype"].as_str(),
+            "control_response" => value["response"]["subtype"].as_str(),
+            "stream_event" => value["event"]["type"].as_str(),
+            _ => fields.get("subtype").and_then(Value::as_str),
+        };
+        match subtype {
+            Some(subtype) => {
+                let _ = writeln!(self.out, "{} {} {}", arrow, kind, subtype);
+            }
+            None => {
+                let _ = writeln!(self.out, "{} {}", arrow, kind);
+            }
+        }
+
+        for (key, field) in fields {
+            match key.as_str() {
+                "type" | "subtype" => {}
+                "message" if matches!(kind, "assistant" | "user") && field.is_object() => {
+                    self.inner_message(field)
+                }
+                "content" if field.is_array() => self.blocks(field),
+                _ => self.field(key, field),
+            }
+        }
+        self.out.push('\n');
+    }
+
+    /// Fields of an assistant/user `message` object, with content blocks expanded
+    fn inner_message(&mut self, message: &Value) {
+        for (key, field) in message.as_object().into_iter().flatten() {
+            match key.as_str() {
+                "role" | "type" => {}
+                "content" if field.is_array() => self.blocks(field),
+                _ => self.field(key, field),
+            }
+        }
+    }
+
+    fn blocks(&mut self, content: &Value) {
+        for block in content.as_array().into_iter().flatten() {
+            match block["type"].as_str() {
+                Some("text") => self.field("text", &block["text"]),
+                Some("thinking") => self.field("thinking", &block["thinking"]),
+                Some("tool_use") => {
+                    let _ = writeln!(
+                        self.out,
+                        "  tool_use {} {}: {}",
+                        block["name"].as_str().unwrap_or("?"),
+                        block["id"].as_str().unwrap_or("?"),
+                        block["input"]
+                    );
+                }
+                Some("tool_result") => {
+                    let label = if block["is_error"] == true {
+                        "tool_error"
+                    } else {
+                        "tool_result"
+                    };
+                    let _ = writeln!(
+                        self.out,
+                        "  {} {}: {}",
+                        label,
+                        block["tool_use_id"].as_str().unwrap_or("?"),
+                        block["content"]
+                    );
+                }
+                _ => self.field("block", block),
+            }
+        }
+    }
+
+    fn field(&mut self, key: &str, value: &Value) {
+        if !value.is_null() {
+            let _ = writeln!(self.out, "  {}: {}", key, value);
+        }
+    }
+
+    /// Replace run-specific values with placeholders, recursively
+    fn normalize(&mut self, key: Option<&str>, value: &Value) -> Value {
+        match value {
+            Value::Object(fields) => Value::Object(
+                fields
+                    .iter()
+                    .map(|(k, v)| (k.clone(), self.normalize(Some(k), v)))
+                    .collect(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|v| self.normalize(key, v)).collect())
+            }
+            _ if key.is_some_and(|k| TIMING_FIELDS.contains(&k)) && !value.is_null() => {
+                Value::String("<elided>".to_string())
+            }
+            Value::String(s) => {
+                let id_kind = key.and_then(|k| {
+                    ID_FIELDS
+                        .iter()
+                        .find(|(field, _)| *field == k)
+                        .map(|(_, kind)| *kind)
+                });
+                match id_kind {
+                    Some(kind) => Value::String(self.placeholder(kind, s)),
+                    None if key == Some("id") => {
+                        let kind = if s.starts_with("tool") {
+                            "tool"
+                        } else if s.starts_with("msg_") {
+                            "message"
+                        } else {
+                            "id"
+                        };
+                        Value::String(self.placeholder(kind, s))
+                    }
+                    None if uuid::Uuid::parse_str(s).is_ok() => {
+                        Value::String(self.placeholder("uuid", s))
+                    }
+                    None => value.clone(),
+                }
+            }
+            _ => value.clone(),
+        }
+    }
+
+    fn placeholder(&mut self, kind: &'static str, original: &str) -> String {
+        if let Some(existing) = self.placeholders.get(original) {
+            return existing.clone();
+        }
+        let counter = self.counters.entry(kind).or_default();
+        *counter += 1;
+        let placeholder = format!("<{}-{}>", kind, counter);
+        self.placeholders
+            .insert(original.to_string(), placeholder.clone());
+        placeholder
+    }
+
+    fn finish(self) -> Transcript {
+        Transcript { text: self.out }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::builders::{
+        AssistantMessageBuilder, ResultMessageBuilder, SystemMessageBuilder, UserMessageBuilder,
+    };
+
+    fn messages(session_id: &str) -> Vec<Message> {
+        vec![
+            SystemMessageBuilder::default()
+                .session_id(session_id)
+                .build(),
+            AssistantMessageBuilder::new()
+                .text("Let me read it.")
+                .tool_use_with_id(
+                    "toolu_01abc",
+                    "Read",
+                    serde_json::json!({"file_path": "/etc/hosts"}),
+                )
+                .session_id(session_id)
+                .build(),
+            UserMessageBuilder::new()
+                .tool_result("toolu_01abc", "127.0.0.1 localhost")
+                .session_id(session_id)
+                .build(),
+            ResultMessageBuilder::default()
+                .session_id(session_id)
+                .duration_ms(1234)
+                .build(),
+        ]
+    }
+
+    #[test]
+    fn test_transcript_is_stable_across_runs() {
+        let first = Transcript::from_messages(&messages("session-a"));
+        let second = Transcript::from_messages(&messages("session-b"));
+
+        assert_eq!(first, second);
+        let text = first.as_str();
+        assert!(text.contains("<- assistant\n"));
+        assert!(text.contains("  tool_use Read <tool-1>: {\"file_path\":\"/etc/hosts\"}\n"));
+        assert!(text.contains("  tool_result <tool-1>: \"127.0.0.1 localhost\"\n"));
+        assert!(text.contains("  session_id: \"<session-1>\"\n"));
+        assert!(text.contains("  duration_ms: \"<elided>\"\n"));
+        assert!(!text.contains("session-a"));
+    }
+
+    #[test]
+    fn test_snapshot_shows_both_directions() {
+        use crate::testing::snapshot::RecordedMessage;
+
+        let snapshot = SessionSnapshot {
+            version: 1,
+            recorded_at: String::new(),
+            sdk_version: String::new(),
+            cli_version: None,
+            options: Value::Null,
+            messages: vec![
+                RecordedMessage {
+                    offset_ms: 3,
+                    direction: MessageDirection::Sent,
+                    content: serde_json::json!({
+                        "type": "control_request",
+                        "request_id": "req_1_9f0c",
+                        "request": {"subtype": "initialize", "hooks": null},
+                    }),
+                },
+                RecordedMessage {
+                    offset_ms: 9,
+                    direction: MessageDirection::Received,
+                    content: serde_json::json!({
+                        "type": "control_response",
+                        "response": {"subtype": "success", "request_id": "req_1_9f0c"},
+                    }),
+                },
+            ],
+        };
+
+        assert_eq!(
+            Transcript::from_snapshot(&snapshot).as_str(),
+            "-> control_request initialize\n  request: {\"hooks\":null,\"subtype\":\"initialize\"}\n  request_id: \"<request-1>\"\n\n\
+             <- control_response success\n  response: {\"request_id\":\"<request-1>\",\"subtype\":\"success\"}\n\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_is_unified() {
+        let transcript = Transcript::from_messages(&messages("s"));
+        assert!(transcript.diff(transcript.as_str()).is_none());
+
+        let golden = transcript.as_str().replace("Let me read it.", "Reading.");
+        let diff = transcript.diff(&golden).unwrap();
+        assert!(diff.contains("--- golden\n+++ actual\n"));
+        assert!(diff.contains("-  text: \"Reading.\"\n"));
+        assert!(diff.contains("+  text: \"Let me read it.\"\n"));
+    }
+}
@@ -9,8 +9,22 @@
 //! - **Message Builders**: Ergonomic builders for all message types
 //! - **Scenario System**: Linear message sequences with timing simulation
 //! - **Snapshot Testing**: Record real sessions, replay in tests
+//! - **Golden Transcripts**: Normalized, diffable renderings of a session checked
+//!   against files (see [`Transcript`])
+//! - **Cassettes**: Record-or-replay sessions selected by `CLAUDE_CASSETTE_MODE`, with
+//!   secrets redacted (see [`CassetteMode`])
 //! - **Hook/Permission Testing**: Verify callbacks are invoked correctly
+//! - **Session Assertions**: Fluent checks over tool calls, hooks, permissions, results
+//!   and control requests (see [`SessionAssertions`])
+//! - **Fault Injection**: Disconnects, garbage lines, split reads and write failures
+//!   scheduled by a [`FaultPlan`]
+//! - **Property Testing**: Proptest generators for wire types (see `strategies`, behind
+//!   the `proptest` feature)
 //! - **Deterministic Timing**: Reproducible delays with seeded random jitters
+//! - **CLI Simulator**: Answer and send control requests to test hooks, SDK MCP tools
+//!   and permission callbacks through the real dispatch path (see [`CliSimulator`])
+//! - **Fake CLI**: The `claude-fake` binary replays scenarios through the real subprocess
+//!   transport (see [`fake_cli`])
 //!
 //! # Example
 //!
@@ -37,14 +51,22 @@
 //! }
 //! ```
 
+mod assertions;
+pub(crate) mod cassette;
 mod client;
+mod faults;
+mod golden;
 mod scenario;
+mod simulator;
 mod snapshot;
 mod timing;
 mod transport;
 
 pub mod builders;
+pub mod fake_cli;
 pub mod recorders;
+#[cfg(feature = "proptest")]
+pub mod strategies;
 
 // Transport
 pub use transport::{
@@ -55,20 +77,35 @@ pub use transport::{
 // Client
 pub use client::MockClient;
 
+pub use assertions::SessionAssertions;
+
+pub use golden::{GOLDEN_UPDATE_ENV, Transcript};
+
+// Fault injection
+pub use faults::{Fault, FaultPlan};
+
+// Control protocol simulation
+pub use simulator::CliSimulator;
+
 // Scenario
-pub use scenario::{Exchange, Scenario, ScenarioBuilder, TimingDefaults};
+pub use scenario::{Exchange, Scenario, ScenarioBuilder, ScenarioError, TimingDefaults};
 
 // Timing
 pub use timing::{TimingSimulator, timing_profiles};
 
 // Snapshot Testing
 pub use snapshot::{
-    MessageDirection, RecordedMessage, SessionSnapshot, SnapshotPlayer, SnapshotRecorder,
+    MessageDirection, RecordedMessage, RecordingTransport, SessionSnapshot, SnapshotPlayer,
+    SnapshotRecorder,
 };
 
+// Cassettes
+pub use cassette::{CASSETTE_MODE_ENV, CassetteMode, REDACTED, ReplayTransport, redact_secrets};
+
 // Message Builders
 pub use builders::{
-    AssistantMessageBuilder, ResultMessageBuilder, SystemMessageBuilder, ToolResultBuilder,
+    AssistantMessageBuilder, ControlCancelRequestBuilder, HookInputBuilder, ResultMessageBuilder,
+    StreamEventBuilder, SystemMessageBuilder, ToolResultBuilder, UserMessageBuilder,
 };
 
 // Recorders
@@ -1,10 +1,82 @@
 //! Scenario system for defining test conversations
+//!
+//! Scenarios are built in Rust with [`ScenarioBuilder`] or loaded from YAML/JSON
+//! files with [`Scenario::load`]. A scenario file looks like:
+//!
+//! ```yaml
+//! name: read_file
+//! seed: 42                  # drives delay jitter
+//! timing:                   # optional, same fields as TimingDefaults
+//!   jitter_ms: 10
+//! on_connect:
+//!   - {type: system, subtype: init, session_id: s1}
+//! exchanges:
+//!   - trigger: "Read the file"        # wait for a matching write
+//!     expect_writes: ["Read the file"]
+//!     responses:
+//!       - {type: assistant, message: {model: m, content: [{type: text, text: Done}]}}
+//!       - message: {type: result, subtype: success, ...}
+//!         delay_ms: 200                 # override the default delay
+//!         jitter_ms: 0
+//! ```
+//!
+//! Each response is either a bare CLI message or a `{message, delay_ms, jitter_ms}`
+//! object. Messages are validated against the SDK's message types when loaded.
 
-use super::transport::{MessageTiming, ScheduledMessage};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::transport::{MessageTiming, ScheduledMessage, WrittenMessage};
 use crate::types::messages::Message;
 
+/// Error when a test scenario file is malformed or its expectations are not met
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ScenarioError {
+    /// The scenario file could not be read
+    #[error("cannot read scenario {}: {source}", path.display())]
+    Io {
+        /// Scenario file
+        path: PathBuf,
+        /// Underlying IO error
+        source: std::io::Error,
+    },
+
+    /// The file extension is not `.yaml`, `.yml` or `.json`
+    #[error("unsupported scenario format: {}", path.display())]
+    UnsupportedFormat {
+        /// Scenario file
+        path: PathBuf,
+    },
+
+    /// The document is not valid YAML/JSON or does not match the scenario schema
+    #[error("invalid scenario: {0}")]
+    Parse(String),
+
+    /// A scripted message or field fails validation
+    #[error("invalid scenario at {location}: {message}")]
+    Invalid {
+        /// Path to the offending entry, e.g. `exchanges[1].responses[0]`
+        location: String,
+        /// What is wrong with it
+        message: String,
+    },
+
+    /// An expected write was not found, in order, among the captured writes
+    #[error("exchange {exchange}: expected a write containing '{pattern}'")]
+    MissingWrite {
+        /// Index of the exchange declaring the expectation
+        exchange: usize,
+        /// Pattern that was not matched
+        pattern: String,
+    },
+}
+
 /// Default timing for scenario messages
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TimingDefaults {
     /// Base delay between messages in an exchange
     pub inter_message_delay_ms: u64,
@@ -25,28 +97,111 @@ impl Default for TimingDefaults {
 }
 
 /// A single request-response exchange
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Exchange {
     /// Messages to emit for this exchange
     pub responses: Vec<ScheduledMessage>,
     /// Optional: only trigger after write matching this pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub trigger_pattern: Option<String>,
+    /// Patterns the client is expected to write during this exchange, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_writes: Vec<String>,
 }
 
 /// A test scenario defining a sequence of messages
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Scenario {
     /// Initial messages sent on connect (e.g., SystemMessage)
+    #[serde(default)]
     pub on_connect: Vec<ScheduledMessage>,
     /// Conversation exchanges
+    #[serde(default)]
     pub exchanges: Vec<Exchange>,
     /// Name for debugging
+    #[serde(default)]
     pub name: String,
+    /// Seed for deterministic delay jitter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl Scenario {
+    /// Load a scenario file, choosing the format from the `.yaml`, `.yml` or `.json`
+    /// extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        let is_yaml = match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml" | "yml") => true,
+            Some("json") => false,
+            _ => {
+                return Err(ScenarioError::UnsupportedFormat {
+                    path: path.to_path_buf(),
+                });
+            }
+        };
+        let content = std::fs::read_to_string(path).map_err(|source| ScenarioError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut scenario = if is_yaml {
+            Self::from_yaml_str(&content)?
+        } else {
+            Self::from_json_str(&content)?
+        };
+        if scenario.name.is_empty()
+            && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+        {
+            scenario.name = stem.to_string();
+        }
+        Ok(scenario)
+    }
+
+    /// Parse a scenario from YAML
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, ScenarioError> {
+        let file: ScenarioFile =
+            serde_norway::from_str(yaml).map_err(|e| ScenarioError::Parse(e.to_string()))?;
+        file.into_scenario()
+    }
+
+    /// Parse a scenario from JSON
+    pub fn from_json_str(json: &str) -> Result<Self, ScenarioError> {
+        let file: ScenarioFile =
+            serde_json::from_str(json).map_err(|e| ScenarioError::Parse(e.to_string()))?;
+        file.into_scenario()
+    }
+
+    /// Check that every exchange's expected writes appear in `written`, in order
+    ///
+    /// Patterns are matched as substrings of the raw written data. Each match must
+    /// be in the same write as the previous one or a later write, so expectations pin
+    /// the write order while several patterns can still describe one write.
+    pub fn verify_writes(&self, written: &[WrittenMessage]) -> Result<(), ScenarioError> {
+        let mut position = 0;
+        for (index, exchange) in self.exchanges.iter().enumerate() {
+            for pattern in &exchange.expected_writes {
+                match written[position..]
+                    .iter()
+                    .position(|w| w.data.contains(pattern.as_str()))
+                {
+                    Some(offset) => position += offset,
+                    None => {
+                        return Err(ScenarioError::MissingWrite {
+                            exchange: index,
+                            pattern: pattern.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Builder for scenarios
 pub struct ScenarioBuilder {
     name: String,
+    seed: Option<u64>,
     on_connect: Vec<ScheduledMessage>,
     exchanges: Vec<Exchange>,
     current_exchange: Option<ExchangeBuilder>,
@@ -58,6 +213,7 @@ impl ScenarioBuilder {
     pub fn new(name: impl Into<String>) -> Self {
         Self {
             name: name.into(),
+            seed: None,
             on_connect: Vec::new(),
             exchanges: Vec::new(),
             current_exchange: None,
@@ -72,9 +228,8 @@ impl ScenarioBuilder {
     }
 
     /// Set seed for deterministic jitter
-    #[allow(dead_code)]
-    pub fn seed(self, _seed: u64) -> Self {
-        // Seed is applied at transport level, not scenario level
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
         self
     }
 
@@ -104,6 +259,18 @@ impl ScenarioBuilder {
         self
     }
 
+    /// Expect the client to write data containing `pattern` during the current
+    /// exchange (checked by [`Scenario::verify_writes`])
+    pub fn expect_write(mut self, pattern: impl Into<String>) -> Self {
+        if self.current_exchange.is_none() {
+            self.current_exchange = Some(ExchangeBuilder::new(&self.timing_defaults));
+        }
+        if let Some(ref mut exchange) = self.current_exchange {
+            exchange.expected_writes.push(pattern.into());
+        }
+        self
+    }
+
     /// Add response message to current exchange
     pub fn respond(mut self, msg: Message) -> Self {
         if self.current_exchange.is_none() {
@@ -140,6 +307,7 @@ impl ScenarioBuilder {
             name: self.name,
             on_connect: self.on_connect,
             exchanges: self.exchanges,
+            seed: self.seed,
         }
     }
 }
@@ -147,6 +315,7 @@ impl ScenarioBuilder {
 struct ExchangeBuilder {
     responses: Vec<ScheduledMessage>,
     trigger_pattern: Option<String>,
+    expected_writes: Vec<String>,
     defaults: TimingDefaults,
     is_first: bool,
 }
@@ -156,12 +325,17 @@ impl ExchangeBuilder {
         Self {
             responses: Vec::new(),
             trigger_pattern: None,
+            expected_writes: Vec::new(),
             defaults: defaults.clone(),
             is_first: true,
         }
     }
 
     fn add_response(&mut self, msg: Message) {
+        self.add_value(serde_json::to_value(msg).expect("Message serialization should not fail"));
+    }
+
+    fn add_value(&mut self, value: serde_json::Value) {
         let timing = if self.is_first {
             self.is_first = false;
             MessageTiming::Delayed {
@@ -175,16 +349,21 @@ impl ExchangeBuilder {
             }
         };
 
-        self.responses.push(ScheduledMessage {
-            value: serde_json::to_value(msg).expect("Message serialization should not fail"),
-            timing,
-        });
+        self.responses.push(ScheduledMessage { value, timing });
     }
 
     fn add_response_delayed(&mut self, msg: Message, base_ms: u64, jitter_ms: u64) {
+        self.add_value_delayed(
+            serde_json::to_value(msg).expect("Message serialization should not fail"),
+            base_ms,
+            jitter_ms,
+        );
+    }
+
+    fn add_value_delayed(&mut self, value: serde_json::Value, base_ms: u64, jitter_ms: u64) {
         self.is_first = false;
         self.responses.push(ScheduledMessage {
-            value: serde_json::to_value(msg).expect("Message serialization should not fail"),
+            value,
             timing: MessageTiming::Delayed { base_ms, jitter_ms },
         });
     }
@@ -193,7 +372,151 @@ impl ExchangeBuilder {
         Exchange {
             responses: self.responses,
             trigger_pattern: self.trigger_pattern,
+            expected_writes: self.expected_writes,
+        }
+    }
+}
+
+/// On-disk scenario schema
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScenarioFile {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    timing: TimingDefaults,
+    #[serde(default)]
+    on_connect: Vec<serde_json::Value>,
+    #[serde(default)]
+    exchanges: Vec<ExchangeFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExchangeFile {
+    #[serde(default)]
+    trigger: Option<String>,
+    #[serde(default)]
+    expect_writes: Vec<String>,
+    #[serde(default)]
+    responses: Vec<serde_json::Value>,
+}
+
+/// A response given as `{message, delay_ms, jitter_ms}` rather than a bare message
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TimedResponse {
+    message: serde_json::Value,
+    #[serde(default)]
+    delay_ms: Option<u64>,
+    #[serde(default)]
+    jitter_ms: Option<u64>,
+}
+
+impl ScenarioFile {
+    fn into_scenario(self) -> Result<Scenario, ScenarioError> {
+        let mut on_connect = Vec::with_capacity(self.on_connect.len());
+        for (i, value) in self.on_connect.into_iter().enumerate() {
+            validate_message(&value, &format!("on_connect[{}]", i))?;
+            on_connect.push(ScheduledMessage {
+                value,
+                timing: MessageTiming::Immediate,
+            });
+        }
+
+        let mut exchanges = Vec::with_capacity(self.exchanges.len());
+        for (i, file) in self.exchanges.into_iter().enumerate() {
+            let location = format!("exchanges[{}]", i);
+            if file.responses.is_empty() && file.expect_writes.is_empty() {
+                return Err(invalid(
+                    &location,
+                    "exchange needs responses or expect_writes",
+                ));
+            }
+            if file.trigger.as_deref() == Some("") {
+                return Err(invalid(&format!("{}.trigger", location), "empty pattern"));
+            }
+            if let Some(j) = file.expect_writes.iter().position(|p| p.is_empty()) {
+                return Err(invalid(
+                    &format!("{}.expect_writes[{}]", location, j),
+                    "empty pattern",
+                ));
+            }
+
+            let mut builder = ExchangeBuilder::new(&self.timing);
+            builder.trigger_pattern = file.trigger;
+            builder.expected_writes = file.expect_writes;
+            for (j, response) in file.responses.into_iter().enumerate() {
+                let location = format!("{}.responses[{}]", location, j);
+                if response.get("type").is_some() {
+                    validate_message(&response, &location)?;
+                    builder.add_value(response);
+                    continue;
+                }
+                let timed: TimedResponse = serde_json::from_value(response)
+                    .map_err(|e| invalid(&location, &e.to_string()))?;
+                validate_message(&timed.message, &format!("{}.message", location))?;
+                match (timed.delay_ms, timed.jitter_ms) {
+                    (None, None) => builder.add_value(timed.message),
+                    (delay_ms, jitter_ms) => builder.add_value_delayed(
+                        timed.message,
+                        delay_ms.unwrap_or(self.timing.inter_message_delay_ms),
+                        jitter_ms.unwrap_or(self.timing.jitter_ms),
+                    ),
+                }
+            }
+            exchanges.push(builder.build());
+        }
+
+        Ok(Scenario {
+            name: self.name,
+            seed: self.seed,
+            on_connect,
+            exchanges,
+        })
+    }
+}
+
+/// Check that `value` is something the CLI could send on stdout
+fn validate_message(value: &serde_json::Value, location: &str) -> Result<(), ScenarioError> {
+    let Some(kind) = value.get("type").and_then(|t| t.as_str()) else {
+        return Err(invalid(location, "message needs a string \"type\""));
+    };
+    match kind {
+        "control_request" => {
+            if value["request_id"].as_str().is_none() {
+                return Err(invalid(location, "control_request needs \"request_id\""));
+            }
+            if value["request"]["subtype"].as_str().is_none() {
+                return Err(invalid(
+                    location,
+                    "control_request needs \"request.subtype\"",
+                ));
+            }
+            Ok(())
+        }
+        "control_response" => {
+            if value["response"].is_object() {
+                Ok(())
+            } else {
+                Err(invalid(
+                    location,
+                    "control_response needs a \"response\" object",
+                ))
+            }
         }
+        _ => serde_json::from_value::<Message>(value.clone())
+            .map(|_| ())
+            .map_err(|e| invalid(location, &format!("invalid {} message: {}", kind, e))),
+    }
+}
+
+fn invalid(location: &str, message: &str) -> ScenarioError {
+    ScenarioError::Invalid {
+        location: location.to_string(),
+        message: message.to_string(),
     }
 }
 
@@ -233,4 +556,147 @@ mod tests {
 
         assert_eq!(scenario.exchanges.len(), 2);
     }
+
+    #[test]
+    fn test_builder_seed_and_expected_writes() {
+        let scenario = ScenarioBuilder::new("seeded")
+            .seed(42)
+            .exchange()
+            .when_write_contains("hello")
+            .expect_write("hello")
+            .respond(AssistantMessageBuilder::new().text("Hi").build())
+            .build();
+
+        assert_eq!(scenario.seed, Some(42));
+        assert_eq!(scenario.exchanges[0].expected_writes, vec!["hello"]);
+    }
+
+    const YAML: &str = r#"
+name: yaml
+seed: 3
+timing: {initial_response_delay_ms: 7, jitter_ms: 1}
+on_connect:
+  - {type: system, subtype: init}
+exchanges:
+  - trigger: ping
+    expect_writes: [ping]
+    responses:
+      - {type: assistant, message: {content: [{type: text, text: pong}]}}
+      - message: {type: result, subtype: success, duration_ms: 1, duration_api_ms: 1, is_error: false, num_turns: 1, session_id: s}
+        delay_ms: 30
+"#;
+
+    #[test]
+    fn test_from_yaml_applies_timing_defaults() {
+        let scenario = Scenario::from_yaml_str(YAML).unwrap();
+
+        assert_eq!(scenario.name, "yaml");
+        assert_eq!(scenario.seed, Some(3));
+        assert!(matches!(
+            scenario.on_connect[0].timing,
+            MessageTiming::Immediate
+        ));
+        let exchange = &scenario.exchanges[0];
+        assert_eq!(exchange.trigger_pattern.as_deref(), Some("ping"));
+        assert!(matches!(
+            exchange.responses[0].timing,
+            MessageTiming::Delayed {
+                base_ms: 7,
+                jitter_ms: 1
+            }
+        ));
+        assert!(matches!(
+            exchange.responses[1].timing,
+            MessageTiming::Delayed {
+                base_ms: 30,
+                jitter_ms: 1
+            }
+        ));
+    }
+
+    #[test]
+    fn test_json_and_yaml_load_the_same_scenario() {
+        let yaml: serde_json::Value = serde_norway::from_str(YAML).unwrap();
+        let from_json = Scenario::from_json_str(&yaml.to_string()).unwrap();
+        let from_yaml = Scenario::from_yaml_str(YAML).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(from_json).unwrap(),
+            serde_json::to_value(from_yaml).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_scenarios_report_location() {
+        let location = |yaml: &str| match Scenario::from_yaml_str(yaml) {
+            Err(ScenarioError::Invalid { location, .. }) => location,
+            other => panic!("expected validation error, got {:?}", other),
+        };
+
+        assert_eq!(location("on_connect: [{subtype: init}]"), "on_connect[0]");
+        assert_eq!(
+            location("exchanges: [{responses: [{type: telemetry}]}]"),
+            "exchanges[0].responses[0]"
+        );
+        assert_eq!(
+            location("exchanges: [{responses: [{type: assistant}]}]"),
+            "exchanges[0].responses[0]"
+        );
+        assert_eq!(
+            location("exchanges: [{responses: [{type: control_request, request_id: r1}]}]"),
+            "exchanges[0].responses[0]"
+        );
+        assert_eq!(
+            location("exchanges: [{responses: [{message: {type: user}, delay: 5}]}]"),
+            "exchanges[0].responses[0]"
+        );
+        assert_eq!(location("exchanges: [{trigger: x}]"), "exchanges[0]");
+        assert_eq!(
+            location("exchanges: [{expect_writes: ['']}]"),
+            "exchanges[0].expect_writes[0]"
+        );
+
+        assert!(matches!(
+            Scenario::from_yaml_str("exchanges: []\nsteps: []"),
+            Err(ScenarioError::Parse(_))
+        ));
+        assert!(matches!(
+            Scenario::load("scenario.toml"),
+            Err(ScenarioError::UnsupportedFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_writes_in_order() {
+        let scenario = ScenarioBuilder::new("order")
+            .exchange()
+            .expect_write("first")
+            .expect_write("second")
+            .build();
+        let written = |data: &[&str]| {
+            data.iter()
+                .map(|d| WrittenMessage {
+                    data: d.to_string(),
+                    parsed: None,
+                    timestamp: std::time::Instant::now(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert!(
+            scenario
+                .verify_writes(&written(&["first", "x", "second"]))
+                .is_ok()
+        );
+        assert!(matches!(
+            scenario.verify_writes(&written(&["second", "first"])),
+            Err(ScenarioError::MissingWrite { exchange: 0, ref pattern }) if pattern == "second"
+        ));
+        // Several patterns may describe the same write
+        assert!(
+            scenario
+                .verify_writes(&written(&[r#"{"first": 1, "second": 2}"#]))
+                .is_ok()
+        );
+    }
 }
@@ -0,0 +1,380 @@
+//! CLI-side simulation of the control protocol
+//!
+//! [`CliSimulator`] attaches to a [`MockTransport`] and plays the part of the Claude
+//! Code CLI in the bidirectional control protocol:
+//!
+//! - Control requests written by the SDK (`initialize`, `interrupt`, `set_model`, ...)
+//!   are answered automatically, with configurable replies or errors.
+//! - Requests can be sent to the SDK (`hook_callback`, `mcp_message`, `can_use_tool`)
+//!   and their responses awaited, correlated by `request_id`.
+//!
+//! This drives hooks, SDK MCP tools and permission callbacks through the real
+//! `QueryFull` dispatch path without a subprocess.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use claude_agent_sdk_rs::testing::{CliSimulator, MockTransport};
+//! use claude_agent_sdk_rs::{ClaudeAgentOptions, ClaudeClient};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> claude_agent_sdk_rs::Result<()> {
+//! let transport = Arc::new(MockTransport::builder().build());
+//! let simulator = CliSimulator::attach(Arc::clone(&transport));
+//!
+//! let mut client = ClaudeClient::with_transport(transport, ClaudeAgentOptions::default());
+//! client.connect_with_transport_and_initialize().await?;
+//!
+//! let output = simulator
+//!     .hook_event("PreToolUse", serde_json::json!({"tool_name": "Bash"}))
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use tokio::sync::oneshot;
+
+use super::transport::MockTransport;
+use crate::errors::{ClaudeError, Result};
+
+/// Default time to wait for the SDK to answer a request
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How the simulator answers one control request subtype
+#[derive(Debug, Clone)]
+enum Reply {
+    Success(Value),
+    Error(String),
+}
+
+#[derive(Default)]
+struct SimulatorState {
+    replies: HashMap<String, Reply>,
+    sdk_requests: Vec<Value>,
+    pending: HashMap<String, oneshot::Sender<Value>>,
+}
+
+/// Scriptable stand-in for the CLI side of the control protocol
+pub struct CliSimulator {
+    transport: Arc<MockTransport>,
+    state: Arc<Mutex<SimulatorState>>,
+    counter: AtomicU64,
+    timeout: Duration,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl CliSimulator {
+    /// Attach to a transport and start answering the SDK's control requests
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn attach(transport: Arc<MockTransport>) -> Self {
+        let state = Arc::new(Mutex::new(SimulatorState::default()));
+        let writes = transport.subscribe_writes();
+
+        let task = {
+            let transport = Arc::clone(&transport);
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                while let Ok(written) = writes.recv_async().await {
+                    let Some(message) = written.parsed else {
+                        continue;
+                    };
+                    match message["type"].as_str() {
+                        Some("control_request") => {
+                            let reply = Self::reply_for(&state, &message);
+                            transport.inject(reply);
+                        }
+                        Some("control_response") => {
+                            let response = &message["response"];
+                            let sender = response["request_id"]
+                                .as_str()
+                                .and_then(|id| state.lock().unwrap().pending.remove(id));
+                            if let Some(sender) = sender {
+                                let _ = sender.send(response.clone());
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            })
+        };
+
+        Self {
+            transport,
+            state,
+            counter: AtomicU64::new(0),
+            timeout: DEFAULT_TIMEOUT,
+            task,
+        }
+    }
+
+    /// Set how long to wait for the SDK to answer a request
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Reply to SDK requests of `subtype` with `data` instead of the default
+    pub fn set_reply(&self, subtype: impl Into<String>, data: Value) {
+        self.state
+            .lock()
+            .unwrap()
+            .replies
+            .insert(subtype.into(), Reply::Success(data));
+    }
+
+    /// Reply to SDK requests of `subtype` with an error
+    pub fn set_error(&self, subtype: impl Into<String>, message: impl Into<String>) {
+        self.state
+            .lock()
+            .unwrap()
+            .replies
+            .insert(subtype.into(), Reply::Error(message.into()));
+    }
+
+    /// All control requests the SDK has sent, in order
+    pub fn sdk_requests(&self) -> Vec<Value> {
+        self.state.lock().unwrap().sdk_requests.clone()
+    }
+
+    /// The `initialize` request sent by the SDK, if any
+    pub fn initialize_request(&self) -> Option<Value> {
+        self.sdk_requests()
+            .into_iter()
+            .find(|r| r["request"]["subtype"] == "initialize")
+    }
+
+    /// Emit an arbitrary message to the SDK as if the CLI had printed it
+    pub fn emit(&self, message: Value) {
+        self.transport.inject(message);
+    }
+
+    /// Invoke a hook callback by ID
+    pub async fn hook_callback(
+        &self,
+        callback_id: &str,
+        input: Value,
+        tool_use_id: Option<&str>,
+    ) -> Result<Value> {
+        self.request(json!({
+            "subtype": "hook_callback",
+            "callback_id": callback_id,
+            "input": input,
+            "tool_use_id": tool_use_id,
+        }))
+        .await
+    }
+
+    /// Fire a hook event, resolving the callback ID from the `initialize` registration
+    ///
+    /// The first callback registered for `event` is used. Missing common input fields
+    /// (`hook_event_name`, `session_id`, `transcript_path`, `cwd`) are filled in.
+    pub async fn hook_event(&self, event: &str, input: Value) -> Result<Value> {
+        let callback_id = self
+            .initialize_request()
+            .and_then(|init| {
+                init["request"]["hooks"][event][0]["hookCallbackIds"][0]
+                    .as_str()
+                    .map(str::to_string)
+            })
+            .ok_or_else(|| {
+                ClaudeError::ControlProtocol(format!("No hook registered for event: {}", event))
+            })?;
+
+        let mut input = input;
+        if let Some(fields) = input.as_object_mut() {
+            fields
+                .entry("hook_event_name")
+                .or_insert_with(|| json!(event));
+            fields
+                .entry("session_id")
+                .or_insert_with(|| json!("sim-session"));
+            fields
+                .entry("transcript_path")
+                .or_insert_with(|| json!("/tmp/transcript.jsonl"));
+            fields.entry("cwd").or_insert_with(|| json!("/tmp"));
+        }
+
+        let tool_use_id = input["tool_use_id"].as_str().map(str::to_string);
+        self.hook_callback(&callback_id, input, tool_use_id.as_deref())
+            .await
+    }
+
+    /// Send a JSONRPC message to an SDK MCP server and return its `mcp_response`
+    pub async fn mcp_message(&self, server_name: &str, message: Value) -> Result<Value> {
+        let response = self
+            .request(json!({
+                "subtype": "mcp_message",
+                "server_name": server_name,
+                "message": message,
+            }))
+            .await?;
+        Ok(response["mcp_response"].clone())
+    }
+
+    /// Call a tool on an SDK MCP server
+    pub async fn call_tool(
+        &self,
+        server_name: &str,
+        tool: &str,
+        arguments: Value,
+    ) -> Result<Value> {
+        let id = self.counter.fetch_add(1, Ordering::SeqCst);
+        self.mcp_message(
+            server_name,
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "tools/call",
+                "params": {"name": tool, "arguments": arguments},
+            }),
+        )
+        .await
+    }
+
+    /// Ask the SDK's `can_use_tool` callback for a permission decision
+    pub async fn can_use_tool(&self, tool_name: &str, input: Value) -> Result<Value> {
+        self.request(json!({
+            "subtype": "can_use_tool",
+            "tool_name": tool_name,
+            "input": input,
+            "permission_suggestions": [],
+        }))
+        .await
+    }
+
+    /// Send a control request to the SDK and wait for the response data
+    ///
+    /// An error reply from the SDK is returned as [`ClaudeError::ControlProtocol`].
+    pub async fn request(&self, request: Value) -> Result<Value> {
+        let n = self.counter.fetch_add(1, Ordering::SeqCst);
+        let request_id = format!("req_sim_{}", n);
+
+        let (tx, rx) = oneshot::channel();
+        self.state
+            .lock()
+            .unwrap()
+            .pending
+            .insert(request_id.clone(), tx);
+
+        self.transport.inject(json!({
+            "type": "control_request",
+            "request_id": request_id,
+            "request": request,
+        }));
+
+        let response = tokio::time::timeout(self.timeout, rx)
+            .await
+            .map_err(|_| {
+                self.state.lock().unwrap().pending.remove(&request_id);
+                ClaudeError::ControlProtocol(format!(
+                    "Timed out waiting for response to {}",
+                    request_id
+                ))
+            })?
+            .map_err(|_| ClaudeError::ControlProtocol("Simulator stopped".to_string()))?;
+
+        match response["subtype"].as_str() {
+            Some("success") => Ok(response["response"].clone()),
+            _ => Err(ClaudeError::ControlProtocol(
+                response["error"]
+                    .as_str()
+                    .unwrap_or("Unknown error")
+                    .to_string(),
+            )),
+        }
+    }
+
+    fn reply_for(state: &Mutex<SimulatorState>, message: &Value) -> Value {
+        let request_id = message["request_id"].clone();
+        let subtype = message["request"]["subtype"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        let mut state = state.lock().unwrap();
+        state.sdk_requests.push(message.clone());
+
+        let reply = state.replies.get(&subtype).cloned().unwrap_or_else(|| {
+            Reply::Success(match subtype.as_str() {
+                "initialize" => json!({"commands": [], "output_style": "default"}),
+                _ => json!({}),
+            })
+        });
+
+        let response = match reply {
+            Reply::Success(data) => json!({
+                "subtype": "success",
+                "request_id": request_id,
+                "response": data,
+            }),
+            Reply::Error(error) => json!({
+                "subtype": "error",
+                "request_id": request_id,
+                "error": error,
+            }),
+        };
+        json!({"type": "control_response", "response": response})
+    }
+}
+
+impl Drop for CliSimulator {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ClaudeClient;
+    use crate::types::config::ClaudeAgentOptions;
+
+    #[tokio::test]
+    async fn test_answers_sdk_control_requests() {
+        let transport = Arc::new(MockTransport::builder().build());
+        let simulator = CliSimulator::attach(Arc::clone(&transport));
+        simulator.set_error("set_model", "unknown model");
+
+        let mut client = ClaudeClient::with_transport(transport, ClaudeAgentOptions::default());
+        client
+            .connect_with_transport_and_initialize()
+            .await
+            .unwrap();
+        client.interrupt().await.unwrap();
+        assert!(client.set_model(Some("bogus")).await.is_err());
+
+        let subtypes: Vec<Value> = simulator
+            .sdk_requests()
+            .iter()
+            .map(|r| r["request"]["subtype"].clone())
+            .collect();
+        assert_eq!(subtypes, vec!["initialize", "interrupt", "set_model"]);
+        assert!(simulator.initialize_request().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_hook_callback_returns_error() {
+        let transport = Arc::new(MockTransport::builder().build());
+        let simulator =
+            CliSimulator::attach(Arc::clone(&transport)).with_timeout(Duration::from_secs(1));
+
+        let mut client = ClaudeClient::with_transport(transport, ClaudeAgentOptions::default());
+        client
+            .connect_with_transport_and_initialize()
+            .await
+            .unwrap();
+
+        let err = simulator
+            .hook_callback("hook_99", json!({}), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("hook_99"));
+    }
+}
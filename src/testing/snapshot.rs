@@ -10,10 +10,12 @@ use futures::stream::Stream;
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
+use super::cassette::redact_secrets;
 use super::scenario::Scenario;
 use super::transport::{MessageTiming, ScheduledMessage};
 use crate::errors::Result;
 use crate::internal::transport::Transport;
+use crate::types::process::{ProcessInfo, ShutdownReport};
 
 /// Message direction in a recorded session
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -87,6 +89,8 @@ impl SnapshotRecorder {
     }
 
     /// Export snapshot to file
+    ///
+    /// Secrets are redacted with [`redact_secrets`] before writing.
     pub async fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
         let snapshot = SessionSnapshot {
             version: 1,
@@ -96,6 +100,9 @@ impl SnapshotRecorder {
             options: serde_json::Value::Null,
             messages: self.messages.lock().await.clone(),
         };
+        let mut snapshot = serde_json::to_value(snapshot)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        redact_secrets(&mut snapshot);
 
         let json = serde_json::to_string_pretty(&snapshot)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
@@ -156,6 +163,7 @@ impl SnapshotPlayer {
             name: "snapshot_replay".to_string(),
             on_connect: Vec::new(),
             exchanges: Vec::new(),
+            seed: None,
         }
     }
 
@@ -257,6 +265,14 @@ impl Transport for RecordingTransport {
         self.inner.close().await
     }
 
+    async fn shutdown(&self) -> Result<ShutdownReport> {
+        self.inner.shutdown().await
+    }
+
+    fn process_info(&self) -> Option<ProcessInfo> {
+        self.inner.process_info()
+    }
+
     fn is_ready(&self) -> bool {
         self.inner.is_ready()
     }
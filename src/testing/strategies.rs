@@ -0,0 +1,638 @@
+//! Proptest generators for the SDK's wire types
+//!
+//! Available with the `proptest` feature. Each `arb_*` function returns a strategy
+//! producing values that serialize to well-formed CLI messages, and the same types
+//! implement [`Arbitrary`] so `any::<Message>()` works in `proptest!` blocks or a
+//! [`TestRunner`](proptest::test_runner::TestRunner):
+//!
+//! ```
+//! use claude_agent_sdk_rs::Message;
+//! use proptest::prelude::*;
+//! use proptest::test_runner::TestRunner;
+//!
+//! let mut runner = TestRunner::default();
+//! runner
+//!     .run(&any::<Message>(), |message| {
+//!         let json = serde_json::to_string(&message).unwrap();
+//!         let parsed: Message = serde_json::from_str(&json).unwrap();
+//!         prop_assert_eq!(
+//!             serde_json::to_value(&parsed).unwrap(),
+//!             serde_json::to_value(&message).unwrap()
+//!         );
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+//!
+//! Free-form fields (`serde_json::Value`, flattened extras) get small JSON values
+//! whose keys cannot collide with the typed fields. Floats are limited to values with
+//! short decimal representations so text round-trips are exact.
+
+use proptest::arbitrary::Arbitrary;
+use proptest::collection::{btree_map, vec};
+use proptest::option;
+use proptest::prelude::*;
+use proptest::strategy::BoxedStrategy;
+use serde_json::Value;
+
+use crate::types::config::{
+    PermissionMode, SandboxIgnoreViolations, SandboxNetworkConfig, SandboxSettings,
+};
+use crate::types::hooks::{
+    AsyncHookJsonOutput, HookInput, HookJsonOutput, HookSpecificOutput, PostToolUseHookInput,
+    PostToolUseHookSpecificOutput, PreCompactHookInput, PreToolUseHookInput,
+    PreToolUseHookSpecificOutput, StopHookInput, SubagentStopHookInput, SyncHookJsonOutput,
+    UserPromptSubmitHookInput, UserPromptSubmitHookSpecificOutput,
+};
+use crate::types::messages::{
+    AssistantMessage, AssistantMessageError, AssistantMessageInner, ContentBlock, ImageBlock,
+    ImageSource, Message, ResultMessage, StreamEvent, SystemMessage, TextBlock, ThinkingBlock,
+    ToolResultBlock, ToolResultContent, ToolUseBlock, UserMessage,
+};
+use crate::types::permissions::{
+    PermissionBehavior, PermissionRuleValue, PermissionUpdate, PermissionUpdateDestination,
+    PermissionUpdateType,
+};
+
+/// Printable text, including non-ASCII and characters that need JSON escaping
+pub fn arb_text() -> impl Strategy<Value = String> {
+    "\\PC{0,24}"
+}
+
+/// Identifier-like strings (IDs, tool names, session IDs)
+pub fn arb_id() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9_-]{1,16}"
+}
+
+/// Small arbitrary JSON values
+pub fn arb_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        arb_text().prop_map(Value::String),
+    ];
+    leaf.prop_recursive(2, 16, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..4).prop_map(Value::Array),
+            btree_map(arb_id(), inner, 0..4).prop_map(|m| Value::Object(m.into_iter().collect())),
+        ]
+    })
+}
+
+/// Small JSON objects
+pub fn arb_json_object() -> impl Strategy<Value = Value> {
+    btree_map(arb_id(), arb_json(), 0..4).prop_map(|m| Value::Object(m.into_iter().collect()))
+}
+
+/// JSON objects for `#[serde(flatten)]` fields; keys are prefixed with `x_` so they
+/// never collide with typed fields
+fn arb_extra_fields() -> impl Strategy<Value = Value> {
+    btree_map("x_[a-z0-9_]{1,10}", arb_json(), 0..3)
+        .prop_map(|m| Value::Object(m.into_iter().collect()))
+}
+
+/// Dollar amounts in whole cents
+fn arb_cost() -> impl Strategy<Value = f64> {
+    (0u32..1_000_000).prop_map(|cents| f64::from(cents) / 100.0)
+}
+
+fn arb_permission_mode() -> impl Strategy<Value = PermissionMode> {
+    prop_oneof![
+        Just(PermissionMode::Default),
+        Just(PermissionMode::AcceptEdits),
+        Just(PermissionMode::Plan),
+        Just(PermissionMode::BypassPermissions),
+    ]
+}
+
+fn arb_permission_mode_name() -> impl Strategy<Value = String> {
+    arb_permission_mode().prop_map(|mode| {
+        serde_json::to_value(mode)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default()
+    })
+}
+
+// =============================================================================
+// Content blocks and messages
+// =============================================================================
+
+fn arb_image_source() -> impl Strategy<Value = ImageSource> {
+    prop_oneof![
+        (
+            prop_oneof![
+                Just("image/jpeg"),
+                Just("image/png"),
+                Just("image/gif"),
+                Just("image/webp"),
+            ],
+            "[A-Za-z0-9+/]{0,32}={0,2}",
+        )
+            .prop_map(|(media_type, data)| ImageSource::Base64 {
+                media_type: media_type.to_string(),
+                data,
+            }),
+        "https://example\\.com/[a-z0-9]{1,12}\\.png".prop_map(|url| ImageSource::Url { url }),
+    ]
+}
+
+fn arb_tool_result_content() -> impl Strategy<Value = ToolResultContent> {
+    prop_oneof![
+        arb_text().prop_map(ToolResultContent::Text),
+        vec(arb_json_object(), 0..3).prop_map(ToolResultContent::Blocks),
+    ]
+}
+
+/// Any [`ContentBlock`]
+pub fn arb_content_block() -> impl Strategy<Value = ContentBlock> {
+    prop_oneof![
+        arb_text().prop_map(|text| ContentBlock::Text(TextBlock { text })),
+        (arb_text(), arb_id()).prop_map(|(thinking, signature)| {
+            ContentBlock::Thinking(ThinkingBlock {
+                thinking,
+                signature,
+            })
+        }),
+        (arb_id(), arb_id(), arb_json_object()).prop_map(|(id, name, input)| {
+            ContentBlock::ToolUse(ToolUseBlock { id, name, input })
+        }),
+        (
+            arb_id(),
+            option::of(arb_tool_result_content()),
+            option::of(any::<bool>())
+        )
+            .prop_map(|(tool_use_id, content, is_error)| {
+                ContentBlock::ToolResult(ToolResultBlock {
+                    tool_use_id,
+                    content,
+                    is_error,
+                })
+            }),
+        arb_image_source().prop_map(|source| ContentBlock::Image(ImageBlock { source })),
+    ]
+}
+
+fn arb_assistant_error() -> impl Strategy<Value = AssistantMessageError> {
+    prop_oneof![
+        Just(AssistantMessageError::AuthenticationFailed),
+        Just(AssistantMessageError::BillingError),
+        Just(AssistantMessageError::RateLimit),
+        Just(AssistantMessageError::InvalidRequest),
+        Just(AssistantMessageError::ServerError),
+        Just(AssistantMessageError::Unknown),
+    ]
+}
+
+fn arb_assistant_message() -> impl Strategy<Value = AssistantMessage> {
+    let inner = (
+        vec(arb_content_block(), 0..4),
+        option::of(arb_id()),
+        option::of(arb_id()),
+        option::of(prop_oneof![
+            Just("end_turn".to_string()),
+            Just("tool_use".to_string()),
+            Just("max_tokens".to_string()),
+        ]),
+        option::of(arb_json_object()),
+        option::of(arb_assistant_error()),
+    )
+        .prop_map(
+            |(content, model, id, stop_reason, usage, error)| AssistantMessageInner {
+                content,
+                model,
+                id,
+                stop_reason,
+                usage,
+                error,
+            },
+        );
+    (
+        inner,
+        option::of(arb_id()),
+        option::of(arb_id()),
+        option::of(arb_id()),
+    )
+        .prop_map(
+            |(message, parent_tool_use_id, session_id, uuid)| AssistantMessage {
+                message,
+                parent_tool_use_id,
+                session_id,
+                uuid,
+            },
+        )
+}
+
+fn arb_system_message() -> impl Strategy<Value = SystemMessage> {
+    (
+        arb_id(),
+        option::of(arb_text()),
+        option::of(arb_id()),
+        option::of(vec(arb_id(), 0..4)),
+        option::of(vec(arb_json_object(), 0..2)),
+        option::of(arb_id()),
+        option::of(arb_permission_mode_name()),
+        option::of(arb_id()),
+        arb_extra_fields(),
+    )
+        .prop_map(
+            |(subtype, cwd, session_id, tools, mcp_servers, model, permission_mode, uuid, data)| {
+                SystemMessage {
+                    subtype,
+                    cwd,
+                    session_id,
+                    tools,
+                    mcp_servers,
+                    model,
+                    permission_mode,
+                    uuid,
+                    data,
+                }
+            },
+        )
+}
+
+fn arb_result_message() -> impl Strategy<Value = ResultMessage> {
+    (
+        prop_oneof![
+            Just("success".to_string()),
+            Just("error_max_turns".to_string()),
+            Just("error_during_execution".to_string()),
+        ],
+        any::<u64>(),
+        any::<u64>(),
+        any::<bool>(),
+        any::<u32>(),
+        arb_id(),
+        option::of(arb_cost()),
+        option::of(arb_json_object()),
+        option::of(arb_text()),
+        option::of(arb_json()),
+    )
+        .prop_map(
+            |(
+                subtype,
+                duration_ms,
+                duration_api_ms,
+                is_error,
+                num_turns,
+                session_id,
+                total_cost_usd,
+                usage,
+                result,
+                structured_output,
+            )| ResultMessage {
+                subtype,
+                duration_ms,
+                duration_api_ms,
+                is_error,
+                num_turns,
+                session_id,
+                total_cost_usd,
+                usage,
+                result,
+                // `Some(null)` cannot be told apart from `None` on the wire
+                structured_output: structured_output.filter(|v| !v.is_null()),
+            },
+        )
+}
+
+fn arb_stream_event() -> impl Strategy<Value = StreamEvent> {
+    (arb_id(), arb_id(), arb_json_object(), option::of(arb_id())).prop_map(
+        |(uuid, session_id, event, parent_tool_use_id)| StreamEvent {
+            uuid,
+            session_id,
+            event,
+            parent_tool_use_id,
+        },
+    )
+}
+
+fn arb_user_message() -> impl Strategy<Value = UserMessage> {
+    (
+        option::of(arb_text()),
+        option::of(vec(arb_content_block(), 0..3)),
+        option::of(arb_id()),
+        option::of(arb_id()),
+        arb_extra_fields(),
+    )
+        .prop_map(
+            |(text, content, uuid, parent_tool_use_id, extra)| UserMessage {
+                text,
+                content,
+                uuid,
+                parent_tool_use_id,
+                extra,
+            },
+        )
+}
+
+/// Any [`Message`]
+pub fn arb_message() -> impl Strategy<Value = Message> {
+    prop_oneof![
+        arb_assistant_message().prop_map(Message::Assistant),
+        arb_system_message().prop_map(Message::System),
+        arb_result_message().prop_map(Message::Result),
+        arb_stream_event().prop_map(Message::StreamEvent),
+        arb_user_message().prop_map(Message::User),
+        arb_id().prop_map(|id| Message::ControlCancelRequest(serde_json::json!({
+            "request_id": id
+        }))),
+    ]
+}
+
+// =============================================================================
+// Hooks
+// =============================================================================
+
+/// Any [`HookInput`]
+pub fn arb_hook_input() -> impl Strategy<Value = HookInput> {
+    let common = (
+        arb_id(),
+        "/tmp/[a-z0-9]{1,8}\\.jsonl",
+        "/[a-z0-9/]{0,16}",
+        option::of(arb_permission_mode_name()),
+    );
+    (
+        common,
+        0..6usize,
+        arb_id(),
+        arb_json_object(),
+        arb_json(),
+        arb_text(),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(
+                (session_id, transcript_path, cwd, permission_mode),
+                variant,
+                tool_name,
+                tool_input,
+                tool_response,
+                prompt,
+                stop_hook_active,
+            )| {
+                match variant {
+                    0 => HookInput::PreToolUse(PreToolUseHookInput {
+                        session_id,
+                        transcript_path,
+                        cwd,
+                        permission_mode,
+                        tool_name,
+                        tool_input,
+                    }),
+                    1 => HookInput::PostToolUse(PostToolUseHookInput {
+                        session_id,
+                        transcript_path,
+                        cwd,
+                        permission_mode,
+                        tool_name,
+                        tool_input,
+                        tool_response,
+                    }),
+                    2 => HookInput::UserPromptSubmit(UserPromptSubmitHookInput {
+                        session_id,
+                        transcript_path,
+                        cwd,
+                        permission_mode,
+                        prompt,
+                    }),
+                    3 => HookInput::Stop(StopHookInput {
+                        session_id,
+                        transcript_path,
+                        cwd,
+                        permission_mode,
+                        stop_hook_active,
+                    }),
+                    4 => HookInput::SubagentStop(SubagentStopHookInput {
+                        session_id,
+                        transcript_path,
+                        cwd,
+                        permission_mode,
+                        stop_hook_active,
+                    }),
+                    _ => {
+                        let custom_instructions = (!prompt.is_empty()).then_some(prompt);
+                        HookInput::PreCompact(PreCompactHookInput {
+                            session_id,
+                            transcript_path,
+                            cwd,
+                            permission_mode,
+                            trigger: if stop_hook_active { "manual" } else { "auto" }.to_string(),
+                            custom_instructions,
+                        })
+                    }
+                }
+            },
+        )
+}
+
+fn arb_hook_specific_output() -> impl Strategy<Value = HookSpecificOutput> {
+    prop_oneof![
+        (
+            option::of(prop_oneof![
+                Just("allow".to_string()),
+                Just("deny".to_string()),
+                Just("ask".to_string()),
+            ]),
+            option::of(arb_text()),
+            option::of(arb_json_object()),
+        )
+            .prop_map(
+                |(permission_decision, permission_decision_reason, updated_input)| {
+                    HookSpecificOutput::PreToolUse(PreToolUseHookSpecificOutput {
+                        permission_decision,
+                        permission_decision_reason,
+                        updated_input,
+                    })
+                }
+            ),
+        option::of(arb_text()).prop_map(|additional_context| {
+            HookSpecificOutput::PostToolUse(PostToolUseHookSpecificOutput { additional_context })
+        }),
+        option::of(arb_text()).prop_map(|additional_context| {
+            HookSpecificOutput::UserPromptSubmit(UserPromptSubmitHookSpecificOutput {
+                additional_context,
+            })
+        }),
+    ]
+}
+
+/// Any [`HookJsonOutput`]
+pub fn arb_hook_json_output() -> impl Strategy<Value = HookJsonOutput> {
+    let sync = (
+        option::of(any::<bool>()),
+        option::of(any::<bool>()),
+        option::of(arb_text()),
+        option::of(prop_oneof![
+            Just("approve".to_string()),
+            Just("block".to_string()),
+        ]),
+        option::of(arb_text()),
+        option::of(arb_text()),
+        option::of(arb_hook_specific_output()),
+    )
+        .prop_map(
+            |(
+                continue_,
+                suppress_output,
+                stop_reason,
+                decision,
+                system_message,
+                reason,
+                hook_specific_output,
+            )| {
+                HookJsonOutput::Sync(SyncHookJsonOutput {
+                    continue_,
+                    suppress_output,
+                    stop_reason,
+                    decision,
+                    system_message,
+                    reason,
+                    hook_specific_output,
+                })
+            },
+        );
+    let async_ = option::of(any::<u64>()).prop_map(|async_timeout| {
+        HookJsonOutput::Async(AsyncHookJsonOutput {
+            async_: true,
+            async_timeout,
+        })
+    });
+    prop_oneof![sync, async_]
+}
+
+// =============================================================================
+// Permissions and sandbox
+// =============================================================================
+
+/// Any [`PermissionUpdate`]
+pub fn arb_permission_update() -> impl Strategy<Value = PermissionUpdate> {
+    let update_type = prop_oneof![
+        Just(PermissionUpdateType::AddRules),
+        Just(PermissionUpdateType::ReplaceRules),
+        Just(PermissionUpdateType::RemoveRules),
+        Just(PermissionUpdateType::SetMode),
+        Just(PermissionUpdateType::AddDirectories),
+        Just(PermissionUpdateType::RemoveDirectories),
+    ];
+    let rule = (arb_id(), option::of("[a-z0-9 :*]{1,12}")).prop_map(|(tool_name, rule_content)| {
+        PermissionRuleValue {
+            tool_name,
+            rule_content,
+        }
+    });
+    let behavior = prop_oneof![
+        Just(PermissionBehavior::Allow),
+        Just(PermissionBehavior::Deny),
+        Just(PermissionBehavior::Ask),
+    ];
+    let destination = prop_oneof![
+        Just(PermissionUpdateDestination::UserSettings),
+        Just(PermissionUpdateDestination::ProjectSettings),
+        Just(PermissionUpdateDestination::LocalSettings),
+        Just(PermissionUpdateDestination::Session),
+    ];
+    (
+        update_type,
+        option::of(vec(rule, 0..3)),
+        option::of(behavior),
+        option::of(arb_permission_mode()),
+        option::of(vec("/[a-z0-9/]{1,16}", 0..3)),
+        option::of(destination),
+    )
+        .prop_map(|(type_, rules, behavior, mode, directories, destination)| {
+            PermissionUpdate {
+                type_,
+                rules,
+                behavior,
+                mode,
+                directories,
+                destination,
+            }
+        })
+}
+
+/// Any [`SandboxSettings`]
+pub fn arb_sandbox_settings() -> impl Strategy<Value = SandboxSettings> {
+    let network = (
+        option::of(vec("/[a-z0-9/.]{1,16}", 0..3)),
+        option::of(any::<bool>()),
+        option::of(any::<bool>()),
+        option::of(any::<u16>()),
+        option::of(any::<u16>()),
+    )
+        .prop_map(
+            |(
+                allow_unix_sockets,
+                allow_all_unix_sockets,
+                allow_local_binding,
+                http_proxy_port,
+                socks_proxy_port,
+            )| SandboxNetworkConfig {
+                allow_unix_sockets,
+                allow_all_unix_sockets,
+                allow_local_binding,
+                http_proxy_port,
+                socks_proxy_port,
+            },
+        );
+    let ignore_violations = (
+        option::of(vec("/[a-z0-9/]{1,16}", 0..3)),
+        option::of(vec("[a-z0-9.]{1,16}", 0..3)),
+    )
+        .prop_map(|(file, network)| SandboxIgnoreViolations { file, network });
+    (
+        option::of(any::<bool>()),
+        option::of(any::<bool>()),
+        option::of(vec(arb_id(), 0..3)),
+        option::of(any::<bool>()),
+        option::of(network),
+        option::of(ignore_violations),
+        option::of(any::<bool>()),
+    )
+        .prop_map(
+            |(
+                enabled,
+                auto_allow_bash_if_sandboxed,
+                excluded_commands,
+                allow_unsandboxed_commands,
+                network,
+                ignore_violations,
+                enable_weaker_nested_sandbox,
+            )| SandboxSettings {
+                enabled,
+                auto_allow_bash_if_sandboxed,
+                excluded_commands,
+                allow_unsandboxed_commands,
+                network,
+                ignore_violations,
+                enable_weaker_nested_sandbox,
+            },
+        )
+}
+
+macro_rules! impl_arbitrary {
+    ($($ty:ty => $strategy:ident),* $(,)?) => {
+        $(
+            impl Arbitrary for $ty {
+                type Parameters = ();
+                type Strategy = BoxedStrategy<Self>;
+
+                fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                    $strategy().boxed()
+                }
+            }
+        )*
+    };
+}
+
+impl_arbitrary! {
+    Message => arb_message,
+    ContentBlock => arb_content_block,
+    HookInput => arb_hook_input,
+    HookJsonOutput => arb_hook_json_output,
+    PermissionUpdate => arb_permission_update,
+    SandboxSettings => arb_sandbox_settings,
+}
@@ -3,29 +3,33 @@
 use std::collections::VecDeque;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::Duration;
 
 use async_trait::async_trait;
 use futures::stream::Stream;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
-use crate::errors::{ClaudeError, ConnectionError, Result};
+use super::faults::{FaultInjector, FaultPlan};
+use crate::errors::{ClaudeError, ConnectionError, ProcessError, Result};
 use crate::internal::transport::Transport;
 
 /// A message scheduled for delivery
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScheduledMessage {
     /// The JSON value to emit
     pub value: serde_json::Value,
     /// Timing configuration for this message
+    #[serde(default)]
     pub timing: MessageTiming,
 }
 
 /// Timing configuration for a message
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum MessageTiming {
     /// Deliver immediately
     #[default]
@@ -89,9 +93,17 @@ pub struct MockTransport {
     injector_rx: flume::Receiver<serde_json::Value>,
     /// Captured writes for assertions
     written: Arc<Mutex<Vec<WrittenMessage>>>,
+    /// Live subscribers notified of every write
+    write_subscribers: std::sync::Mutex<Vec<flume::Sender<WrittenMessage>>>,
     /// Connection state
     connected: AtomicBool,
     ready: AtomicBool,
+    /// Scheduled failures
+    faults: FaultPlan,
+    /// Number of successful writes, for write-count faults
+    writes: AtomicUsize,
+    /// Exit code once a simulated process exit has happened
+    exited: std::sync::Mutex<Option<i32>>,
     /// Timing configuration
     timing: TimingConfig,
     /// Random number generator for deterministic jitter (reserved for future use)
@@ -126,8 +138,12 @@ impl MockTransport {
             injector_tx,
             injector_rx,
             written: Arc::new(Mutex::new(Vec::new())),
+            write_subscribers: std::sync::Mutex::new(Vec::new()),
             connected: AtomicBool::new(false),
             ready: AtomicBool::new(false),
+            faults: FaultPlan::default(),
+            writes: AtomicUsize::new(0),
+            exited: std::sync::Mutex::new(None),
             timing: timing.clone(),
             rng: Mutex::new(StdRng::seed_from_u64(timing.seed)),
         }
@@ -158,6 +174,18 @@ impl MockTransport {
         self.written.lock().await.clone()
     }
 
+    /// Subscribe to writes made after this call
+    ///
+    /// Every successful `write` is delivered to the returned receiver, which lets test
+    /// code react to what the SDK sends (see [`CliSimulator`](super::CliSimulator)).
+    pub fn subscribe_writes(&self) -> flume::Receiver<WrittenMessage> {
+        let (tx, rx) = flume::unbounded();
+        if let Ok(mut subscribers) = self.write_subscribers.lock() {
+            subscribers.push(tx);
+        }
+        rx
+    }
+
     /// Check if any messages are waiting for a write pattern
     async fn check_write_triggers(&self, data: &str) {
         let mut pending = self.pending_triggers.lock().await;
@@ -179,7 +207,13 @@ impl MockTransport {
     }
 
     /// Create a MockTransport from a scenario
+    ///
+    /// Jitter is seeded from [`Scenario::seed`](super::Scenario::seed), or 0 when unset.
     pub fn from_scenario(scenario: super::Scenario) -> Self {
+        let timing = TimingConfig {
+            seed: scenario.seed.unwrap_or(0),
+            ..TimingConfig::default()
+        };
         let mut all_messages = scenario.on_connect;
         for exchange in scenario.exchanges {
             // If exchange has a trigger_pattern, convert responses to AfterWrite timing
@@ -194,7 +228,7 @@ impl MockTransport {
                 all_messages.extend(exchange.responses);
             }
         }
-        Self::new(all_messages, TimingConfig::default())
+        Self::new(all_messages, timing)
     }
 
     /// Check if there are pending triggered messages
@@ -219,13 +253,39 @@ impl Transport for MockTransport {
         if !self.connected.load(Ordering::SeqCst) {
             return Err(ClaudeError::Transport("Not connected".to_string()));
         }
+        if let Some(code) = *self.exited.lock().unwrap() {
+            return Err(ClaudeError::Transport(format!(
+                "Failed to write to stdin: process exited with code {}",
+                code
+            )));
+        }
+        let writes = self.writes.load(Ordering::SeqCst);
+        if self
+            .faults
+            .write_limit()
+            .is_some_and(|limit| writes >= limit)
+        {
+            return Err(ClaudeError::Transport(
+                "Failed to write to stdin: Broken pipe".to_string(),
+            ));
+        }
+        self.writes.fetch_add(1, Ordering::SeqCst);
 
         let parsed = serde_json::from_str(data).ok();
-        self.written.lock().await.push(WrittenMessage {
+        let message = WrittenMessage {
             data: data.to_string(),
             parsed,
             timestamp: std::time::Instant::now(),
-        });
+        };
+        self.written.lock().await.push(message.clone());
+        if let Ok(mut subscribers) = self.write_subscribers.lock() {
+            subscribers.retain(|tx| tx.send(message.clone()).is_ok());
+        }
+
+        if let Some(code) = self.faults.exit_at(writes + 1) {
+            *self.exited.lock().unwrap() = Some(code);
+            return Ok(());
+        }
 
         // Check if any messages are waiting for this write
         self.check_write_triggers(data).await;
@@ -239,7 +299,13 @@ impl Transport for MockTransport {
         let injector_rx = self.injector_rx.clone();
         let timing = self.timing.clone();
         let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(self.timing.seed)));
+        let mut faults = FaultInjector::new(
+            self.faults.clone(),
+            self.timing.seed,
+            self.timing.speed_factor,
+        );
         let connected = &self.connected;
+        let exited = &self.exited;
 
         Box::pin(async_stream::stream! {
             loop {
@@ -248,73 +314,87 @@ impl Transport for MockTransport {
                     break;
                 }
 
-                // Check for triggered messages first (from AfterWrite)
-                {
-                    let mut triggered = triggered_messages.lock().await;
-                    if let Some(msg) = triggered.pop_front() {
-                        yield Ok(msg);
-                        continue;
-                    }
+                // A simulated process exit ends the stream with an error
+                let exit_code = *exited.lock().unwrap();
+                if let Some(code) = exit_code {
+                    yield Err(ClaudeError::Process(ProcessError::new(
+                        format!("Command failed with exit code {}", code),
+                        Some(code),
+                        None,
+                    )));
+                    break;
                 }
 
-                // Check for injected messages (non-blocking)
-                if let Ok(msg) = injector_rx.try_recv() {
-                    yield Ok(msg);
-                    continue;
+                if faults.is_disconnected() {
+                    break;
                 }
 
-                // Check pre-loaded messages
-                let next = {
-                    let mut guard = messages.lock().await;
-                    guard.pop_front()
-                };
+                // Check for triggered messages first (from AfterWrite)
+                let triggered = triggered_messages.lock().await.pop_front();
 
-                match next {
-                    Some(scheduled) => {
-                        // Apply timing
-                        match scheduled.timing {
-                            MessageTiming::Immediate => {},
-                            MessageTiming::Delayed { base_ms, jitter_ms } => {
-                                let jitter = if jitter_ms > 0 {
-                                    let mut rng_guard = rng.lock().await;
-                                    rng_guard.gen_range(0..=jitter_ms)
-                                } else {
-                                    0
-                                };
-                                let delay = Duration::from_millis(
-                                    ((base_ms + jitter) as f64 * timing.speed_factor) as u64
-                                );
-                                if delay > Duration::ZERO {
-                                    tokio::time::sleep(delay).await;
-                                }
-                            },
-                            MessageTiming::AfterWrite { .. } => {
-                                // Should not reach here - AfterWrite messages are in pending_triggers
-                            },
+                let value = if let Some(msg) = triggered {
+                    msg
+                } else if let Ok(msg) = injector_rx.try_recv() {
+                    // Injected messages (non-blocking)
+                    msg
+                } else {
+                    // Check pre-loaded messages
+                    let next = {
+                        let mut guard = messages.lock().await;
+                        guard.pop_front()
+                    };
+
+                    match next {
+                        Some(scheduled) => {
+                            // Apply timing
+                            match scheduled.timing {
+                                MessageTiming::Immediate => {},
+                                MessageTiming::Delayed { base_ms, jitter_ms } => {
+                                    let jitter = if jitter_ms > 0 {
+                                        let mut rng_guard = rng.lock().await;
+                                        rng_guard.gen_range(0..=jitter_ms)
+                                    } else {
+                                        0
+                                    };
+                                    let delay = Duration::from_millis(
+                                        ((base_ms + jitter) as f64 * timing.speed_factor) as u64
+                                    );
+                                    if delay > Duration::ZERO {
+                                        tokio::time::sleep(delay).await;
+                                    }
+                                },
+                                MessageTiming::AfterWrite { .. } => {
+                                    // Should not reach here - AfterWrite messages are in pending_triggers
+                                },
+                            }
+                            scheduled.value
                         }
-                        yield Ok(scheduled.value);
-                    }
-                    None => {
-                        // No more pre-loaded messages, poll for injection or triggered messages
-                        // Use a short timeout to periodically check for triggered messages
-                        // This prevents deadlock when AfterWrite messages are triggered by writes
-                        tokio::select! {
-                            result = injector_rx.recv_async() => {
-                                match result {
-                                    Ok(msg) => yield Ok(msg),
-                                    Err(_) => break, // Channel closed
+                        None => {
+                            // No more pre-loaded messages, poll for injection or triggered messages
+                            // Use a short timeout to periodically check for triggered messages
+                            // This prevents deadlock when AfterWrite messages are triggered by writes
+                            tokio::select! {
+                                result = injector_rx.recv_async() => {
+                                    match result {
+                                        Ok(msg) => msg,
+                                        Err(_) => break, // Channel closed
+                                    }
                                 }
-                            }
-                            _ = tokio::time::sleep(Duration::from_millis(10)) => {
-                                // Check if we're still connected and if triggered messages appeared
-                                if !connected.load(Ordering::SeqCst) {
-                                    break;
+                                _ = tokio::time::sleep(Duration::from_millis(10)) => {
+                                    // Loop back to check connection, faults and triggered messages
+                                    continue;
                                 }
-                                // Loop back to check triggered_messages
-                                continue;
                             }
                         }
                     }
+                };
+
+                let (items, end) = faults.deliver(value).await;
+                for item in items {
+                    yield item;
+                }
+                if end {
+                    break;
                 }
             }
         })
@@ -340,6 +420,7 @@ impl Transport for MockTransport {
 pub struct MockTransportBuilder {
     messages: Vec<ScheduledMessage>,
     timing: Option<TimingConfig>,
+    faults: FaultPlan,
 }
 
 impl MockTransportBuilder {
@@ -401,9 +482,17 @@ impl MockTransportBuilder {
         self
     }
 
+    /// Schedule failures, randomized with the timing seed (see [`FaultPlan`])
+    pub fn faults(mut self, plan: FaultPlan) -> Self {
+        self.faults = plan;
+        self
+    }
+
     /// Build the transport
     pub fn build(self) -> MockTransport {
-        MockTransport::new(self.messages, self.timing.unwrap_or_default())
+        let mut transport = MockTransport::new(self.messages, self.timing.unwrap_or_default());
+        transport.faults = self.faults;
+        transport
     }
 }
 
@@ -412,6 +501,17 @@ mod tests {
     use super::*;
     use futures::StreamExt;
 
+    #[test]
+    fn test_from_scenario_uses_scenario_seed() {
+        let scenario = super::super::ScenarioBuilder::new("seeded")
+            .seed(42)
+            .build();
+        assert_eq!(MockTransport::from_scenario(scenario).timing.seed, 42);
+
+        let unseeded = super::super::ScenarioBuilder::new("unseeded").build();
+        assert_eq!(MockTransport::from_scenario(unseeded).timing.seed, 0);
+    }
+
     #[tokio::test]
     async fn test_mock_transport_basic() {
         let transport = MockTransport::builder()
@@ -0,0 +1,295 @@
+//! Loading and validating subagent definitions
+//!
+//! Agents can be defined in Markdown files with YAML frontmatter, the format
+//! Claude Code reads from `.claude/agents/`:
+//!
+//! ```markdown
+//! ---
+//! name: code-reviewer
+//! description: Reviews code for bugs and style issues
+//! tools: Read, Grep, Glob
+//! model: claude-sonnet-4-5-20250929
+//! permissionMode: plan
+//! ---
+//! You are a meticulous code reviewer...
+//! ```
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::config::{AgentDefinition, AgentModel, PermissionMode};
+use crate::errors::{ClaudeError, Result};
+
+/// A list that may be written as `[A, B]` or as a comma-separated string `A, B`
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum NameList {
+    List(Vec<String>),
+    Csv(String),
+}
+
+impl From<NameList> for Vec<String> {
+    fn from(list: NameList) -> Self {
+        match list {
+            NameList::List(items) => items,
+            NameList::Csv(csv) => csv
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AgentFrontmatter {
+    name: Option<String>,
+    description: String,
+    tools: Option<NameList>,
+    disallowed_tools: Option<NameList>,
+    model: Option<AgentModel>,
+    permission_mode: Option<PermissionMode>,
+    mcp_servers: Option<NameList>,
+}
+
+impl AgentDefinition {
+    /// Parse an agent from Markdown with YAML frontmatter
+    ///
+    /// Returns the agent name from the frontmatter (if present) and the definition.
+    /// The Markdown body becomes the agent prompt.
+    pub fn from_markdown(content: &str) -> Result<(Option<String>, AgentDefinition)> {
+        let (frontmatter, body) = split_frontmatter(content).ok_or_else(|| {
+            ClaudeError::InvalidConfig("Agent file is missing YAML frontmatter".to_string())
+        })?;
+
+        let parsed: AgentFrontmatter = serde_norway::from_str(frontmatter)
+            .map_err(|e| ClaudeError::InvalidConfig(format!("Invalid agent frontmatter: {}", e)))?;
+
+        let agent = AgentDefinition {
+            description: parsed.description,
+            prompt: body.trim().to_string(),
+            tools: parsed.tools.map(Into::into),
+            disallowed_tools: parsed.disallowed_tools.map(Into::into),
+            model: parsed.model,
+            permission_mode: parsed.permission_mode,
+            mcp_servers: parsed.mcp_servers.map(Into::into),
+        };
+
+        Ok((parsed.name, agent))
+    }
+
+    /// Check that every tool granted to this agent exists in the parent's tool set
+    ///
+    /// Only `tools` is checked: disallowing a tool the parent lacks is harmless. Tool
+    /// specifiers such as `Bash(git:*)` are matched by their base name, and an
+    /// `mcp__<server>` entry in the parent covers all of that server's tools.
+    pub fn validate_tools(&self, parent_tools: &[String]) -> Result<()> {
+        let unknown: Vec<&str> = self
+            .tools
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .filter(|tool| !parent_tools.iter().any(|p| tool_matches(p, tool)))
+            .collect();
+
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(ClaudeError::InvalidConfig(format!(
+                "Agent references tools not available to the parent: {}",
+                unknown.join(", ")
+            )))
+        }
+    }
+}
+
+/// Load an agent from a Markdown file
+///
+/// The agent name defaults to the file stem when the frontmatter has no `name`.
+pub fn load_agent_file(path: impl AsRef<Path>) -> Result<(String, AgentDefinition)> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)?;
+    let (name, agent) = AgentDefinition::from_markdown(&content).map_err(|e| match e {
+        ClaudeError::InvalidConfig(msg) => {
+            ClaudeError::InvalidConfig(format!("{}: {}", path.display(), msg))
+        }
+        other => other,
+    })?;
+
+    let name = match name {
+        Some(name) => name,
+        None => path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .ok_or_else(|| {
+                ClaudeError::InvalidConfig(format!("{}: cannot derive agent name", path.display()))
+            })?,
+    };
+
+    Ok((name, agent))
+}
+
+/// Load all `*.md` agent files in a directory (e.g. `.claude/agents`)
+///
+/// A missing directory yields an empty map.
+pub fn load_agents_dir(dir: impl AsRef<Path>) -> Result<HashMap<String, AgentDefinition>> {
+    let dir = dir.as_ref();
+    let mut agents = HashMap::new();
+    if !dir.is_dir() {
+        return Ok(agents);
+    }
+
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let (name, agent) = load_agent_file(&path)?;
+        agents.insert(name, agent);
+    }
+
+    Ok(agents)
+}
+
+/// Validate the tools of every agent against the parent's tool set
+pub fn validate_agent_tools(
+    agents: &HashMap<String, AgentDefinition>,
+    parent_tools: &[String],
+) -> Result<()> {
+    let mut names: Vec<&String> = agents.keys().collect();
+    names.sort();
+    for name in names {
+        agents[name]
+            .validate_tools(parent_tools)
+            .map_err(|e| match e {
+                ClaudeError::InvalidConfig(msg) => {
+                    ClaudeError::InvalidConfig(format!("agent '{}': {}", name, msg))
+                }
+                other => other,
+            })?;
+    }
+    Ok(())
+}
+
+/// Split `---\n<yaml>\n---\n<body>` into its parts
+pub(crate) fn split_frontmatter(content: &str) -> Option<(&str, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn base_tool_name(tool: &str) -> &str {
+    tool.split('(').next().unwrap_or(tool).trim()
+}
+
+fn tool_matches(parent: &str, tool: &str) -> bool {
+    let parent = base_tool_name(parent);
+    let tool = base_tool_name(tool);
+    parent == tool
+        || (parent.starts_with("mcp__")
+            && tool
+                .strip_prefix(parent)
+                .is_some_and(|rest| rest.starts_with("__")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REVIEWER: &str = "---
+name: code-reviewer
+description: Reviews code
+tools: Read, Grep, Bash(git diff:*)
+disallowedTools: [Write]
+model: claude-opus-4-1-20250805
+permissionMode: plan
+mcpServers: [github]
+---
+
+You are a code reviewer.
+";
+
+    #[test]
+    fn test_from_markdown() {
+        let (name, agent) = AgentDefinition::from_markdown(REVIEWER).unwrap();
+        assert_eq!(name.as_deref(), Some("code-reviewer"));
+        assert_eq!(agent.description, "Reviews code");
+        assert_eq!(agent.prompt, "You are a code reviewer.");
+        assert_eq!(
+            agent.tools.as_deref(),
+            Some(&["Read".to_string(), "Grep".into(), "Bash(git diff:*)".into()][..])
+        );
+        assert_eq!(agent.disallowed_tools, Some(vec!["Write".to_string()]));
+        assert_eq!(
+            agent.model,
+            Some(AgentModel::Custom("claude-opus-4-1-20250805".to_string()))
+        );
+        assert_eq!(agent.permission_mode, Some(PermissionMode::Plan));
+        assert_eq!(agent.mcp_servers, Some(vec!["github".to_string()]));
+    }
+
+    #[test]
+    fn test_from_markdown_requires_frontmatter() {
+        assert!(AgentDefinition::from_markdown("Just a prompt").is_err());
+        assert!(AgentDefinition::from_markdown("---\nname: x\n").is_err());
+    }
+
+    #[test]
+    fn test_load_agents_dir_uses_file_stem() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::write(dir.join("reviewer.md"), REVIEWER).unwrap();
+        std::fs::write(
+            dir.join("writer.md"),
+            "---\ndescription: Writes docs\nmodel: haiku\n---\nWrite docs.\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let agents = load_agents_dir(dir).unwrap();
+
+        assert_eq!(agents.len(), 2);
+        assert!(agents.contains_key("code-reviewer"));
+        assert_eq!(agents["writer"].model, Some(AgentModel::Haiku));
+    }
+
+    #[test]
+    fn test_validate_tools() {
+        let (_, agent) = AgentDefinition::from_markdown(REVIEWER).unwrap();
+        let parent: Vec<String> = ["Read", "Grep", "Bash", "Write"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(agent.validate_tools(&parent).is_ok());
+
+        let err = agent.validate_tools(&parent[..2]).unwrap_err().to_string();
+        assert!(err.contains("Bash(git diff:*)"));
+        // Disallowed tools need not exist in the parent
+        assert!(!err.contains("Write"));
+        assert!(agent.validate_tools(&parent[..3]).is_ok());
+
+        let mcp_agent = AgentDefinition::builder()
+            .description("d")
+            .prompt("p")
+            .tools(vec!["mcp__github__create_issue".to_string()])
+            .build();
+        assert!(
+            mcp_agent
+                .validate_tools(&["mcp__github".to_string()])
+                .is_ok()
+        );
+        assert!(mcp_agent.validate_tools(&["mcp__git".to_string()]).is_err());
+    }
+}
@@ -11,6 +11,8 @@ use super::hooks::{HookEvent, HookMatcher};
 use super::mcp::McpServers;
 use super::permissions::CanUseToolCallback;
 use super::plugin::SdkPluginConfig;
+use super::process::{ProcessIsolation, ProcessLimits, ShutdownPolicy};
+use crate::cli::CliVersion;
 
 /// Main configuration options for Claude Agent
 #[derive(Clone, TypedBuilder)]
@@ -80,13 +82,45 @@ pub struct ClaudeAgentOptions {
     /// Extra CLI arguments
     #[builder(default)]
     pub extra_args: HashMap<String, Option<String>>,
-    /// Maximum buffer size for subprocess output
+    /// Maximum size of a single line of subprocess output (default 10MB)
+    ///
+    /// Longer lines are skipped and reported as
+    /// [`ClaudeError::BufferSizeExceeded`](crate::ClaudeError::BufferSizeExceeded);
+    /// the session continues.
     #[builder(default, setter(strip_option))]
     pub max_buffer_size: Option<usize>,
+    /// Total bytes of subprocess output allowed for the whole session
+    ///
+    /// Once exceeded, the message stream ends with a transport error. Unlimited by
+    /// default.
+    #[builder(default, setter(strip_option))]
+    pub max_output_bytes: Option<u64>,
+    /// How the CLI process is stopped on disconnect
+    #[builder(default)]
+    pub shutdown_policy: ShutdownPolicy,
+    /// Start the CLI in its own process group or session, so its descendants are
+    /// signalled together with it on disconnect and drop (Unix only)
+    #[builder(default)]
+    pub process_isolation: ProcessIsolation,
+    /// Have the kernel send `SIGKILL` to the CLI if this process dies without
+    /// disconnecting (Linux only, via `PR_SET_PDEATHSIG`)
+    ///
+    /// Only the CLI itself is covered; combine with
+    /// [`process_isolation`](Self::process_isolation) so that its descendants are cleaned
+    /// up on orderly shutdown too. The kernel ties the signal to the thread that spawned
+    /// the CLI, so connect from a long-lived runtime thread rather than a temporary one.
+    #[builder(default)]
+    pub kill_on_parent_death: bool,
+    /// Resource limits applied to the CLI process before it starts
+    #[builder(default)]
+    pub process_limits: ProcessLimits,
     /// Callback for stderr output
     #[builder(default, setter(strip_option))]
     pub stderr_callback: Option<Arc<dyn Fn(String) + Send + Sync>>,
     /// Callback for tool usage permission
+    ///
+    /// Needs the control protocol, so it is only supported by [`ClaudeClient`](crate::ClaudeClient)
+    /// and cannot be combined with `permission_prompt_tool_name`.
     #[builder(default, setter(strip_option))]
     pub can_use_tool: Option<CanUseToolCallback>,
     /// Hook callbacks
@@ -132,9 +166,18 @@ pub struct ClaudeAgentOptions {
 
     /// Skip CLI version check on connect (default: false).
     /// When true, saves ~100-500ms by skipping the version compatibility check.
-    /// Use when you know the CLI version is compatible.
+    /// Use when you know the CLI version is compatible. Ignored when
+    /// `min_cli_version` or `max_cli_version` is set, since those bounds are always
+    /// enforced.
     #[builder(default = false)]
     pub skip_version_check: bool,
+    /// Reject CLI versions older than this on connect, instead of only logging a warning
+    /// below [`MIN_CLI_VERSION`](crate::version::MIN_CLI_VERSION)
+    #[builder(default, setter(strip_option))]
+    pub min_cli_version: Option<CliVersion>,
+    /// Reject CLI versions newer than this on connect
+    #[builder(default, setter(strip_option))]
+    pub max_cli_version: Option<CliVersion>,
 
     /// Enable verbose CLI output (default: true).
     ///
@@ -258,6 +301,9 @@ pub enum SettingSource {
 }
 
 /// Custom agent definition
+///
+/// Agents can also be loaded from Markdown files with YAML frontmatter
+/// (the `.claude/agents/*.md` format), see [`AgentDefinition::from_markdown`].
 #[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
 #[builder(doc)]
 pub struct AgentDefinition {
@@ -271,15 +317,41 @@ pub struct AgentDefinition {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[builder(default, setter(into, strip_option))]
     pub tools: Option<Vec<String>>,
+    /// Tools the agent may not use
+    #[serde(
+        rename = "disallowedTools",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[builder(default, setter(into, strip_option))]
+    pub disallowed_tools: Option<Vec<String>>,
     /// Model to use for the agent
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(into, strip_option))]
     pub model: Option<AgentModel>,
+    /// Permission mode for the agent's tool calls
+    #[serde(
+        rename = "permissionMode",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[builder(default, setter(strip_option))]
+    pub permission_mode: Option<PermissionMode>,
+    /// Names of the parent's MCP servers the agent may use
+    #[serde(
+        rename = "mcpServers",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[builder(default, setter(into, strip_option))]
+    pub mcp_servers: Option<Vec<String>>,
 }
 
 /// Model selection for agents
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
+///
+/// Serialized as a plain string: the aliases `sonnet`, `opus`, `haiku` and
+/// `inherit`, or a full model ID such as `claude-sonnet-4-5-20250929`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AgentModel {
     /// Claude Sonnet
     Sonnet,
@@ -289,6 +361,51 @@ pub enum AgentModel {
     Haiku,
     /// Inherit from parent
     Inherit,
+    /// Exact model ID
+    Custom(String),
+}
+
+impl AgentModel {
+    /// The string sent to the CLI
+    pub fn as_str(&self) -> &str {
+        match self {
+            AgentModel::Sonnet => "sonnet",
+            AgentModel::Opus => "opus",
+            AgentModel::Haiku => "haiku",
+            AgentModel::Inherit => "inherit",
+            AgentModel::Custom(id) => id,
+        }
+    }
+}
+
+impl From<&str> for AgentModel {
+    fn from(value: &str) -> Self {
+        match value {
+            "sonnet" => AgentModel::Sonnet,
+            "opus" => AgentModel::Opus,
+            "haiku" => AgentModel::Haiku,
+            "inherit" => AgentModel::Inherit,
+            other => AgentModel::Custom(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for AgentModel {
+    fn from(value: String) -> Self {
+        AgentModel::from(value.as_str())
+    }
+}
+
+impl Serialize for AgentModel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for AgentModel {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(AgentModel::from(String::deserialize(deserializer)?))
+    }
 }
 
 /// SDK Beta features
@@ -486,6 +603,38 @@ pub struct SandboxSettings {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_agent_model_serde() {
+        assert_eq!(serde_json::to_value(AgentModel::Sonnet).unwrap(), "sonnet");
+        assert_eq!(
+            serde_json::to_value(AgentModel::from("claude-sonnet-4-5-20250929")).unwrap(),
+            "claude-sonnet-4-5-20250929"
+        );
+        let model: AgentModel = serde_json::from_value(serde_json::json!("inherit")).unwrap();
+        assert_eq!(model, AgentModel::Inherit);
+        let model: AgentModel =
+            serde_json::from_value(serde_json::json!("claude-haiku-4-5")).unwrap();
+        assert_eq!(model, AgentModel::Custom("claude-haiku-4-5".to_string()));
+    }
+
+    #[test]
+    fn test_agent_definition_wire_format() {
+        let agent = AgentDefinition::builder()
+            .description("Reviewer")
+            .prompt("Review")
+            .disallowed_tools(vec!["Write".to_string()])
+            .model("claude-opus-4-1")
+            .permission_mode(PermissionMode::AcceptEdits)
+            .mcp_servers(vec!["github".to_string()])
+            .build();
+        let value = serde_json::to_value(&agent).unwrap();
+        assert_eq!(value["disallowedTools"], serde_json::json!(["Write"]));
+        assert_eq!(value["model"], "claude-opus-4-1");
+        assert_eq!(value["permissionMode"], "acceptEdits");
+        assert_eq!(value["mcpServers"], serde_json::json!(["github"]));
+        assert!(value.get("tools").is_none());
+    }
+
     #[test]
     fn test_tools_from_str_array() {
         let tools: Tools = ["Write", "Read", "Bash"].into();
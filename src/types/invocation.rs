@@ -0,0 +1,378 @@
+//! Dry-run view of the CLI invocation built from [`ClaudeAgentOptions`]
+//!
+//! [`ClaudeAgentOptions::to_invocation`] resolves the CLI and builds the same argv,
+//! environment and `initialize` request that [`ClaudeClient`](crate::ClaudeClient) uses,
+//! without starting anything. Secrets are redacted, so the result is safe to log.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use super::config::ClaudeAgentOptions;
+use super::mcp::{McpServerConfig, McpServers};
+use crate::cli::CliLocator;
+use crate::client::ClaudeClient;
+use crate::errors::Result;
+use crate::internal::query_full::initialize_request;
+use crate::internal::secrets::{API_KEY_PREFIX, is_secret_name};
+use crate::internal::transport::SubprocessTransport;
+use crate::internal::transport::subprocess::QueryPrompt;
+
+/// Placeholder for redacted values
+pub const REDACTED: &str = "<redacted>";
+
+/// How the CLI would be started for a [`ClaudeClient`] session
+///
+/// Values of environment variables, JSON keys and extra flags whose names look secret
+/// (`*_KEY`, `*_TOKEN`, `Authorization`, ...) and anything resembling an Anthropic API
+/// key are replaced with [`REDACTED`]. Process isolation and resource limits are
+/// applied when spawning and do not appear here.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliInvocation {
+    /// Resolved CLI executable
+    pub cli_path: PathBuf,
+    /// Arguments passed to the CLI
+    pub args: Vec<String>,
+    /// Variables set on top of the inherited environment
+    pub env: BTreeMap<String, String>,
+    /// Working directory
+    pub cwd: Option<PathBuf>,
+    /// The `initialize` control request sent once the CLI is up
+    pub initialize_request: Value,
+    /// Names of in-process SDK MCP servers served over the control protocol
+    pub sdk_mcp_servers: Vec<String>,
+}
+
+impl CliInvocation {
+    /// Render as a POSIX shell command line
+    ///
+    /// Redacted environment variables are passed through from the calling shell as
+    /// `NAME="$NAME"`. The CLI reads stream-json messages on stdin, starting with
+    /// [`initialize_request`](Self::initialize_request) wrapped in a `control_request`.
+    pub fn to_shell(&self) -> String {
+        let mut words = Vec::new();
+        for (name, value) in &self.env {
+            if value == REDACTED {
+                words.push(format!("{}=\"${}\"", name, name));
+            } else {
+                words.push(format!("{}={}", name, shell_quote(value)));
+            }
+        }
+        words.push(shell_quote(&self.cli_path.to_string_lossy()));
+        words.extend(self.args.iter().map(|arg| shell_quote(arg)));
+
+        let command = words.join(" ");
+        match &self.cwd {
+            Some(cwd) => format!("cd {} && {}", shell_quote(&cwd.to_string_lossy()), command),
+            None => command,
+        }
+    }
+}
+
+impl fmt::Display for CliInvocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_shell())
+    }
+}
+
+impl ClaudeAgentOptions {
+    /// Describe the CLI invocation these options produce, without starting it
+    ///
+    /// Resolves the CLI like [`ClaudeClient::connect`](crate::ClaudeClient::connect) does,
+    /// but skips option validation and the version check so that problematic options
+    /// can be inspected. Hook callbacks are numbered in event order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use claude_agent_sdk_rs::ClaudeAgentOptions;
+    ///
+    /// let options = ClaudeAgentOptions::builder()
+    ///     .cli_path("/usr/local/bin/claude")
+    ///     .model("claude-sonnet-4-5")
+    ///     .build();
+    ///
+    /// let invocation = options.to_invocation()?;
+    /// assert!(invocation.args.windows(2).any(|w| w == ["--model", "claude-sonnet-4-5"]));
+    /// println!("{}", invocation.to_shell());
+    /// # Ok::<(), claude_agent_sdk_rs::ClaudeError>(())
+    /// ```
+    pub fn to_invocation(&self) -> Result<CliInvocation> {
+        let transport = SubprocessTransport::new(QueryPrompt::Streaming, self.clone())?;
+
+        let env = transport
+            .build_env()
+            .into_iter()
+            .map(|(name, value)| {
+                let value = if is_secret_name(&name) || value.starts_with(API_KEY_PREFIX) {
+                    REDACTED.to_string()
+                } else {
+                    value
+                };
+                (name, value)
+            })
+            .collect();
+
+        let mut next_id = 0;
+        let initialize_request = initialize_request(ClaudeClient::build_hooks_config(self), |_| {
+            let id = format!("hook_{}", next_id);
+            next_id += 1;
+            id
+        });
+
+        let mut sdk_mcp_servers: Vec<String> = match &self.mcp_servers {
+            McpServers::Dict(servers) => servers
+                .iter()
+                .filter(|(_, config)| matches!(config, McpServerConfig::Sdk(_)))
+                .map(|(name, _)| name.clone())
+                .collect(),
+            _ => Vec::new(),
+        };
+        sdk_mcp_servers.sort();
+
+        // connect() would search for an executable within the version bounds; a dry run
+        // may block, so probe here
+        let cli_path = if self.cli_path.is_none()
+            && (self.min_cli_version.is_some() || self.max_cli_version.is_some())
+        {
+            CliLocator::from_options(self).locate()?.path
+        } else {
+            transport.cli_path().to_path_buf()
+        };
+
+        Ok(CliInvocation {
+            cli_path,
+            args: redact_args(transport.build_command()),
+            env,
+            cwd: transport.cwd().map(Path::to_path_buf),
+            initialize_request,
+            sdk_mcp_servers,
+        })
+    }
+}
+
+/// Redact values of secret-looking flags and secrets inside JSON arguments
+fn redact_args(args: Vec<String>) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut secret_flag = false;
+    for arg in args {
+        let is_flag = arg.starts_with("--");
+        let arg = if !is_flag && (secret_flag || arg.starts_with(API_KEY_PREFIX)) {
+            REDACTED.to_string()
+        } else if arg.starts_with('{')
+            && let Ok(mut value) = serde_json::from_str::<Value>(&arg)
+        {
+            redact_json(&mut value);
+            value.to_string()
+        } else {
+            arg
+        };
+        secret_flag = is_flag && is_secret_name(&arg);
+        redacted.push(arg);
+    }
+    redacted
+}
+
+/// Redact scalars under secret-looking keys, and API keys anywhere
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::String(s) if s.starts_with(API_KEY_PREFIX) => *s = REDACTED.to_string(),
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                if is_secret_name(key) && (item.is_string() || item.is_number()) {
+                    *item = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(item);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Quote a word for a POSIX shell, leaving plain words untouched
+fn shell_quote(word: &str) -> String {
+    let plain = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:=,+@%".contains(c));
+    if plain {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::hooks::{HookContext, HookInput, HookJsonOutput, Hooks, SyncHookJsonOutput};
+    use crate::types::mcp::{McpHttpServerConfig, McpStdioServerConfig, create_sdk_mcp_server};
+    use std::collections::HashMap;
+
+    const CLI: &str = "/opt/claude/bin/claude";
+
+    #[test]
+    fn test_invocation_matches_transport() {
+        let options = ClaudeAgentOptions::builder()
+            .cli_path(CLI)
+            .cwd(std::env::temp_dir())
+            .model("claude-sonnet-4-5")
+            .max_turns(3)
+            .env(HashMap::from([("REGION".to_string(), "eu".to_string())]))
+            .build();
+        let invocation = options.to_invocation().unwrap();
+
+        assert_eq!(invocation.cli_path, PathBuf::from(CLI));
+        assert_eq!(
+            invocation.cwd.as_deref(),
+            Some(std::env::temp_dir().as_path())
+        );
+        assert_eq!(
+            invocation.args[..5],
+            [
+                "--output-format",
+                "stream-json",
+                "--verbose",
+                "--input-format",
+                "stream-json"
+            ]
+        );
+        assert!(
+            invocation
+                .args
+                .windows(2)
+                .any(|w| w == ["--max-turns", "3"])
+        );
+        assert_eq!(invocation.env["REGION"], "eu");
+        assert!(invocation.env.contains_key("CLAUDE_CODE_ENTRYPOINT"));
+        assert_eq!(
+            invocation.initialize_request,
+            serde_json::json!({"subtype": "initialize", "hooks": null})
+        );
+    }
+
+    #[test]
+    fn test_secrets_are_redacted() {
+        let options = ClaudeAgentOptions::builder()
+            .cli_path(CLI)
+            .env(HashMap::from([
+                ("ANTHROPIC_API_KEY".to_string(), "abc".to_string()),
+                ("NOTES".to_string(), "sk-ant-123".to_string()),
+                ("MAX_TOKENS".to_string(), "100".to_string()),
+            ]))
+            .mcp_servers(McpServers::Dict(HashMap::from([
+                (
+                    "remote".to_string(),
+                    McpServerConfig::Http(McpHttpServerConfig {
+                        url: "https://example.com".to_string(),
+                        headers: Some(HashMap::from([(
+                            "Authorization".to_string(),
+                            "Bearer xyz".to_string(),
+                        )])),
+                    }),
+                ),
+                (
+                    "local".to_string(),
+                    McpServerConfig::Stdio(McpStdioServerConfig {
+                        command: "server".to_string(),
+                        args: None,
+                        env: Some(HashMap::from([(
+                            "GITHUB_TOKEN".to_string(),
+                            "ghp".to_string(),
+                        )])),
+                    }),
+                ),
+            ])))
+            .extra_args(HashMap::from([
+                ("api-key".to_string(), Some("abc".to_string())),
+                ("debug".to_string(), None),
+            ]))
+            .max_thinking_tokens(8000)
+            .settings(r#"{"oauth": {"accessToken": "at-1", "clientSecret": "cs-2"}}"#)
+            .build();
+        let invocation = options.to_invocation().unwrap();
+
+        assert_eq!(invocation.env["ANTHROPIC_API_KEY"], REDACTED);
+        assert_eq!(invocation.env["NOTES"], REDACTED);
+        assert_eq!(invocation.env["MAX_TOKENS"], "100");
+        assert!(
+            invocation
+                .args
+                .windows(2)
+                .any(|w| w == ["--api-key", REDACTED])
+        );
+        assert!(
+            invocation
+                .args
+                .windows(2)
+                .any(|w| w == ["--max-thinking-tokens", "8000"])
+        );
+        let rendered = invocation.args.join(" ");
+        assert!(!rendered.contains("xyz") && !rendered.contains("ghp"));
+        assert!(!rendered.contains("at-1") && !rendered.contains("cs-2"));
+        assert!(rendered.contains("https://example.com"));
+        assert!(
+            invocation
+                .to_shell()
+                .contains("ANTHROPIC_API_KEY=\"$ANTHROPIC_API_KEY\"")
+        );
+    }
+
+    #[test]
+    fn test_initialize_request_and_sdk_servers() {
+        async fn noop(_: HookInput, _: Option<String>, _: HookContext) -> HookJsonOutput {
+            HookJsonOutput::Sync(SyncHookJsonOutput::default())
+        }
+
+        let mut hooks = Hooks::new();
+        hooks.add_pre_tool_use_with_matcher("Bash", noop);
+        hooks.add_stop(noop);
+        let options = ClaudeAgentOptions::builder()
+            .cli_path(CLI)
+            .hooks(hooks.build())
+            .mcp_servers(McpServers::Dict(HashMap::from([
+                (
+                    "tools-b".to_string(),
+                    McpServerConfig::Sdk(create_sdk_mcp_server("b", "1.0.0", vec![])),
+                ),
+                (
+                    "tools-a".to_string(),
+                    McpServerConfig::Sdk(create_sdk_mcp_server("a", "1.0.0", vec![])),
+                ),
+            ])))
+            .build();
+        let invocation = options.to_invocation().unwrap();
+
+        assert_eq!(
+            invocation.initialize_request,
+            serde_json::json!({
+                "subtype": "initialize",
+                "hooks": {
+                    "PreToolUse": [{"matcher": "Bash", "hookCallbackIds": ["hook_0"]}],
+                    "Stop": [{"matcher": null, "hookCallbackIds": ["hook_1"]}]
+                }
+            })
+        );
+        assert_eq!(invocation.sdk_mcp_servers, ["tools-a", "tools-b"]);
+        let mcp_config = invocation
+            .args
+            .iter()
+            .skip_while(|arg| *arg != "--mcp-config")
+            .nth(1)
+            .unwrap();
+        assert!(mcp_config.contains(r#""type":"sdk""#));
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("--model"), "--model");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote(r#"{"a":1}"#), r#"'{"a":1}'"#);
+    }
+}
@@ -6,8 +6,10 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
 
-use crate::errors::Result;
+use crate::errors::{ClaudeError, Result};
 
 /// MCP servers configuration
 #[derive(Clone, Default)]
@@ -81,12 +83,292 @@ pub struct McpSdkServerConfig {
 pub trait SdkMcpServer: Send + Sync {
     /// Handle an MCP message
     async fn handle_message(&self, message: serde_json::Value) -> Result<serde_json::Value>;
+
+    /// Handle an MCP message with access to the session's notification channel
+    ///
+    /// The SDK always dispatches through this method. The default implementation
+    /// ignores the context and delegates to [`handle_message`](Self::handle_message),
+    /// so existing servers keep working unchanged.
+    async fn handle_message_with_context(
+        &self,
+        message: serde_json::Value,
+        context: McpRequestContext,
+    ) -> Result<serde_json::Value> {
+        let _ = context;
+        self.handle_message(message).await
+    }
 }
 
 /// Tool handler trait
 pub trait ToolHandler: Send + Sync {
     /// Handle a tool invocation
     fn handle(&self, args: serde_json::Value) -> BoxFuture<'static, Result<ToolResult>>;
+
+    /// Handle a tool invocation with a [`ToolContext`]
+    ///
+    /// Override this to report progress, emit log messages or observe
+    /// cancellation. The default implementation delegates to [`handle`](Self::handle).
+    fn handle_with_context(
+        &self,
+        args: serde_json::Value,
+        context: ToolContext,
+    ) -> BoxFuture<'static, Result<ToolResult>> {
+        let _ = context;
+        self.handle(args)
+    }
+}
+
+/// Sink for server-initiated MCP notifications
+///
+/// Each call sends one JSON-RPC notification (without `id`) to the CLI through
+/// the `mcp_message` control channel.
+pub type McpNotificationSink =
+    Arc<dyn Fn(serde_json::Value) -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// Context passed to [`SdkMcpServer::handle_message_with_context`]
+#[derive(Clone, Default)]
+pub struct McpRequestContext {
+    notifier: Option<McpNotificationSink>,
+}
+
+impl McpRequestContext {
+    /// Create a context that forwards notifications to the given sink
+    pub fn new(notifier: McpNotificationSink) -> Self {
+        Self {
+            notifier: Some(notifier),
+        }
+    }
+
+    /// Send a JSON-RPC notification to the CLI
+    ///
+    /// This is a no-op when the context has no notification sink (e.g. in tests
+    /// that call the server directly).
+    pub async fn notify(&self, method: &str, params: serde_json::Value) -> Result<()> {
+        match &self.notifier {
+            Some(notifier) => {
+                notifier(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "method": method,
+                    "params": params
+                }))
+                .await
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Cancellation signal for an in-flight tool call
+///
+/// Triggered when the CLI sends `notifications/cancelled` for the request.
+/// Cloning shares the underlying signal.
+#[derive(Clone, Default)]
+pub struct CancellationSignal {
+    inner: Arc<CancellationState>,
+}
+
+#[derive(Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationSignal {
+    /// Create a new, untriggered signal
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trigger cancellation, waking all waiters
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Check whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Wait until cancellation is requested
+    ///
+    /// Useful with `tokio::select!` to abort long-running work.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+impl std::fmt::Debug for CancellationSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellationSignal")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Log severity for MCP `notifications/message` (RFC 5424 levels)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum McpLogLevel {
+    /// Detailed debugging information
+    Debug,
+    /// Informational messages
+    Info,
+    /// Normal but significant events
+    Notice,
+    /// Warning conditions
+    Warning,
+    /// Error conditions
+    Error,
+    /// Critical conditions
+    Critical,
+    /// Action must be taken immediately
+    Alert,
+    /// System is unusable
+    Emergency,
+}
+
+/// Per-invocation context for SDK MCP tools
+///
+/// Gives a [`ToolHandler`] access to MCP progress notifications (using the
+/// request's `progressToken`), log messages and the cancellation signal.
+///
+/// # Example
+///
+/// ```no_run
+/// use claude_agent_sdk_rs::{ToolContext, ToolResult, McpToolResultContent};
+///
+/// async fn build(ctx: ToolContext) -> claude_agent_sdk_rs::Result<ToolResult> {
+///     for step in 0..10 {
+///         if ctx.is_cancelled() {
+///             break;
+///         }
+///         ctx.report_progress(step as f64, Some(10.0), Some("compiling")).await?;
+///     }
+///     Ok(ToolResult {
+///         content: vec![McpToolResultContent::Text { text: "done".to_string() }],
+///         is_error: false,
+///     })
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct ToolContext {
+    tool_name: String,
+    request_id: Option<serde_json::Value>,
+    progress_token: Option<serde_json::Value>,
+    mcp: McpRequestContext,
+    cancellation: CancellationSignal,
+}
+
+impl ToolContext {
+    /// Create a context for a tool call
+    pub fn new(
+        tool_name: impl Into<String>,
+        request_id: Option<serde_json::Value>,
+        progress_token: Option<serde_json::Value>,
+        mcp: McpRequestContext,
+        cancellation: CancellationSignal,
+    ) -> Self {
+        Self {
+            tool_name: tool_name.into(),
+            request_id,
+            progress_token,
+            mcp,
+            cancellation,
+        }
+    }
+
+    /// Name of the tool being invoked
+    pub fn tool_name(&self) -> &str {
+        &self.tool_name
+    }
+
+    /// JSON-RPC id of the `tools/call` request
+    pub fn request_id(&self) -> Option<&serde_json::Value> {
+        self.request_id.as_ref()
+    }
+
+    /// Progress token supplied by the caller in `params._meta.progressToken`
+    pub fn progress_token(&self) -> Option<&serde_json::Value> {
+        self.progress_token.as_ref()
+    }
+
+    /// Report progress via `notifications/progress`
+    ///
+    /// Does nothing if the caller did not request progress (no `progressToken`).
+    pub async fn report_progress(
+        &self,
+        progress: f64,
+        total: Option<f64>,
+        message: Option<&str>,
+    ) -> Result<()> {
+        let Some(ref token) = self.progress_token else {
+            return Ok(());
+        };
+
+        let mut params = serde_json::json!({
+            "progressToken": token,
+            "progress": progress
+        });
+        if let Some(total) = total {
+            params["total"] = serde_json::json!(total);
+        }
+        if let Some(message) = message {
+            params["message"] = serde_json::json!(message);
+        }
+
+        self.mcp.notify("notifications/progress", params).await
+    }
+
+    /// Send a log message via `notifications/message`
+    pub async fn log(&self, level: McpLogLevel, data: impl Serialize) -> Result<()> {
+        let data = serde_json::to_value(data).map_err(|e| {
+            ClaudeError::Transport(format!("Failed to serialize log message: {}", e))
+        })?;
+
+        self.mcp
+            .notify(
+                "notifications/message",
+                serde_json::json!({
+                    "level": level,
+                    "logger": self.tool_name,
+                    "data": data
+                }),
+            )
+            .await
+    }
+
+    /// Cancellation signal for this call
+    pub fn cancellation(&self) -> &CancellationSignal {
+        &self.cancellation
+    }
+
+    /// Check whether the CLI cancelled this call
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Wait until the CLI cancels this call
+    pub async fn cancelled(&self) {
+        self.cancellation.cancelled().await
+    }
+}
+
+impl std::fmt::Debug for ToolContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolContext")
+            .field("tool_name", &self.tool_name)
+            .field("request_id", &self.request_id)
+            .field("progress_token", &self.progress_token)
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
 }
 
 /// Tool result
@@ -139,6 +421,7 @@ pub fn create_sdk_mcp_server(
         name: name.into(),
         version: version.into(),
         tools: tools.into_iter().map(|t| (t.name.clone(), t)).collect(),
+        in_flight: dashmap::DashMap::new(),
     };
 
     McpSdkServerConfig {
@@ -152,11 +435,22 @@ struct DefaultSdkMcpServer {
     name: String,
     version: String,
     tools: HashMap<String, SdkMcpTool>,
+    /// Cancellation signals for in-flight `tools/call` requests, keyed by JSON-RPC id
+    in_flight: dashmap::DashMap<String, CancellationSignal>,
 }
 
 #[async_trait]
 impl SdkMcpServer for DefaultSdkMcpServer {
     async fn handle_message(&self, message: serde_json::Value) -> Result<serde_json::Value> {
+        self.handle_message_with_context(message, McpRequestContext::default())
+            .await
+    }
+
+    async fn handle_message_with_context(
+        &self,
+        message: serde_json::Value,
+        context: McpRequestContext,
+    ) -> Result<serde_json::Value> {
         // Parse the MCP message
         let method = message["method"]
             .as_str()
@@ -216,7 +510,32 @@ impl SdkMcpServer for DefaultSdkMcpServer {
                     crate::errors::ClaudeError::Transport(format!("Tool not found: {}", tool_name))
                 })?;
 
-                let result = tool.handler.handle(arguments).await?;
+                let progress_token = params
+                    .get("_meta")
+                    .and_then(|meta| meta.get("progressToken"))
+                    .cloned();
+                let cancellation = CancellationSignal::new();
+                let in_flight_key = message_id.as_ref().map(|id| id.to_string());
+                if let Some(ref key) = in_flight_key {
+                    self.in_flight.insert(key.clone(), cancellation.clone());
+                }
+
+                let tool_context = ToolContext::new(
+                    tool_name,
+                    message_id.clone(),
+                    progress_token,
+                    context,
+                    cancellation,
+                );
+                let result = tool
+                    .handler
+                    .handle_with_context(arguments, tool_context)
+                    .await;
+
+                if let Some(ref key) = in_flight_key {
+                    self.in_flight.remove(key);
+                }
+                let result = result?;
 
                 Ok(serde_json::json!({
                     "jsonrpc": "2.0",
@@ -227,7 +546,19 @@ impl SdkMcpServer for DefaultSdkMcpServer {
                     }
                 }))
             }
-            "notifications/initialized" | "notifications/cancelled" => {
+            "notifications/cancelled" => {
+                // Signal the in-flight tool call, if it is still running
+                if let Some(request_id) = message
+                    .get("params")
+                    .and_then(|p| p.get("requestId"))
+                    .map(|id| id.to_string())
+                    && let Some(signal) = self.in_flight.get(&request_id)
+                {
+                    signal.cancel();
+                }
+                Ok(serde_json::json!(null))
+            }
+            "notifications/initialized" => {
                 // Notifications don't require a response
                 // Return null for notifications
                 Ok(serde_json::json!(null))
@@ -275,3 +606,232 @@ macro_rules! tool {
         }
     }};
 }
+
+/// Macro to create a tool whose handler receives a [`ToolContext`]
+///
+/// Same as [`tool!`](crate::tool), but the handler takes `(args, context)` so it
+/// can report progress, log, and observe cancellation.
+#[macro_export]
+macro_rules! tool_with_context {
+    ($name:expr, $desc:expr, $schema:expr, $handler:expr) => {{
+        struct Handler<F>(F);
+
+        impl<F, Fut> $crate::types::mcp::ToolHandler for Handler<F>
+        where
+            F: Fn(serde_json::Value, $crate::types::mcp::ToolContext) -> Fut + Send + Sync,
+            Fut: std::future::Future<Output = anyhow::Result<$crate::types::mcp::ToolResult>>
+                + Send
+                + 'static,
+        {
+            fn handle(
+                &self,
+                args: serde_json::Value,
+            ) -> futures::future::BoxFuture<
+                'static,
+                $crate::errors::Result<$crate::types::mcp::ToolResult>,
+            > {
+                self.handle_with_context(args, $crate::types::mcp::ToolContext::default())
+            }
+
+            fn handle_with_context(
+                &self,
+                args: serde_json::Value,
+                context: $crate::types::mcp::ToolContext,
+            ) -> futures::future::BoxFuture<
+                'static,
+                $crate::errors::Result<$crate::types::mcp::ToolResult>,
+            > {
+                use futures::FutureExt;
+                let f = &self.0;
+                let fut = f(args, context);
+                async move { fut.await.map_err(|e| e.into()) }.boxed()
+            }
+        }
+
+        $crate::types::mcp::SdkMcpTool {
+            name: $name.to_string(),
+            description: $desc.to_string(),
+            input_schema: $schema,
+            handler: std::sync::Arc::new(Handler($handler)),
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+    use tokio::sync::Mutex;
+
+    fn recording_context() -> (McpRequestContext, Arc<Mutex<Vec<serde_json::Value>>>) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sink_sent = Arc::clone(&sent);
+        let sink: McpNotificationSink = Arc::new(move |notification| {
+            let sent = Arc::clone(&sink_sent);
+            Box::pin(async move {
+                sent.lock().await.push(notification);
+                Ok(())
+            })
+        });
+        (McpRequestContext::new(sink), sent)
+    }
+
+    fn text_result(text: &str) -> ToolResult {
+        ToolResult {
+            content: vec![ToolResultContent::Text {
+                text: text.to_string(),
+            }],
+            is_error: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_context_reports_progress_with_token() {
+        let server = create_sdk_mcp_server(
+            "builder",
+            "1.0.0",
+            vec![crate::tool_with_context!(
+                "build",
+                "Run a build",
+                json!({"type": "object"}),
+                |_args: serde_json::Value, ctx: ToolContext| async move {
+                    ctx.report_progress(1.0, Some(2.0), Some("halfway")).await?;
+                    ctx.log(McpLogLevel::Info, "compiled").await?;
+                    Ok(text_result("ok"))
+                }
+            )],
+        );
+
+        let (context, sent) = recording_context();
+        let response = server
+            .instance
+            .handle_message_with_context(
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 7,
+                    "method": "tools/call",
+                    "params": {
+                        "name": "build",
+                        "arguments": {},
+                        "_meta": {"progressToken": "tok-1"}
+                    }
+                }),
+                context,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response["result"]["content"][0]["text"], "ok");
+
+        let sent = sent.lock().await;
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0]["method"], "notifications/progress");
+        assert_eq!(sent[0]["params"]["progressToken"], "tok-1");
+        assert_eq!(sent[0]["params"]["progress"], 1.0);
+        assert_eq!(sent[0]["params"]["total"], 2.0);
+        assert_eq!(sent[0]["params"]["message"], "halfway");
+        assert_eq!(sent[1]["method"], "notifications/message");
+        assert_eq!(sent[1]["params"]["level"], "info");
+        assert_eq!(sent[1]["params"]["logger"], "build");
+    }
+
+    #[tokio::test]
+    async fn test_tool_context_skips_progress_without_token() {
+        let (context, sent) = recording_context();
+        let ctx = ToolContext::new("t", None, None, context, CancellationSignal::new());
+
+        ctx.report_progress(1.0, None, None).await.unwrap();
+        assert!(sent.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_notification_signals_in_flight_call() {
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let started_tx = std::sync::Mutex::new(Some(started_tx));
+        let server = Arc::new(create_sdk_mcp_server(
+            "slow",
+            "1.0.0",
+            vec![crate::tool_with_context!(
+                "wait",
+                "Wait until cancelled",
+                json!({"type": "object"}),
+                move |_args: serde_json::Value, ctx: ToolContext| {
+                    let started = started_tx.lock().unwrap().take();
+                    async move {
+                        if let Some(started) = started {
+                            let _ = started.send(());
+                        }
+                        ctx.cancelled().await;
+                        Ok(text_result("cancelled"))
+                    }
+                }
+            )],
+        ));
+
+        let call_server = Arc::clone(&server);
+        let call = tokio::spawn(async move {
+            call_server
+                .instance
+                .handle_message(json!({
+                    "jsonrpc": "2.0",
+                    "id": "call-1",
+                    "method": "tools/call",
+                    "params": {"name": "wait", "arguments": {}}
+                }))
+                .await
+        });
+
+        // The call is registered as in flight before its handler runs
+        started_rx.await.unwrap();
+        let ack = server
+            .instance
+            .handle_message(json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/cancelled",
+                "params": {"requestId": "call-1"}
+            }))
+            .await
+            .unwrap();
+        assert!(ack.is_null());
+
+        let response = tokio::time::timeout(Duration::from_secs(1), call)
+            .await
+            .expect("tool call should finish after cancellation")
+            .unwrap()
+            .unwrap();
+        assert_eq!(response["result"]["content"][0]["text"], "cancelled");
+    }
+
+    #[tokio::test]
+    async fn test_plain_tool_ignores_context() {
+        let server = create_sdk_mcp_server(
+            "plain",
+            "1.0.0",
+            vec![crate::tool!(
+                "echo",
+                "Echo",
+                json!({"type": "object"}),
+                |_args: serde_json::Value| async move { Ok(text_result("echo")) }
+            )],
+        );
+
+        let (context, sent) = recording_context();
+        let response = server
+            .instance
+            .handle_message_with_context(
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "method": "tools/call",
+                    "params": {"name": "echo", "arguments": {}, "_meta": {"progressToken": 1}}
+                }),
+                context,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response["result"]["content"][0]["text"], "echo");
+        assert!(sent.lock().await.is_empty());
+    }
+}
@@ -0,0 +1,857 @@
+//! SDK-side proxy for external stdio MCP servers
+//!
+//! [`McpServerConfig::Stdio`](super::mcp::McpServerConfig::Stdio) servers are spawned by the
+//! CLI, so their traffic never passes through the SDK. A stdio proxy instead launches the
+//! server as a child of the Rust process and exposes it to the CLI as an in-process SDK
+//! server, which lets you filter the tool list, rewrite arguments and observe every call.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use claude_agent_sdk_rs::{
+//!     ClaudeAgentOptions, McpProxyHooks, McpServerConfig, McpServers, McpStdioServerConfig,
+//!     create_stdio_mcp_proxy,
+//! };
+//! use std::collections::HashMap;
+//! use std::sync::Arc;
+//!
+//! let hooks = McpProxyHooks::builder()
+//!     .tool_filter(Arc::new(|tool: &serde_json::Value| tool["name"] != "delete_file"))
+//!     .on_call(Arc::new(|call: &claude_agent_sdk_rs::McpProxyCall| {
+//!         println!("{} took {:?}", call.tool_name, call.duration);
+//!     }))
+//!     .build();
+//!
+//! let proxy = create_stdio_mcp_proxy(
+//!     "filesystem",
+//!     McpStdioServerConfig {
+//!         command: "npx".to_string(),
+//!         args: Some(vec!["-y".to_string(), "@modelcontextprotocol/server-filesystem".to_string()]),
+//!         env: None,
+//!     },
+//!     hooks,
+//! );
+//!
+//! let options = ClaudeAgentOptions::builder()
+//!     .mcp_servers(McpServers::Dict(HashMap::from([(
+//!         "filesystem".to_string(),
+//!         McpServerConfig::Sdk(proxy),
+//!     )])))
+//!     .build();
+//! ```
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use serde_json::json;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{Mutex, oneshot};
+use tracing::{debug, warn};
+use typed_builder::TypedBuilder;
+
+use super::mcp::{McpRequestContext, McpSdkServerConfig, McpStdioServerConfig, SdkMcpServer};
+use crate::errors::{ClaudeError, ProcessError, Result};
+
+/// Predicate deciding whether a tool from `tools/list` is exposed to the CLI
+///
+/// Receives the tool definition as returned by the server (`name`, `description`,
+/// `inputSchema`, ...).
+pub type McpToolFilter = Arc<dyn Fn(&serde_json::Value) -> bool + Send + Sync>;
+
+/// Rewrites tool arguments before they are forwarded to the server
+///
+/// Receives the tool name and the arguments sent by the CLI.
+pub type McpArgumentRewriter =
+    Arc<dyn Fn(&str, serde_json::Value) -> serde_json::Value + Send + Sync>;
+
+/// Observer invoked after every proxied tool call
+pub type McpCallObserver = Arc<dyn Fn(&McpProxyCall) + Send + Sync>;
+
+/// Hooks applied by a stdio MCP proxy
+#[derive(Clone, Default, TypedBuilder)]
+#[builder(doc)]
+pub struct McpProxyHooks {
+    /// Filter applied to `tools/list`; calls to filtered tools are rejected
+    #[builder(default, setter(strip_option))]
+    pub tool_filter: Option<McpToolFilter>,
+    /// Rewrite arguments of `tools/call` requests
+    #[builder(default, setter(strip_option))]
+    pub rewrite_arguments: Option<McpArgumentRewriter>,
+    /// Record every call and its result
+    #[builder(default, setter(strip_option))]
+    pub on_call: Option<McpCallObserver>,
+}
+
+/// A proxied tool call, passed to [`McpProxyHooks::on_call`]
+#[derive(Debug, Clone)]
+pub struct McpProxyCall {
+    /// Name of the proxy (as registered with the CLI)
+    pub server_name: String,
+    /// Tool name
+    pub tool_name: String,
+    /// Arguments as sent by the CLI
+    pub original_arguments: serde_json::Value,
+    /// Arguments forwarded to the server (after rewriting)
+    pub arguments: serde_json::Value,
+    /// JSON-RPC `result` on success, or the `error` object on failure
+    pub result: std::result::Result<serde_json::Value, serde_json::Value>,
+    /// Time spent waiting for the server
+    pub duration: Duration,
+}
+
+/// Create an SDK MCP server that proxies an external stdio MCP server
+///
+/// The server process is started lazily on the first message from the CLI and
+/// killed when the proxy is dropped.
+pub fn create_stdio_mcp_proxy(
+    name: impl Into<String>,
+    config: McpStdioServerConfig,
+    hooks: McpProxyHooks,
+) -> McpSdkServerConfig {
+    let proxy = StdioMcpProxy::new(name, config, hooks);
+    McpSdkServerConfig {
+        name: proxy.name.clone(),
+        instance: Arc::new(proxy),
+    }
+}
+
+/// SDK MCP server that forwards JSON-RPC traffic to a stdio MCP server
+///
+/// If the server exits, it is restarted on the next message and the CLI's
+/// `initialize` handshake is replayed before that message is forwarded.
+pub struct StdioMcpProxy {
+    name: String,
+    config: McpStdioServerConfig,
+    hooks: McpProxyHooks,
+    process: Mutex<Option<ProxyProcess>>,
+    /// Tools currently exposed to the CLI (`None` until the first `tools/list`)
+    exposed_tools: std::sync::Mutex<Option<Vec<String>>>,
+    /// Maps CLI request ids to upstream ids for in-flight requests
+    in_flight: DashMap<String, u64>,
+    /// Notification channels of in-flight requests, by upstream id
+    contexts: Arc<DashMap<u64, InFlightContext>>,
+    /// Channel for notifications not tied to a request (logs, list_changed)
+    fallback_context: Arc<std::sync::Mutex<McpRequestContext>>,
+    /// `params` of the CLI's last successful `initialize`, replayed on restart
+    initialize_params: std::sync::Mutex<Option<serde_json::Value>>,
+    next_id: AtomicU64,
+}
+
+/// Where progress for an in-flight request is sent
+struct InFlightContext {
+    /// `params._meta.progressToken` of the request, if any
+    progress_token: Option<serde_json::Value>,
+    context: McpRequestContext,
+}
+
+struct ProxyProcess {
+    _child: Child,
+    stdin: Arc<Mutex<ChildStdin>>,
+    pending: Arc<DashMap<u64, oneshot::Sender<serde_json::Value>>>,
+    /// Cleared by the stdout reader when the server exits
+    alive: Arc<AtomicBool>,
+}
+
+/// Write one JSON-RPC message as a line
+async fn write_message(stdin: &Mutex<ChildStdin>, message: &serde_json::Value) -> Result<()> {
+    let mut line = serde_json::to_string(message)
+        .map_err(|e| ClaudeError::Transport(format!("Failed to serialize MCP message: {}", e)))?;
+    line.push('\n');
+
+    let mut stdin = stdin.lock().await;
+    stdin.write_all(line.as_bytes()).await?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+impl StdioMcpProxy {
+    /// Create a new proxy (the server is not started until first use)
+    pub fn new(
+        name: impl Into<String>,
+        config: McpStdioServerConfig,
+        hooks: McpProxyHooks,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            config,
+            hooks,
+            process: Mutex::new(None),
+            exposed_tools: std::sync::Mutex::new(None),
+            in_flight: DashMap::new(),
+            contexts: Arc::new(DashMap::new()),
+            fallback_context: Arc::new(std::sync::Mutex::new(McpRequestContext::default())),
+            initialize_params: std::sync::Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Spawn the server process and its stdout reader
+    fn spawn(&self) -> Result<ProxyProcess> {
+        let mut cmd = Command::new(&self.config.command);
+        cmd.args(self.config.args.iter().flatten())
+            .envs(self.config.env.iter().flatten())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = cmd.spawn().map_err(|e| {
+            ClaudeError::Process(ProcessError::new(
+                format!(
+                    "Failed to spawn MCP server '{}' ({}): {}",
+                    self.name, self.config.command, e
+                ),
+                None,
+                None,
+            ))
+        })?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| ClaudeError::Transport("Failed to get MCP server stdin".to_string()))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| ClaudeError::Transport("Failed to get MCP server stdout".to_string()))?;
+
+        if let Some(stderr) = child.stderr.take() {
+            let name = self.name.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    debug!("MCP server '{}' stderr: {}", name, line);
+                }
+            });
+        }
+
+        let stdin = Arc::new(Mutex::new(stdin));
+        let pending: Arc<DashMap<u64, oneshot::Sender<serde_json::Value>>> =
+            Arc::new(DashMap::new());
+        let alive = Arc::new(AtomicBool::new(true));
+        let reader_stdin = Arc::clone(&stdin);
+        let reader_pending = Arc::clone(&pending);
+        let reader_alive = Arc::clone(&alive);
+        let contexts = Arc::clone(&self.contexts);
+        let fallback_context = Arc::clone(&self.fallback_context);
+        let name = self.name.clone();
+
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let trimmed = line.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                let message: serde_json::Value = match serde_json::from_str(trimmed) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        warn!("MCP server '{}' wrote invalid JSON: {}", name, e);
+                        continue;
+                    }
+                };
+
+                let id = message.get("id").filter(|id| !id.is_null());
+                let method = message.get("method").and_then(|m| m.as_str());
+                let is_response = message.get("result").is_some() || message.get("error").is_some();
+
+                match (id, method) {
+                    (Some(id), _) if is_response => {
+                        if let Some((_, tx)) = id.as_u64().and_then(|id| reader_pending.remove(&id))
+                        {
+                            let _ = tx.send(message);
+                        }
+                    }
+                    (Some(id), Some(method)) => {
+                        // Server-initiated request (ping, roots/list, sampling/createMessage):
+                        // the CLI cannot answer these, so reply here rather than leave the
+                        // server waiting
+                        let reply = if method == "ping" {
+                            json!({"jsonrpc": "2.0", "id": id, "result": {}})
+                        } else {
+                            json!({
+                                "jsonrpc": "2.0",
+                                "id": id,
+                                "error": {
+                                    "code": -32601,
+                                    "message": format!("Method not supported by the SDK proxy: {}", method)
+                                }
+                            })
+                        };
+                        if let Err(e) = write_message(&reader_stdin, &reply).await {
+                            warn!("Failed to answer MCP server '{}' request: {}", name, e);
+                        }
+                    }
+                    (None, Some(method)) => {
+                        // Server-initiated notification: progress goes to the request that
+                        // carries its token, everything else to the latest context
+                        let params = message.get("params").cloned().unwrap_or(json!({}));
+                        let context = params
+                            .get("progressToken")
+                            .and_then(|token| {
+                                contexts
+                                    .iter()
+                                    .find(|entry| entry.progress_token.as_ref() == Some(token))
+                                    .map(|entry| entry.context.clone())
+                            })
+                            .or_else(|| fallback_context.lock().ok().map(|c| c.clone()))
+                            .unwrap_or_default();
+                        if let Err(e) = context.notify(method, params).await {
+                            warn!("Failed to forward MCP notification from '{}': {}", name, e);
+                        }
+                    }
+                    _ => {
+                        debug!(
+                            "Ignoring unsupported message from MCP server '{}': {}",
+                            name, trimmed
+                        );
+                    }
+                }
+            }
+
+            // Server exited: restart on the next message and fail all pending requests
+            // by dropping their senders
+            reader_alive.store(false, Ordering::SeqCst);
+            reader_pending.clear();
+        });
+
+        Ok(ProxyProcess {
+            _child: child,
+            stdin,
+            pending,
+            alive,
+        })
+    }
+
+    /// Start the server, replaying the handshake unless `message` is itself an
+    /// `initialize` request
+    async fn start(&self, message: &serde_json::Value) -> Result<ProxyProcess> {
+        let process = self.spawn()?;
+
+        let params = self.initialize_params.lock().ok().and_then(|p| p.clone());
+        let Some(params) = params.filter(|_| message["method"] != "initialize") else {
+            return Ok(process);
+        };
+
+        debug!(
+            "Replaying initialize for restarted MCP server '{}'",
+            self.name
+        );
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        process.pending.insert(id, tx);
+        write_message(
+            &process.stdin,
+            &json!({"jsonrpc": "2.0", "id": id, "method": "initialize", "params": params}),
+        )
+        .await?;
+        let response = rx.await.map_err(|_| {
+            ClaudeError::Transport(format!(
+                "MCP server '{}' exited during initialize",
+                self.name
+            ))
+        })?;
+        if let Some(error) = response.get("error") {
+            return Err(ClaudeError::Transport(format!(
+                "MCP server '{}' rejected initialize on restart: {}",
+                self.name, error
+            )));
+        }
+        write_message(
+            &process.stdin,
+            &json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        )
+        .await?;
+
+        Ok(process)
+    }
+
+    /// Write a message to the server, (re)starting it if necessary
+    ///
+    /// Returns a receiver for the response when `upstream_id` is given.
+    async fn send(
+        &self,
+        message: &serde_json::Value,
+        upstream_id: Option<u64>,
+    ) -> Result<Option<oneshot::Receiver<serde_json::Value>>> {
+        let mut guard = self.process.lock().await;
+        if !guard
+            .as_ref()
+            .is_some_and(|p| p.alive.load(Ordering::SeqCst))
+        {
+            *guard = None;
+            *guard = Some(self.start(message).await?);
+        }
+        let process = guard.as_mut().expect("process was just started");
+
+        let rx = upstream_id.map(|id| {
+            let (tx, rx) = oneshot::channel();
+            process.pending.insert(id, tx);
+            rx
+        });
+
+        if let Err(e) = write_message(&process.stdin, message).await {
+            // Broken pipe: the server is gone, restart on next message
+            *guard = None;
+            return Err(ClaudeError::Transport(format!(
+                "Failed to write to MCP server '{}': {}",
+                self.name, e
+            )));
+        }
+
+        Ok(rx)
+    }
+
+    /// Forward a request and wait for its response, restoring the CLI's id
+    ///
+    /// Progress notifications carrying the request's token are sent to `context`.
+    async fn forward_request(
+        &self,
+        mut message: serde_json::Value,
+        context: &McpRequestContext,
+    ) -> Result<serde_json::Value> {
+        let original_id = message.get("id").cloned().unwrap_or(json!(null));
+        let upstream_id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        message["id"] = json!(upstream_id);
+
+        let in_flight_key = original_id.to_string();
+        self.in_flight.insert(in_flight_key.clone(), upstream_id);
+        self.contexts.insert(
+            upstream_id,
+            InFlightContext {
+                progress_token: message["params"]["_meta"].get("progressToken").cloned(),
+                context: context.clone(),
+            },
+        );
+
+        let result = match self.send(&message, Some(upstream_id)).await {
+            Ok(Some(rx)) => rx.await.map_err(|_| {
+                ClaudeError::Transport(format!(
+                    "MCP server '{}' exited before responding",
+                    self.name
+                ))
+            }),
+            Ok(None) => unreachable!("requests always have a response channel"),
+            Err(e) => Err(e),
+        };
+
+        self.in_flight.remove(&in_flight_key);
+        self.contexts.remove(&upstream_id);
+
+        let mut response = result?;
+        response["id"] = original_id;
+        Ok(response)
+    }
+
+    /// Apply the tool filter to a `tools/list` response
+    fn filter_tools(&self, mut response: serde_json::Value) -> serde_json::Value {
+        if let Some(tools) = response
+            .get_mut("result")
+            .and_then(|r| r.get_mut("tools"))
+            .and_then(|t| t.as_array_mut())
+        {
+            if let Some(ref filter) = self.hooks.tool_filter {
+                tools.retain(|tool| filter(tool));
+            }
+            let names = tools
+                .iter()
+                .filter_map(|t| t.get("name").and_then(|n| n.as_str()))
+                .map(String::from)
+                .collect();
+            if let Ok(mut exposed) = self.exposed_tools.lock() {
+                *exposed = Some(names);
+            }
+        }
+        response
+    }
+
+    /// Whether a tool may be called (it must not have been filtered out)
+    fn is_tool_exposed(&self, tool_name: &str) -> bool {
+        if self.hooks.tool_filter.is_none() {
+            return true;
+        }
+        match self.exposed_tools.lock().ok().and_then(|e| e.clone()) {
+            Some(exposed) => exposed.iter().any(|t| t == tool_name),
+            // tools/list not seen yet: filter by name only
+            None => self
+                .hooks
+                .tool_filter
+                .as_ref()
+                .is_some_and(|filter| filter(&json!({ "name": tool_name }))),
+        }
+    }
+
+    async fn handle_tool_call(
+        &self,
+        mut message: serde_json::Value,
+        context: &McpRequestContext,
+    ) -> Result<serde_json::Value> {
+        let tool_name = message["params"]["name"]
+            .as_str()
+            .ok_or_else(|| ClaudeError::Transport("Missing tool name".to_string()))?
+            .to_string();
+
+        if !self.is_tool_exposed(&tool_name) {
+            return Ok(json!({
+                "jsonrpc": "2.0",
+                "id": message.get("id").cloned().unwrap_or(json!(null)),
+                "error": {
+                    "code": -32602,
+                    "message": format!("Tool not available through proxy: {}", tool_name)
+                }
+            }));
+        }
+
+        let original_arguments = message["params"]
+            .get("arguments")
+            .cloned()
+            .unwrap_or(json!({}));
+        let arguments = match self.hooks.rewrite_arguments {
+            Some(ref rewrite) => rewrite(&tool_name, original_arguments.clone()),
+            None => original_arguments.clone(),
+        };
+        message["params"]["arguments"] = arguments.clone();
+
+        let started = Instant::now();
+        let response = self.forward_request(message, context).await?;
+
+        if let Some(ref on_call) = self.hooks.on_call {
+            let result = match response.get("error") {
+                Some(error) => Err(error.clone()),
+                None => Ok(response.get("result").cloned().unwrap_or(json!(null))),
+            };
+            on_call(&McpProxyCall {
+                server_name: self.name.clone(),
+                tool_name,
+                original_arguments,
+                arguments,
+                result,
+                duration: started.elapsed(),
+            });
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl SdkMcpServer for StdioMcpProxy {
+    async fn handle_message(&self, message: serde_json::Value) -> Result<serde_json::Value> {
+        self.handle_message_with_context(message, McpRequestContext::default())
+            .await
+    }
+
+    async fn handle_message_with_context(
+        &self,
+        mut message: serde_json::Value,
+        context: McpRequestContext,
+    ) -> Result<serde_json::Value> {
+        if let Ok(mut fallback) = self.fallback_context.lock() {
+            *fallback = context.clone();
+        }
+
+        let method = message["method"]
+            .as_str()
+            .ok_or_else(|| ClaudeError::Transport("Missing method".to_string()))?
+            .to_string();
+
+        // Notifications are forwarded without waiting for a response
+        if message.get("id").is_none() {
+            if method == "notifications/cancelled"
+                && let Some(upstream_id) = message["params"]
+                    .get("requestId")
+                    .and_then(|id| self.in_flight.get(&id.to_string()).map(|e| *e.value()))
+            {
+                message["params"]["requestId"] = json!(upstream_id);
+            }
+            self.send(&message, None).await?;
+            return Ok(json!(null));
+        }
+
+        match method.as_str() {
+            "initialize" => {
+                let params = message.get("params").cloned();
+                let response = self.forward_request(message, &context).await?;
+                if response.get("result").is_some()
+                    && let Ok(mut cached) = self.initialize_params.lock()
+                {
+                    *cached = Some(params.unwrap_or(json!({})));
+                }
+                Ok(response)
+            }
+            "tools/list" => {
+                let response = self.forward_request(message, &context).await?;
+                Ok(self.filter_tools(response))
+            }
+            "tools/call" => self.handle_tool_call(message, &context).await,
+            _ => self.forward_request(message, &context).await,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// A canned stdio MCP server: answers initialize (id 1), tools/list (id 2)
+    /// and tools/call (id 3) in order, echoing the call arguments back.
+    fn fake_server() -> McpStdioServerConfig {
+        let script = r#"
+read line
+echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2024-11-05","capabilities":{"tools":{}},"serverInfo":{"name":"fake","version":"0.1"}}}'
+read line
+read line
+echo '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"read_file","inputSchema":{}},{"name":"delete_file","inputSchema":{}}]}}'
+read line
+echo '{"jsonrpc":"2.0","method":"notifications/progress","params":{"progressToken":"p","progress":1}}'
+echo "{\"jsonrpc\":\"2.0\",\"id\":3,\"result\":{\"content\":[{\"type\":\"text\",\"text\":$(echo "$line" | sed 's/.*"arguments":\({[^}]*}\).*/\1/' | sed 's/"/\\"/g' | sed 's/^/"/;s/$/"/')}]}}"
+read line
+"#;
+        McpStdioServerConfig {
+            command: "sh".to_string(),
+            args: Some(vec!["-c".to_string(), script.to_string()]),
+            env: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_proxy_filters_rewrites_and_records() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&calls);
+        let hooks = McpProxyHooks::builder()
+            .tool_filter(Arc::new(|tool: &serde_json::Value| {
+                tool["name"] != "delete_file"
+            }))
+            .rewrite_arguments(Arc::new(|_name: &str, mut args: serde_json::Value| {
+                args["path"] = json!("/sandbox/a.txt");
+                args
+            }))
+            .on_call(Arc::new(move |call: &McpProxyCall| {
+                recorded.lock().unwrap().push(call.clone());
+            }))
+            .build();
+        let proxy = StdioMcpProxy::new("files", fake_server(), hooks);
+
+        let init = proxy
+            .handle_message(
+                json!({"jsonrpc": "2.0", "id": "init-a", "method": "initialize", "params": {}}),
+            )
+            .await
+            .unwrap();
+        assert_eq!(init["id"], "init-a");
+        assert_eq!(init["result"]["serverInfo"]["name"], "fake");
+
+        let ack = proxy
+            .handle_message(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .await
+            .unwrap();
+        assert!(ack.is_null());
+
+        let list = proxy
+            .handle_message(json!({"jsonrpc": "2.0", "id": 10, "method": "tools/list"}))
+            .await
+            .unwrap();
+        assert_eq!(list["id"], 10);
+        let tools = list["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0]["name"], "read_file");
+
+        let denied = proxy
+            .handle_message(json!({
+                "jsonrpc": "2.0", "id": 11, "method": "tools/call",
+                "params": {"name": "delete_file", "arguments": {}}
+            }))
+            .await
+            .unwrap();
+        assert_eq!(denied["id"], 11);
+        assert!(
+            denied["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("delete_file")
+        );
+
+        let result = proxy
+            .handle_message(json!({
+                "jsonrpc": "2.0", "id": 12, "method": "tools/call",
+                "params": {"name": "read_file", "arguments": {"path": "/etc/passwd"}}
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result["id"], 12);
+        let echoed = result["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(echoed.contains("/sandbox/a.txt"), "got {}", echoed);
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].tool_name, "read_file");
+        assert_eq!(calls[0].original_arguments["path"], "/etc/passwd");
+        assert_eq!(calls[0].arguments["path"], "/sandbox/a.txt");
+        assert!(calls[0].result.is_ok());
+    }
+
+    /// Extracts the numeric `id` of the request in `$line`
+    const EXTRACT_ID: &str = r#"id=$(echo "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')"#;
+
+    fn sh_server(script: String) -> McpStdioServerConfig {
+        McpStdioServerConfig {
+            command: "sh".to_string(),
+            args: Some(vec!["-c".to_string(), script]),
+            env: None,
+        }
+    }
+
+    fn recording_context() -> (
+        McpRequestContext,
+        Arc<std::sync::Mutex<Vec<serde_json::Value>>>,
+    ) {
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&received);
+        let context = McpRequestContext::new(Arc::new(move |notification| {
+            sink.lock().unwrap().push(notification);
+            Box::pin(async { Ok(()) })
+        }));
+        (context, received)
+    }
+
+    #[tokio::test]
+    async fn test_proxy_replays_initialize_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("crashed");
+        // Rejects anything before initialize; the first instance exits on its first request
+        let script = format!(
+            r#"
+read line
+case "$line" in *'"method":"initialize"'*) ;; *) exit 1;; esac
+{EXTRACT_ID}
+echo "{{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{{\"serverInfo\":{{\"name\":\"fake\"}}}}}}"
+read line
+case "$line" in *'notifications/initialized'*) ;; *) exit 1;; esac
+read line
+[ -e '{marker}' ] || {{ touch '{marker}'; exit 0; }}
+{EXTRACT_ID}
+echo "{{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{{\"tools\":[]}}}}"
+read line
+"#,
+            marker = marker.display()
+        );
+        let proxy = StdioMcpProxy::new("flaky", sh_server(script), McpProxyHooks::default());
+
+        proxy
+            .handle_message(json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"protocolVersion": "2024-11-05"}}))
+            .await
+            .unwrap();
+        proxy
+            .handle_message(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .await
+            .unwrap();
+
+        let crashed = proxy
+            .handle_message(json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}))
+            .await;
+        assert!(crashed.is_err());
+
+        let list = proxy
+            .handle_message(json!({"jsonrpc": "2.0", "id": 3, "method": "tools/list"}))
+            .await
+            .unwrap();
+        assert_eq!(list["id"], 3);
+        assert_eq!(list["result"]["tools"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_answers_server_initiated_requests() {
+        // Asks for roots before answering tools/list and lists the reply's error code
+        let script = format!(
+            r#"
+read line
+{EXTRACT_ID}
+echo '{{"jsonrpc":"2.0","id":"srv-1","method":"roots/list"}}'
+read reply
+code=$(echo "$reply" | sed 's/.*"code":\(-[0-9]*\).*/\1/')
+echo "{{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{{\"tools\":[{{\"name\":\"code$code\"}}]}}}}"
+read line
+"#
+        );
+        let proxy = StdioMcpProxy::new("roots", sh_server(script), McpProxyHooks::default());
+
+        let list = proxy
+            .handle_message(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}))
+            .await
+            .unwrap();
+        assert_eq!(list["result"]["tools"][0]["name"], "code-32601");
+    }
+
+    #[tokio::test]
+    async fn test_proxy_routes_progress_to_its_request() {
+        // Reads two concurrent calls, reports progress for each in reverse order, then
+        // answers both
+        let script = r#"
+read first
+read second
+for line in "$second" "$first"; do
+  token=$(echo "$line" | sed 's/.*"progressToken":"\([a-z]*\)".*/\1/')
+  echo "{\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\",\"params\":{\"progressToken\":\"$token\",\"progress\":1}}"
+done
+for line in "$first" "$second"; do
+  id=$(echo "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+  echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"content\":[]}}"
+done
+read line
+"#;
+        let proxy = StdioMcpProxy::new(
+            "progress",
+            sh_server(script.to_string()),
+            McpProxyHooks::default(),
+        );
+        let call = |id: u64, token: &str| {
+            json!({
+                "jsonrpc": "2.0", "id": id, "method": "tools/call",
+                "params": {"name": "work", "arguments": {}, "_meta": {"progressToken": token}}
+            })
+        };
+        let (context_a, received_a) = recording_context();
+        let (context_b, received_b) = recording_context();
+
+        let (a, b) = tokio::join!(
+            proxy.handle_message_with_context(call(1, "a"), context_a),
+            proxy.handle_message_with_context(call(2, "b"), context_b),
+        );
+        assert_eq!(a.unwrap()["id"], 1);
+        assert_eq!(b.unwrap()["id"], 2);
+
+        let tokens = |received: &std::sync::Mutex<Vec<serde_json::Value>>| {
+            received
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|n| n["params"]["progressToken"].clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tokens(&received_a), [json!("a")]);
+        assert_eq!(tokens(&received_b), [json!("b")]);
+    }
+
+    #[tokio::test]
+    async fn test_proxy_reports_spawn_failure() {
+        let proxy = StdioMcpProxy::new(
+            "missing",
+            McpStdioServerConfig {
+                command: "/nonexistent/mcp-server".to_string(),
+                args: None,
+                env: None,
+            },
+            McpProxyHooks::default(),
+        );
+
+        let err = proxy
+            .handle_message(json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ClaudeError::Process(_)));
+    }
+}
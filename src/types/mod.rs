@@ -1,9 +1,14 @@
 //! Type definitions for the Claude Agent SDK
 
+pub mod agents;
 pub mod config;
 pub mod efficiency;
 pub mod hooks;
+pub mod invocation;
 pub mod mcp;
+pub mod mcp_proxy;
 pub mod messages;
 pub mod permissions;
 pub mod plugin;
+pub mod process;
+pub mod validation;
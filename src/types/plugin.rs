@@ -5,7 +5,12 @@
 //! loading plugins from local paths.
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::agents::{NameList, split_frontmatter};
+use super::config::AgentDefinition;
+use crate::errors::PluginError;
 
 /// Plugin configuration for extending Claude Code functionality
 ///
@@ -30,7 +35,9 @@ pub enum SdkPluginConfig {
     /// Local filesystem plugin
     ///
     /// Loads a plugin from a local directory path. The path should point to
-    /// a valid Claude Code plugin directory structure.
+    /// a valid Claude Code plugin directory structure. A leading `~` expands to
+    /// the home directory and relative paths are resolved against the session's
+    /// `cwd`, as the CLI resolves `--plugin-dir`.
     ///
     /// # Example
     ///
@@ -79,6 +86,449 @@ impl SdkPluginConfig {
             SdkPluginConfig::Local { path } => Some(path),
         }
     }
+
+    /// Load and validate the plugin
+    ///
+    /// A leading `~` expands to the home directory; relative paths are taken from the
+    /// current directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use claude_agent_sdk_rs::SdkPluginConfig;
+    ///
+    /// let plugin = SdkPluginConfig::local("./fixtures/test-plugin").load().unwrap();
+    /// assert_eq!(plugin.slash_commands(), vec!["/test-plugin:test-cmd"]);
+    /// ```
+    pub fn load(&self) -> Result<Plugin, PluginError> {
+        Plugin::load(self.resolved_path(None, home_dir().as_deref()))
+    }
+
+    /// Plugin directory as the CLI resolves `--plugin-dir`
+    ///
+    /// A leading `~` expands to `home` and relative paths are taken from `cwd`, the
+    /// directory the CLI runs in.
+    fn resolved_path(&self, cwd: Option<&Path>, home: Option<&Path>) -> PathBuf {
+        let SdkPluginConfig::Local { path } = self;
+        let expanded = match (path.strip_prefix("~"), home) {
+            (Ok(rest), Some(home)) => home.join(rest),
+            _ => path.clone(),
+        };
+        match cwd {
+            Some(cwd) if expanded.is_relative() => cwd.join(expanded),
+            _ => expanded,
+        }
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// Load every configured plugin, failing on the first invalid one
+///
+/// Paths are resolved against `cwd` (the session's working directory) the way the CLI
+/// resolves them, and the directories are read on the blocking thread pool.
+pub(crate) async fn load_all(
+    configs: &[SdkPluginConfig],
+    cwd: Option<&Path>,
+) -> Result<Vec<Plugin>, PluginError> {
+    if configs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let home = home_dir();
+    let roots: Vec<PathBuf> = configs
+        .iter()
+        .map(|config| config.resolved_path(cwd, home.as_deref()))
+        .collect();
+    match tokio::task::spawn_blocking(move || roots.iter().map(Plugin::load).collect()).await {
+        Ok(plugins) => plugins,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+/// Plugin manifest from `.claude-plugin/plugin.json`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginManifest {
+    /// Plugin name (kebab-case, used to namespace its commands)
+    pub name: String,
+    /// Plugin version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Plugin description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Plugin author
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<PluginAuthor>,
+    /// Homepage URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    /// Repository URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// License identifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// Search keywords
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    /// Additional command files or directories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commands: Option<PathList>,
+    /// Additional agent files or directories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agents: Option<PathList>,
+    /// Hooks configuration file or inline configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<PathOrInline>,
+    /// MCP server configuration file or inline configuration
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mcp_servers: Option<PathOrInline>,
+}
+
+/// Plugin author information
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PluginAuthor {
+    /// Author name
+    pub name: String,
+    /// Author email
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Author URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// One or more paths relative to the plugin root
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum PathList {
+    /// A single path
+    Single(String),
+    /// Several paths
+    Multiple(Vec<String>),
+}
+
+impl PathList {
+    fn paths(&self) -> Vec<&str> {
+        match self {
+            PathList::Single(path) => vec![path.as_str()],
+            PathList::Multiple(paths) => paths.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// A path to a JSON file relative to the plugin root, or the configuration inline
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum PathOrInline {
+    /// Path to a JSON file
+    Path(String),
+    /// Inline configuration
+    Inline(serde_json::Value),
+}
+
+/// A slash command contributed by a plugin
+#[derive(Debug, Clone)]
+pub struct PluginCommand {
+    /// Command name (file stem)
+    pub name: String,
+    /// Command file
+    pub path: PathBuf,
+    /// Description from the frontmatter, if any
+    pub description: Option<String>,
+    /// Tools the command may use
+    pub allowed_tools: Option<Vec<String>>,
+}
+
+/// A loaded and validated plugin
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    /// Plugin root directory
+    pub root: PathBuf,
+    /// Parsed manifest
+    pub manifest: PluginManifest,
+    /// Slash commands
+    pub commands: Vec<PluginCommand>,
+    /// Agents by name
+    pub agents: HashMap<String, AgentDefinition>,
+    /// Hooks configuration, keyed by event name
+    pub hooks: Option<serde_json::Map<String, serde_json::Value>>,
+    /// MCP server configurations by name
+    pub mcp_servers: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct CommandFrontmatter {
+    description: Option<String>,
+    allowed_tools: Option<NameList>,
+}
+
+impl Plugin {
+    /// Load a plugin from its root directory
+    ///
+    /// Reads `.claude-plugin/plugin.json` and the default component locations
+    /// (`commands/`, `agents/`, `hooks/hooks.json`, `.mcp.json`) plus any
+    /// additional paths declared in the manifest.
+    pub fn load(root: impl AsRef<Path>) -> Result<Self, PluginError> {
+        let root = root.as_ref();
+        if !root.is_dir() {
+            return Err(PluginError::DirectoryNotFound {
+                path: root.to_path_buf(),
+            });
+        }
+
+        let manifest_path = root.join(".claude-plugin").join("plugin.json");
+        if !manifest_path.is_file() {
+            return Err(PluginError::ManifestNotFound {
+                path: manifest_path,
+            });
+        }
+        let invalid_manifest = |message: String| PluginError::InvalidManifest {
+            path: manifest_path.clone(),
+            message,
+        };
+        let content =
+            std::fs::read_to_string(&manifest_path).map_err(|e| invalid_manifest(e.to_string()))?;
+        let manifest: PluginManifest =
+            serde_json::from_str(&content).map_err(|e| invalid_manifest(e.to_string()))?;
+        if !is_valid_plugin_name(&manifest.name) {
+            return Err(invalid_manifest(format!(
+                "plugin name '{}' must be kebab-case (lowercase letters, digits and '-')",
+                manifest.name
+            )));
+        }
+
+        let mut plugin = Plugin {
+            root: root.to_path_buf(),
+            manifest,
+            commands: Vec::new(),
+            agents: HashMap::new(),
+            hooks: None,
+            mcp_servers: HashMap::new(),
+        };
+
+        for path in plugin.component_files("commands")? {
+            plugin.load_command(path)?;
+        }
+        for path in plugin.component_files("agents")? {
+            let (name, agent) = crate::types::agents::load_agent_file(&path).map_err(|e| {
+                PluginError::InvalidComponent {
+                    component: "agents",
+                    path: path.clone(),
+                    message: e.to_string(),
+                }
+            })?;
+            plugin.agents.insert(name, agent);
+        }
+        plugin.load_hooks()?;
+        plugin.load_mcp_servers()?;
+
+        Ok(plugin)
+    }
+
+    /// Plugin name from the manifest
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    /// Slash commands contributed by this plugin, namespaced as `/plugin:command`
+    pub fn slash_commands(&self) -> Vec<String> {
+        self.commands
+            .iter()
+            .map(|c| format!("/{}:{}", self.manifest.name, c.name))
+            .collect()
+    }
+
+    /// Markdown files for a component: the default directory plus declared paths
+    fn component_files(&self, component: &'static str) -> Result<Vec<PathBuf>, PluginError> {
+        let mut entries = vec![self.root.join(component)];
+        let declared = match component {
+            "commands" => self.manifest.commands.as_ref(),
+            _ => self.manifest.agents.as_ref(),
+        };
+        for relative in declared.map(PathList::paths).unwrap_or_default() {
+            let path = self.root.join(relative);
+            if !path.exists() {
+                return Err(PluginError::MissingComponent {
+                    plugin: self.manifest.name.clone(),
+                    component,
+                    path,
+                });
+            }
+            entries.push(path);
+        }
+
+        let mut files = Vec::new();
+        for entry in entries {
+            if entry.is_file() {
+                files.push(entry);
+            } else if entry.is_dir() {
+                let mut found: Vec<PathBuf> = std::fs::read_dir(&entry)
+                    .map_err(|e| PluginError::InvalidComponent {
+                        component,
+                        path: entry.clone(),
+                        message: e.to_string(),
+                    })?
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "md"))
+                    .collect();
+                found.sort();
+                files.extend(found);
+            }
+        }
+        files.dedup();
+        Ok(files)
+    }
+
+    fn load_command(&mut self, path: PathBuf) -> Result<(), PluginError> {
+        let invalid = |message: String| PluginError::InvalidComponent {
+            component: "commands",
+            path: path.clone(),
+            message,
+        };
+        let content = std::fs::read_to_string(&path).map_err(|e| invalid(e.to_string()))?;
+        let frontmatter: CommandFrontmatter = match split_frontmatter(&content) {
+            Some((yaml, _)) if !yaml.trim().is_empty() => {
+                serde_norway::from_str(yaml).map_err(|e| invalid(e.to_string()))?
+            }
+            _ => CommandFrontmatter::default(),
+        };
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.commands.push(PluginCommand {
+            name,
+            path,
+            description: frontmatter.description,
+            allowed_tools: frontmatter.allowed_tools.map(Into::into),
+        });
+        Ok(())
+    }
+
+    /// Resolve a JSON component: declared path, inline value, or the default file
+    fn json_component(
+        &self,
+        component: &'static str,
+        declared: Option<&PathOrInline>,
+        default: &str,
+    ) -> Result<Option<(PathBuf, serde_json::Value)>, PluginError> {
+        let path = match declared {
+            Some(PathOrInline::Inline(value)) => {
+                return Ok(Some((
+                    self.root.join(".claude-plugin/plugin.json"),
+                    value.clone(),
+                )));
+            }
+            Some(PathOrInline::Path(relative)) => {
+                let path = self.root.join(relative);
+                if !path.is_file() {
+                    return Err(PluginError::MissingComponent {
+                        plugin: self.manifest.name.clone(),
+                        component,
+                        path,
+                    });
+                }
+                path
+            }
+            None => {
+                let path = self.root.join(default);
+                if !path.is_file() {
+                    return Ok(None);
+                }
+                path
+            }
+        };
+
+        let content =
+            std::fs::read_to_string(&path).map_err(|e| PluginError::InvalidComponent {
+                component,
+                path: path.clone(),
+                message: e.to_string(),
+            })?;
+        let value = serde_json::from_str(&content).map_err(|e| PluginError::InvalidComponent {
+            component,
+            path: path.clone(),
+            message: e.to_string(),
+        })?;
+        Ok(Some((path, value)))
+    }
+
+    fn load_hooks(&mut self) -> Result<(), PluginError> {
+        let Some((path, value)) =
+            self.json_component("hooks", self.manifest.hooks.as_ref(), "hooks/hooks.json")?
+        else {
+            return Ok(());
+        };
+        let invalid = |message: &str| PluginError::InvalidComponent {
+            component: "hooks",
+            path: path.clone(),
+            message: message.to_string(),
+        };
+
+        // hooks.json wraps the events in a top-level "hooks" key
+        let events = value.get("hooks").unwrap_or(&value);
+        let events = events
+            .as_object()
+            .ok_or_else(|| invalid("expected an object keyed by hook event"))?;
+        for (event, matchers) in events {
+            if !matchers.is_array() {
+                return Err(invalid(&format!(
+                    "matchers for '{}' must be an array",
+                    event
+                )));
+            }
+        }
+        self.hooks = Some(events.clone());
+        Ok(())
+    }
+
+    fn load_mcp_servers(&mut self) -> Result<(), PluginError> {
+        let Some((path, value)) = self.json_component(
+            "mcpServers",
+            self.manifest.mcp_servers.as_ref(),
+            ".mcp.json",
+        )?
+        else {
+            return Ok(());
+        };
+        let invalid = |message: String| PluginError::InvalidComponent {
+            component: "mcpServers",
+            path: path.clone(),
+            message,
+        };
+
+        let servers = value.get("mcpServers").unwrap_or(&value);
+        let servers = servers
+            .as_object()
+            .ok_or_else(|| invalid("expected an object keyed by server name".to_string()))?;
+        for (name, config) in servers {
+            let has_target = config.get("command").is_some() || config.get("url").is_some();
+            if !has_target {
+                return Err(invalid(format!(
+                    "server '{}' needs either a 'command' or a 'url'",
+                    name
+                )));
+            }
+            self.mcp_servers.insert(name.clone(), config.clone());
+        }
+        Ok(())
+    }
+}
+
+fn is_valid_plugin_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
 }
 
 #[cfg(test)]
@@ -141,6 +591,134 @@ mod tests {
         );
     }
 
+    fn fixture_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/test-plugin")
+    }
+
+    fn temp_plugin(manifest: &str) -> tempfile::TempDir {
+        let root = tempfile::tempdir().unwrap();
+        write_manifest(root.path(), manifest);
+        root
+    }
+
+    fn write_manifest(root: &Path, manifest: &str) {
+        std::fs::create_dir_all(root.join(".claude-plugin")).unwrap();
+        std::fs::write(root.join(".claude-plugin/plugin.json"), manifest).unwrap();
+    }
+
+    #[test]
+    fn test_load_fixture_plugin() {
+        let plugin = SdkPluginConfig::local(fixture_path()).load().unwrap();
+        assert_eq!(plugin.name(), "test-plugin");
+        assert_eq!(plugin.manifest.version.as_deref(), Some("1.0.0"));
+        assert_eq!(
+            plugin.manifest.author.as_ref().unwrap().name,
+            "Claude Agent SDK Test Suite"
+        );
+        assert_eq!(plugin.commands.len(), 1);
+        assert_eq!(plugin.commands[0].name, "test-cmd");
+        assert_eq!(plugin.slash_commands(), vec!["/test-plugin:test-cmd"]);
+        assert!(plugin.agents.is_empty());
+        assert!(plugin.hooks.is_none());
+        assert!(plugin.mcp_servers.is_empty());
+    }
+
+    #[test]
+    fn test_load_plugin_errors() {
+        let missing = Plugin::load("/nonexistent/plugin").unwrap_err();
+        assert!(matches!(missing, PluginError::DirectoryNotFound { .. }));
+
+        let no_manifest = Plugin::load(fixture_path().join("commands")).unwrap_err();
+        assert!(matches!(no_manifest, PluginError::ManifestNotFound { .. }));
+
+        let root = temp_plugin(r#"{"name": "Bad Name"}"#);
+        let bad_name = Plugin::load(root.path()).unwrap_err();
+        assert!(matches!(bad_name, PluginError::InvalidManifest { .. }));
+
+        let root = temp_plugin(r#"{"name": "p", "commands": "./extra"}"#);
+        let missing_component = Plugin::load(root.path()).unwrap_err();
+        assert!(matches!(
+            missing_component,
+            PluginError::MissingComponent {
+                component: "commands",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_load_plugin_components() {
+        let temp = temp_plugin(r#"{"name": "tools", "mcpServers": {"db": {"command": "db-mcp"}}}"#);
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("agents")).unwrap();
+        std::fs::write(
+            root.join("agents/helper.md"),
+            "---\ndescription: Helps\n---\nHelp.\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(root.join("hooks")).unwrap();
+        std::fs::write(
+            root.join("hooks/hooks.json"),
+            r#"{"hooks": {"PreToolUse": [{"matcher": "Bash", "hooks": []}]}}"#,
+        )
+        .unwrap();
+
+        let plugin = Plugin::load(root);
+        std::fs::write(root.join("hooks/hooks.json"), r#"{"hooks": {"Stop": {}}}"#).unwrap();
+        let bad_hooks = Plugin::load(root);
+
+        let plugin = plugin.unwrap();
+        assert_eq!(plugin.agents["helper"].description, "Helps");
+        assert!(plugin.hooks.unwrap().contains_key("PreToolUse"));
+        assert_eq!(plugin.mcp_servers["db"]["command"], "db-mcp");
+        assert!(matches!(
+            bad_hooks.unwrap_err(),
+            PluginError::InvalidComponent {
+                component: "hooks",
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_load_all_resolves_relative_paths_against_cwd() {
+        let cwd = tempfile::tempdir().unwrap();
+        write_manifest(&cwd.path().join("plugins/local"), r#"{"name": "local"}"#);
+
+        let configs = [SdkPluginConfig::local("plugins/local")];
+        let plugins = load_all(&configs, Some(cwd.path())).await.unwrap();
+        assert_eq!(plugins[0].name(), "local");
+        assert_eq!(plugins[0].root, cwd.path().join("plugins/local"));
+
+        // Without the session cwd the path is looked up in the process cwd
+        assert!(matches!(
+            load_all(&configs, None).await.unwrap_err(),
+            PluginError::DirectoryNotFound { .. }
+        ));
+    }
+
+    #[test]
+    fn test_resolved_path_expands_home() {
+        let home = Path::new("/home/user");
+        let cwd = Path::new("/work");
+        let resolve =
+            |path: &str| SdkPluginConfig::local(path).resolved_path(Some(cwd), Some(home));
+
+        assert_eq!(
+            resolve("~/.claude/plugins/my-plugin"),
+            home.join(".claude/plugins/my-plugin")
+        );
+        assert_eq!(resolve("~"), home);
+        assert_eq!(resolve("./my-plugin"), cwd.join("my-plugin"));
+        assert_eq!(resolve("/opt/plugin"), PathBuf::from("/opt/plugin"));
+        // Only a bare `~` component is expanded
+        assert_eq!(resolve("~other/plugin"), cwd.join("~other/plugin"));
+        assert_eq!(
+            SdkPluginConfig::local("~/p").resolved_path(None, None),
+            PathBuf::from("~/p")
+        );
+    }
+
     #[test]
     fn test_plugin_with_home_directory() {
         let plugin = SdkPluginConfig::local("~/my-plugin");
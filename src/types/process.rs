@@ -0,0 +1,305 @@
+//! CLI process lifecycle configuration and reporting
+
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+
+/// How the CLI process is stopped on disconnect
+///
+/// Shutdown escalates through three stages, each with its own deadline: stdin is
+/// closed and the CLI gets `stdin_grace` to exit on its own, then it is sent `SIGTERM`
+/// and given `term_timeout`, then `SIGKILL` and `kill_timeout`. On Windows, where
+/// there is no `SIGTERM`, the second stage is skipped.
+///
+/// ```
+/// use std::time::Duration;
+/// use claude_agent_sdk_rs::{ClaudeAgentOptions, ShutdownPolicy};
+///
+/// let options = ClaudeAgentOptions::builder()
+///     .shutdown_policy(
+///         ShutdownPolicy::builder()
+///             .stdin_grace(Duration::from_secs(2))
+///             .build(),
+///     )
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TypedBuilder)]
+pub struct ShutdownPolicy {
+    /// Time to wait for the CLI to exit after stdin is closed
+    #[builder(default = Duration::from_secs(5))]
+    pub stdin_grace: Duration,
+    /// Time to wait after `SIGTERM`
+    #[builder(default = Duration::from_secs(5))]
+    pub term_timeout: Duration,
+    /// Time to wait after `SIGKILL`
+    #[builder(default = Duration::from_secs(2))]
+    pub kill_timeout: Duration,
+}
+
+impl Default for ShutdownPolicy {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// Which process group the CLI is started in (Unix only)
+///
+/// The CLI spawns Bash commands, MCP servers and subagents of its own. Starting it in
+/// a new process group or session lets the transport signal all of those descendants
+/// together on disconnect and drop, instead of only the CLI itself. It also detaches
+/// the CLI from the terminal's job control, so Ctrl-C in the parent's terminal no longer
+/// reaches it directly. Ignored on other platforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessIsolation {
+    /// Share the parent's process group (default)
+    #[default]
+    Inherit,
+    /// Start the CLI as the leader of a new process group
+    ProcessGroup,
+    /// Start the CLI as the leader of a new session (and process group), fully
+    /// detached from the parent's controlling terminal
+    Session,
+}
+
+impl ProcessIsolation {
+    /// Whether the CLI leads its own process group, so the group can be signalled
+    pub fn owns_group(&self) -> bool {
+        !matches!(self, ProcessIsolation::Inherit)
+    }
+}
+
+/// Resource limits applied to the CLI process before it starts (Unix only)
+///
+/// Limits are set between `fork` and `exec`, so they bind from the first instruction
+/// and are inherited by the Bash commands, MCP servers and subagents the CLI starts;
+/// rlimits apply to each of those processes individually. Connecting fails with
+/// [`ClaudeError::InvalidConfig`](crate::ClaudeError::InvalidConfig) on platforms that
+/// cannot apply a requested limit.
+///
+/// ```
+/// use claude_agent_sdk_rs::{ClaudeAgentOptions, ProcessLimits};
+///
+/// let options = ClaudeAgentOptions::builder()
+///     .process_limits(
+///         ProcessLimits::builder()
+///             .max_address_space(4 * 1024 * 1024 * 1024)
+///             .max_open_files(1024)
+///             .nice(10)
+///             .build(),
+///     )
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, TypedBuilder)]
+pub struct ProcessLimits {
+    /// Maximum virtual address space in bytes (`RLIMIT_AS`)
+    #[builder(default, setter(strip_option))]
+    pub max_address_space: Option<u64>,
+    /// Maximum CPU time (`RLIMIT_CPU`), rounded up to whole seconds; the process is
+    /// killed when it is used up
+    #[builder(default, setter(strip_option))]
+    pub max_cpu_time: Option<Duration>,
+    /// Maximum number of open file descriptors (`RLIMIT_NOFILE`)
+    #[builder(default, setter(strip_option))]
+    pub max_open_files: Option<u64>,
+    /// Nice level from -20 to 19; negative values need elevated privileges
+    #[builder(default, setter(strip_option))]
+    pub nice: Option<i32>,
+    /// Existing cgroup v2 directory to place the process in, e.g.
+    /// `/sys/fs/cgroup/agents/agent-1` (Linux only)
+    ///
+    /// The directory's `cgroup.procs` must be writable by this process.
+    #[builder(default, setter(strip_option, into))]
+    pub cgroup: Option<PathBuf>,
+}
+
+impl ProcessLimits {
+    /// Whether no limit is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Resource usage of the CLI process, as reported by
+/// [`ClaudeClient::resource_usage`](crate::ClaudeClient::resource_usage)
+///
+/// Covers the CLI process only, not the commands and servers it has started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResourceUsage {
+    /// Process ID
+    pub pid: u32,
+    /// CPU time spent in user mode
+    pub user_cpu: Duration,
+    /// CPU time spent in kernel mode
+    pub system_cpu: Duration,
+    /// Resident set size in bytes
+    pub rss_bytes: u64,
+    /// Peak resident set size in bytes
+    pub peak_rss_bytes: u64,
+    /// Virtual memory size in bytes
+    pub virtual_bytes: u64,
+    /// Number of threads
+    pub threads: u64,
+    /// Number of open file descriptors
+    pub open_files: u64,
+}
+
+impl ResourceUsage {
+    /// Read the usage of `pid` from `/proc`
+    #[cfg(target_os = "linux")]
+    pub(crate) fn read(pid: u32) -> std::io::Result<Self> {
+        let proc_dir = PathBuf::from(format!("/proc/{}", pid));
+        let stat = std::fs::read_to_string(proc_dir.join("stat"))?;
+        let status = std::fs::read_to_string(proc_dir.join("status"))?;
+        let open_files = std::fs::read_dir(proc_dir.join("fd"))?.count() as u64;
+        // SAFETY: sysconf has no preconditions
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+
+        let mut usage = Self::parse(pid, &stat, &status, ticks.max(1) as u64).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unexpected /proc/{}/stat format", pid),
+            )
+        })?;
+        usage.open_files = open_files;
+        Ok(usage)
+    }
+
+    /// Parse `/proc/<pid>/stat` and `/proc/<pid>/status`
+    #[cfg(any(target_os = "linux", test))]
+    fn parse(pid: u32, stat: &str, status: &str, ticks_per_second: u64) -> Option<Self> {
+        // The command name may contain spaces and parentheses, so fields are counted
+        // from the last ')'; the first one after it is field 3 (state)
+        let fields: Vec<&str> = stat
+            .get(stat.rfind(')')? + 1..)?
+            .split_whitespace()
+            .collect();
+        let ticks = |field: usize| -> Option<Duration> {
+            let ticks: u64 = fields.get(field - 3)?.parse().ok()?;
+            Some(Duration::from_millis(ticks * 1000 / ticks_per_second))
+        };
+        let kib = |key: &str| -> u64 {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+                .and_then(|value| value.split_whitespace().next()?.parse::<u64>().ok())
+                .map_or(0, |kib| kib * 1024)
+        };
+
+        Some(Self {
+            pid,
+            user_cpu: ticks(14)?,
+            system_cpu: ticks(15)?,
+            rss_bytes: kib("VmRSS"),
+            peak_rss_bytes: kib("VmHWM"),
+            virtual_bytes: kib("VmSize"),
+            threads: fields.get(20 - 3)?.parse().ok()?,
+            open_files: 0,
+        })
+    }
+}
+
+/// The running CLI process, as reported by [`ClientStatus`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    /// Process ID
+    pub pid: u32,
+    /// When the process was spawned
+    pub started_at: SystemTime,
+    /// Resolved path of the CLI executable
+    pub cli_path: PathBuf,
+    /// Version reported by `claude --version`, or `None` if the version check was skipped
+    pub cli_version: Option<String>,
+}
+
+impl ProcessInfo {
+    /// Time since the process was spawned
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed().unwrap_or_default()
+    }
+}
+
+/// Snapshot of a [`ClaudeClient`](crate::ClaudeClient)'s connection, returned by
+/// [`ClaudeClient::status`](crate::ClaudeClient::status)
+#[derive(Debug, Clone, Default)]
+pub struct ClientStatus {
+    /// Whether `connect()` has completed and `disconnect()` has not been called
+    pub connected: bool,
+    /// The CLI process, if the transport runs one and it has not been stopped
+    pub process: Option<ProcessInfo>,
+    /// Whether the transport is ready for reads and writes
+    pub transport_ready: bool,
+    /// Control requests sent to the CLI that are still awaiting a response
+    pub pending_control_requests: usize,
+    /// Messages received from the CLI that have not been consumed yet
+    pub queued_messages: usize,
+    /// When the last line of output was received from the CLI
+    pub last_activity: Option<SystemTime>,
+}
+
+/// The shutdown stage at which the CLI process exited
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShutdownStage {
+    /// There was no process to stop (not connected, or a non-subprocess transport)
+    #[default]
+    NoProcess,
+    /// Exited on its own after stdin was closed
+    Graceful,
+    /// Exited after `SIGTERM`
+    Terminated,
+    /// Exited after `SIGKILL`
+    Killed,
+    /// Still running when the `SIGKILL` deadline passed
+    Unresponsive,
+}
+
+/// Outcome of stopping the CLI process, returned by
+/// [`ClaudeClient::disconnect`](crate::ClaudeClient::disconnect)
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownReport {
+    /// Stage at which the process exited
+    pub stage: ShutdownStage,
+    /// Final exit status, if the process was reaped
+    pub exit_status: Option<ExitStatus>,
+    /// Total time spent shutting down
+    pub elapsed: Duration,
+}
+
+impl ShutdownReport {
+    /// Whether the process exited without being signalled
+    pub fn is_graceful(&self) -> bool {
+        matches!(
+            self.stage,
+            ShutdownStage::Graceful | ShutdownStage::NoProcess
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_usage() {
+        let stat = "4242 (claude (node)) S 1 4242 4242 0 -1 4194560 1000 0 0 0 250 75 0 0 20 0 11 0 100 999 1234 18446744073709551615";
+        let status = "Name:\tclaude\nVmHWM:\t  204800 kB\nVmRSS:\t  102400 kB\nVmSize:\t 1048576 kB\nThreads:\t11\n";
+
+        let usage = ResourceUsage::parse(4242, stat, status, 100).unwrap();
+
+        assert_eq!(usage.user_cpu, Duration::from_millis(2500));
+        assert_eq!(usage.system_cpu, Duration::from_millis(750));
+        assert_eq!(usage.rss_bytes, 100 * 1024 * 1024);
+        assert_eq!(usage.peak_rss_bytes, 200 * 1024 * 1024);
+        assert_eq!(usage.virtual_bytes, 1024 * 1024 * 1024);
+        assert_eq!(usage.threads, 11);
+    }
+
+    #[test]
+    fn test_process_limits_is_empty() {
+        assert!(ProcessLimits::default().is_empty());
+        assert!(!ProcessLimits::builder().nice(5).build().is_empty());
+    }
+}
@@ -0,0 +1,427 @@
+//! Validation of [`ClaudeAgentOptions`] combinations
+//!
+//! Some options conflict with each other, and some are silently dropped when the CLI
+//! command is built. [`ClaudeAgentOptions::validate`] reports both as [`OptionIssue`]s.
+//! [`ClaudeClient::try_new`](crate::ClaudeClient::try_new) and the [`query`](crate::query)
+//! functions run it automatically: warnings are logged and errors fail with
+//! [`ClaudeError::InvalidOptions`](crate::ClaudeError::InvalidOptions).
+
+use std::fmt;
+use std::path::PathBuf;
+
+use tracing::warn;
+
+use super::config::{ClaudeAgentOptions, SystemPrompt, Tools};
+use crate::errors::{OptionsValidationError, Result};
+
+/// The only preset name the CLI understands
+const CLAUDE_CODE_PRESET: &str = "claude_code";
+
+/// How serious an [`OptionIssue`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IssueSeverity {
+    /// The option is ignored or likely a mistake; the session can still run
+    Warning,
+    /// The options cannot be honoured; the session is not started
+    Error,
+}
+
+/// What is wrong with an option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptionIssueKind {
+    /// Two options contradict each other
+    Conflict,
+    /// The option has no effect in this combination and is dropped
+    Ignored,
+    /// The option's value is malformed or out of range
+    InvalidValue,
+}
+
+/// A problem found by [`ClaudeAgentOptions::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionIssue {
+    /// How serious the issue is
+    pub severity: IssueSeverity,
+    /// What kind of issue it is
+    pub kind: OptionIssueKind,
+    /// The option at fault, as named on [`ClaudeAgentOptions`]
+    pub field: &'static str,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+impl OptionIssue {
+    fn error(kind: OptionIssueKind, field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: IssueSeverity::Error,
+            kind,
+            field,
+            message: message.into(),
+        }
+    }
+
+    fn warning(kind: OptionIssueKind, field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity: IssueSeverity::Warning,
+            kind,
+            field,
+            message: message.into(),
+        }
+    }
+
+    /// Whether the issue prevents the session from starting
+    pub fn is_error(&self) -> bool {
+        self.severity == IssueSeverity::Error
+    }
+}
+
+impl fmt::Display for OptionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            IssueSeverity::Warning => "warning",
+            IssueSeverity::Error => "error",
+        };
+        write!(f, "{} in `{}`: {}", severity, self.field, self.message)
+    }
+}
+
+impl ClaudeAgentOptions {
+    /// Check for conflicting, ignored and malformed options
+    ///
+    /// Returns every issue found, errors and warnings alike; an empty list means the
+    /// options are consistent. This does not touch the CLI, only the filesystem for
+    /// `settings` files.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use claude_agent_sdk_rs::{ClaudeAgentOptions, IssueSeverity};
+    ///
+    /// let options = ClaudeAgentOptions::builder()
+    ///     .resume("session-1")
+    ///     .continue_conversation(true)
+    ///     .build();
+    ///
+    /// let issues = options.validate();
+    /// assert_eq!(issues[0].field, "resume");
+    /// assert_eq!(issues[0].severity, IssueSeverity::Error);
+    /// ```
+    pub fn validate(&self) -> Vec<OptionIssue> {
+        use OptionIssueKind::{Conflict, Ignored, InvalidValue};
+
+        let mut issues = Vec::new();
+
+        if self.resume.is_some() && self.continue_conversation {
+            issues.push(OptionIssue::error(
+                Conflict,
+                "resume",
+                "`resume` and `continue_conversation` both select the session to load; set only one",
+            ));
+        }
+        if self.can_use_tool.is_some() && self.permission_prompt_tool_name.is_some() {
+            issues.push(OptionIssue::error(
+                Conflict,
+                "can_use_tool",
+                "`can_use_tool` answers permission prompts over the control protocol; it cannot be combined with `permission_prompt_tool_name`",
+            ));
+        }
+        if self.fork_session && self.resume.is_none() {
+            issues.push(OptionIssue::warning(
+                Ignored,
+                "fork_session",
+                "`fork_session` only applies when resuming a session with `resume`",
+            ));
+        }
+
+        if let Some(format) = &self.output_format {
+            if format.get("type").and_then(|t| t.as_str()) != Some("json_schema") {
+                issues.push(OptionIssue::warning(
+                    Ignored,
+                    "output_format",
+                    format!(
+                        "only `{{\"type\": \"json_schema\", ...}}` is supported, got type {}; the output format is dropped",
+                        format.get("type").map_or("<missing>".to_string(), |t| t.to_string())
+                    ),
+                ));
+            } else if !format.get("schema").is_some_and(|s| s.is_object()) {
+                issues.push(OptionIssue::error(
+                    InvalidValue,
+                    "output_format",
+                    "a `json_schema` output format needs a `schema` object",
+                ));
+            }
+        }
+
+        if let Some(SystemPrompt::Preset(preset)) = &self.system_prompt
+            && preset.preset != CLAUDE_CODE_PRESET
+        {
+            issues.push(OptionIssue::warning(
+                Ignored,
+                "system_prompt",
+                format!(
+                    "unknown preset '{}'; the CLI's default `{}` prompt is used",
+                    preset.preset, CLAUDE_CODE_PRESET
+                ),
+            ));
+        }
+        if let Some(Tools::Preset(preset)) = &self.tools
+            && preset.preset != CLAUDE_CODE_PRESET
+        {
+            issues.push(OptionIssue::warning(
+                Ignored,
+                "tools",
+                format!(
+                    "unknown preset '{}'; the CLI's default `{}` tools are used",
+                    preset.preset, CLAUDE_CODE_PRESET
+                ),
+            ));
+        }
+
+        if let Some(settings) = &self.settings
+            && let Err(message) = self.check_settings(settings)
+        {
+            issues.push(OptionIssue::error(InvalidValue, "settings", message));
+        }
+
+        if let Some(budget) = self.max_budget_usd
+            && !(budget.is_finite() && budget > 0.0)
+        {
+            issues.push(OptionIssue::error(
+                InvalidValue,
+                "max_budget_usd",
+                format!("must be a positive amount, got {}", budget),
+            ));
+        }
+        if self.max_turns == Some(0) {
+            issues.push(OptionIssue::error(
+                InvalidValue,
+                "max_turns",
+                "must be at least 1",
+            ));
+        }
+        if self.max_buffer_size == Some(0) {
+            issues.push(OptionIssue::error(
+                InvalidValue,
+                "max_buffer_size",
+                "must be at least 1 byte",
+            ));
+        }
+        if let (Some(min), Some(max)) = (self.min_cli_version, self.max_cli_version)
+            && min > max
+        {
+            issues.push(OptionIssue::error(
+                Conflict,
+                "min_cli_version",
+                format!("minimum {} is above maximum {}", min, max),
+            ));
+        }
+        if let Some(nice) = self.process_limits.nice
+            && !(-20..=19).contains(&nice)
+        {
+            issues.push(OptionIssue::error(
+                InvalidValue,
+                "process_limits",
+                format!("nice level must be between -20 and 19, got {}", nice),
+            ));
+        }
+
+        issues
+    }
+
+    /// Run [`validate`](Self::validate), logging warnings and failing on errors
+    pub(crate) fn ensure_valid(&self) -> Result<()> {
+        Self::report(self.validate())
+    }
+
+    /// Like [`ensure_valid`](Self::ensure_valid), for the one-shot [`query`](crate::query)
+    /// functions, which run without a control protocol loop
+    pub(crate) fn ensure_valid_for_one_shot(&self) -> Result<()> {
+        let mut issues = self.validate();
+        if self.can_use_tool.is_some() {
+            issues.push(OptionIssue::error(
+                OptionIssueKind::Conflict,
+                "can_use_tool",
+                "one-shot queries cannot answer permission prompts; use `ClaudeClient` for `can_use_tool`",
+            ));
+        }
+        Self::report(issues)
+    }
+
+    fn report(issues: Vec<OptionIssue>) -> Result<()> {
+        let (errors, warnings): (Vec<_>, Vec<_>) =
+            issues.into_iter().partition(OptionIssue::is_error);
+        for issue in &warnings {
+            warn!("{}", issue);
+        }
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(OptionsValidationError::new(errors).into())
+    }
+
+    /// Check that `settings` is a JSON object, inline or in a readable file
+    fn check_settings(&self, settings: &str) -> std::result::Result<(), String> {
+        let trimmed = settings.trim();
+        let (source, content) = if trimmed.starts_with('{') {
+            ("inline settings", trimmed.to_string())
+        } else {
+            // The CLI resolves relative paths against its working directory
+            let mut path = PathBuf::from(trimmed);
+            if path.is_relative()
+                && let Some(cwd) = &self.cwd
+            {
+                path = cwd.join(path);
+            }
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+            ("settings file", content)
+        };
+
+        match serde_json::from_str::<serde_json::Value>(&content) {
+            Ok(serde_json::Value::Object(_)) => Ok(()),
+            Ok(_) => Err(format!("{} must be a JSON object", source)),
+            Err(e) => Err(format!("{} is not valid JSON: {}", source, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::config::{SystemPromptPreset, ToolsPreset};
+
+    fn fields(options: &ClaudeAgentOptions) -> Vec<(&'static str, IssueSeverity)> {
+        options
+            .validate()
+            .into_iter()
+            .map(|issue| (issue.field, issue.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_options_are_valid() {
+        assert!(ClaudeAgentOptions::default().validate().is_empty());
+        ClaudeAgentOptions::default().ensure_valid().unwrap();
+    }
+
+    #[test]
+    fn test_session_conflicts() {
+        let options = ClaudeAgentOptions::builder()
+            .resume("s1")
+            .continue_conversation(true)
+            .build();
+        assert_eq!(fields(&options), [("resume", IssueSeverity::Error)]);
+
+        let options = ClaudeAgentOptions::builder().fork_session(true).build();
+        assert_eq!(fields(&options), [("fork_session", IssueSeverity::Warning)]);
+    }
+
+    #[test]
+    fn test_output_format_checks() {
+        let dropped = ClaudeAgentOptions::builder()
+            .output_format(serde_json::json!({"type": "json_object"}))
+            .build();
+        assert_eq!(
+            fields(&dropped),
+            [("output_format", IssueSeverity::Warning)]
+        );
+
+        let no_schema = ClaudeAgentOptions::builder()
+            .output_format(serde_json::json!({"type": "json_schema"}))
+            .build();
+        assert_eq!(
+            fields(&no_schema),
+            [("output_format", IssueSeverity::Error)]
+        );
+
+        let valid = ClaudeAgentOptions::builder()
+            .output_format(serde_json::json!({"type": "json_schema", "schema": {}}))
+            .build();
+        assert!(valid.validate().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_presets_are_reported() {
+        let options = ClaudeAgentOptions::builder()
+            .system_prompt(SystemPrompt::Preset(SystemPromptPreset::new("minimal")))
+            .tools(ToolsPreset::new("everything"))
+            .build();
+        assert_eq!(
+            fields(&options),
+            [
+                ("system_prompt", IssueSeverity::Warning),
+                ("tools", IssueSeverity::Warning)
+            ]
+        );
+
+        let options = ClaudeAgentOptions::builder()
+            .system_prompt(SystemPrompt::Preset(SystemPromptPreset::new("claude_code")))
+            .build();
+        assert!(options.validate().is_empty());
+    }
+
+    #[test]
+    fn test_settings_must_parse() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::write(dir.join("good.json"), r#"{"model": "x"}"#).unwrap();
+        std::fs::write(dir.join("bad.json"), "{not json").unwrap();
+
+        let with_settings = |settings: &str| {
+            ClaudeAgentOptions::builder()
+                .cwd(dir)
+                .settings(settings)
+                .build()
+        };
+        assert!(with_settings("good.json").validate().is_empty());
+        assert!(with_settings(r#"{"model": "x"}"#).validate().is_empty());
+        for bad in ["bad.json", "missing.json", "{\"model\": ", "[1]"] {
+            let issues = with_settings(bad).validate();
+            assert_eq!(issues.len(), 1, "{bad}");
+            assert_eq!(issues[0].kind, OptionIssueKind::InvalidValue);
+        }
+    }
+
+    #[test]
+    fn test_ensure_valid_fails_only_on_errors() {
+        let warning_only = ClaudeAgentOptions::builder().fork_session(true).build();
+        warning_only.ensure_valid().unwrap();
+
+        let invalid = ClaudeAgentOptions::builder()
+            .max_budget_usd(-1.0)
+            .max_turns(0)
+            .fork_session(true)
+            .build();
+        let err = invalid.ensure_valid().unwrap_err();
+        let crate::ClaudeError::InvalidOptions(err) = err else {
+            panic!("expected InvalidOptions, got {err}");
+        };
+        assert_eq!(err.issues.len(), 2);
+        assert!(err.issues.iter().all(OptionIssue::is_error));
+    }
+
+    #[test]
+    fn test_can_use_tool_conflicts() {
+        use crate::types::permissions::{CanUseToolCallback, PermissionResult};
+        use futures::FutureExt;
+
+        let callback: CanUseToolCallback = std::sync::Arc::new(|_, _, _| {
+            async { PermissionResult::Allow(Default::default()) }.boxed()
+        });
+
+        let options = ClaudeAgentOptions::builder()
+            .can_use_tool(callback.clone())
+            .permission_prompt_tool_name("mcp__auth__prompt")
+            .build();
+        assert_eq!(fields(&options), [("can_use_tool", IssueSeverity::Error)]);
+
+        let options = ClaudeAgentOptions::builder().can_use_tool(callback).build();
+        options.ensure_valid().unwrap();
+        let err = options.ensure_valid_for_one_shot().unwrap_err();
+        let crate::ClaudeError::InvalidOptions(err) = err else {
+            panic!("expected InvalidOptions, got {err}");
+        };
+        assert_eq!(err.issues[0].field, "can_use_tool");
+        assert_eq!(err.issues[0].kind, OptionIssueKind::Conflict);
+    }
+}
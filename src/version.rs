@@ -10,7 +10,10 @@ static CLAUDE_CODE_VERSION: OnceLock<Option<String>> = OnceLock::new();
 
 /// Get the Claude Code CLI version.
 ///
-/// This function caches the result using `OnceLock`, so the CLI is only invoked once.
+/// Reports the CLI the SDK would use by default, found with
+/// [`CliLocator`](crate::cli::CliLocator). The result is computed once per process with
+/// `OnceLock`; the probe shares the `--version` cache with connecting clients (see
+/// [`cli::prewarm`](crate::cli::prewarm)), so a CLI they already probed is not run again.
 /// Returns `None` if the CLI is not found or the version cannot be determined.
 ///
 /// # Example
@@ -27,19 +30,11 @@ static CLAUDE_CODE_VERSION: OnceLock<Option<String>> = OnceLock::new();
 pub fn get_claude_code_version() -> Option<&'static str> {
     CLAUDE_CODE_VERSION
         .get_or_init(|| {
-            std::process::Command::new("claude")
-                .arg("--version")
-                .output()
-                .ok()
-                .filter(|output| output.status.success())
-                .and_then(|output| {
-                    let version_output = String::from_utf8_lossy(&output.stdout);
-                    version_output
-                        .lines()
-                        .next()
-                        .and_then(|line| line.split_whitespace().next())
-                        .map(|v| v.trim().to_string())
-                })
+            let cli_path = crate::cli::CliLocator::default().first_path()?;
+            crate::cli::version_output_blocking(&cli_path, &Default::default())?
+                .split_whitespace()
+                .next()
+                .map(|v| v.trim().to_string())
         })
         .as_deref()
 }
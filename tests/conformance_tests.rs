@@ -0,0 +1,423 @@
+#![cfg(feature = "testing")]
+//! Protocol conformance against Python SDK goldens
+//!
+//! Each file in `fixtures/conformance/` pairs a set of options (using the Python
+//! `ClaudeAgentOptions` field names) with the CLI arguments, environment entries and
+//! `initialize` request the Python SDK produces for them. Every case is run through
+//! the real subprocess transport against `claude-fake`, and what the fake CLI
+//! actually received is compared with the golden.
+//!
+//! Comparison rules:
+//! - Arguments are compared as a multiset of `--flag [value]` groups, so ordering
+//!   differences are ignored but duplicated or missing flags are not. JSON-valued
+//!   arguments are compared after parsing.
+//! - Environment entries are compared exactly, ignoring variables inherited from the
+//!   test process and the SDK identity variables, which differ between SDKs.
+//! - Hook callback IDs in `initialize` are compared by shape only, since Rust
+//!   registers events in hash-map order.
+//! - Arguments listed in [`OPEN_GAPS`] are known differences and are skipped.
+//!
+//! The goldens are currently hand-written from the Python SDK sources, not generated
+//! by it; see `fixtures/conformance/README.md`.
+
+use claude_agent_sdk_rs::testing::ScenarioBuilder;
+use claude_agent_sdk_rs::{
+    ClaudeAgentOptions, ClaudeClient, HookCallback, HookEvent, HookJsonOutput, HookMatcher,
+    McpServerConfig, McpServers, SyncHookJsonOutput, create_sdk_mcp_server,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const FAKE_CLI: &str = env!("CARGO_BIN_EXE_claude-fake");
+
+/// Environment variables that identify the SDK and intentionally differ from Python
+const SDK_IDENTITY_ENV: &[&str] = &["CLAUDE_CODE_ENTRYPOINT", "CLAUDE_AGENT_SDK_VERSION"];
+
+/// Open parity gaps: arguments the Python SDK passes that the Rust SDK omits
+///
+/// Python sends an empty system prompt and empty setting sources when neither is
+/// configured, opting out of the CLI's default prompt and settings. The Rust SDK
+/// leaves both flags off, so the CLI applies its defaults. These are real behavior
+/// differences, tracked in `fixtures/conformance/README.md`; they are excluded from
+/// the argument comparison and `test_open_gaps_still_diverge` fails once one is
+/// closed, so the entry gets removed.
+const OPEN_GAPS: &[(&str, &str)] = &[("--system-prompt", ""), ("--setting-sources", "")];
+
+fn conformance_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/conformance")
+}
+
+fn load_goldens() -> Vec<Value> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(conformance_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap())
+        .collect()
+}
+
+fn field<T: DeserializeOwned>(options: &Value, key: &str) -> Option<T> {
+    options
+        .get(key)
+        .filter(|value| !value.is_null())
+        .map(|value| {
+            serde_json::from_value(value.clone())
+                .unwrap_or_else(|e| panic!("invalid golden option '{}': {}", key, e))
+        })
+}
+
+fn noop_hook() -> HookCallback {
+    Arc::new(|_, _, _| Box::pin(async { HookJsonOutput::Sync(SyncHookJsonOutput::default()) }))
+}
+
+/// Translate Python-style golden options into `ClaudeAgentOptions`
+fn options_from_golden(golden: &Value) -> ClaudeAgentOptions {
+    const SUPPORTED: &[&str] = &[
+        "system_prompt",
+        "tools",
+        "allowed_tools",
+        "disallowed_tools",
+        "permission_mode",
+        "model",
+        "fallback_model",
+        "betas",
+        "max_turns",
+        "max_budget_usd",
+        "max_thinking_tokens",
+        "permission_prompt_tool_name",
+        "resume",
+        "continue_conversation",
+        "fork_session",
+        "include_partial_messages",
+        "add_dirs",
+        "setting_sources",
+        "extra_args",
+        "settings",
+        "sandbox",
+        "mcp_servers",
+        "agents",
+        "output_format",
+        "env",
+        "enable_file_checkpointing",
+        "hooks",
+    ];
+    let o = &golden["options"];
+    for key in o.as_object().unwrap().keys() {
+        assert!(
+            SUPPORTED.contains(&key.as_str()),
+            "{}: golden option '{}' is not mapped by the conformance harness",
+            golden["name"],
+            key
+        );
+    }
+
+    let mut options = ClaudeAgentOptions {
+        system_prompt: field(o, "system_prompt"),
+        tools: field(o, "tools"),
+        allowed_tools: field(o, "allowed_tools").unwrap_or_default(),
+        disallowed_tools: field(o, "disallowed_tools").unwrap_or_default(),
+        permission_mode: field(o, "permission_mode"),
+        model: field(o, "model"),
+        fallback_model: field(o, "fallback_model"),
+        betas: field(o, "betas").unwrap_or_default(),
+        max_turns: field(o, "max_turns"),
+        max_budget_usd: field(o, "max_budget_usd"),
+        max_thinking_tokens: field(o, "max_thinking_tokens"),
+        permission_prompt_tool_name: field(o, "permission_prompt_tool_name"),
+        resume: field(o, "resume"),
+        continue_conversation: field(o, "continue_conversation").unwrap_or_default(),
+        fork_session: field(o, "fork_session").unwrap_or_default(),
+        include_partial_messages: field(o, "include_partial_messages").unwrap_or_default(),
+        add_dirs: field(o, "add_dirs").unwrap_or_default(),
+        setting_sources: field(o, "setting_sources"),
+        extra_args: field(o, "extra_args").unwrap_or_default(),
+        settings: field(o, "settings"),
+        sandbox: field(o, "sandbox"),
+        agents: field(o, "agents"),
+        output_format: field(o, "output_format"),
+        env: field(o, "env").unwrap_or_default(),
+        enable_file_checkpointing: field(o, "enable_file_checkpointing").unwrap_or_default(),
+        ..Default::default()
+    };
+
+    if let Some(servers) = o.get("mcp_servers").and_then(Value::as_object) {
+        let servers = servers
+            .iter()
+            .map(|(name, config)| {
+                let config = match config["type"].as_str() {
+                    Some("sdk") => McpServerConfig::Sdk(create_sdk_mcp_server(
+                        config["name"].as_str().unwrap(),
+                        "1.0.0",
+                        vec![],
+                    )),
+                    Some("sse") => {
+                        McpServerConfig::Sse(serde_json::from_value(config.clone()).unwrap())
+                    }
+                    Some("http") => {
+                        McpServerConfig::Http(serde_json::from_value(config.clone()).unwrap())
+                    }
+                    _ => McpServerConfig::Stdio(serde_json::from_value(config.clone()).unwrap()),
+                };
+                (name.clone(), config)
+            })
+            .collect();
+        options.mcp_servers = McpServers::Dict(servers);
+    }
+
+    if let Some(hooks) = o.get("hooks").and_then(Value::as_object) {
+        let hooks = hooks
+            .iter()
+            .map(|(event, matchers)| {
+                let event: HookEvent = serde_json::from_value(json!(event)).unwrap();
+                let matchers = matchers
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|m| HookMatcher {
+                        matcher: field(m, "matcher"),
+                        hooks: (0..m["hooks"].as_u64().unwrap())
+                            .map(|_| noop_hook())
+                            .collect(),
+                        timeout: field(m, "timeout"),
+                    })
+                    .collect();
+                (event, matchers)
+            })
+            .collect();
+        options.hooks = Some(hooks);
+    }
+
+    options
+}
+
+/// Group arguments into sorted `(flag, value)` pairs, parsing JSON values
+fn normalize_args(args: &[String]) -> Vec<(String, Option<Value>)> {
+    let mut groups = Vec::new();
+    let mut iter = args.iter().peekable();
+    while let Some(flag) = iter.next() {
+        assert!(
+            flag.starts_with("--"),
+            "unexpected positional argument '{}'",
+            flag
+        );
+        let value = iter.next_if(|next| !next.starts_with("--")).map(|value| {
+            match serde_json::from_str::<Value>(value) {
+                Ok(parsed @ (Value::Object(_) | Value::Array(_))) => normalize_numbers(parsed),
+                _ => Value::String(value.clone()),
+            }
+        });
+        groups.push((flag.clone(), value));
+    }
+    groups.sort_by_key(|(flag, value)| (flag.clone(), value.as_ref().map(Value::to_string)));
+    groups
+}
+
+/// Represent every number as `f64`, since `30` and `30.0` are the same on the wire
+fn normalize_numbers(value: Value) -> Value {
+    match value {
+        Value::Number(n) => json!(n.as_f64().unwrap()),
+        Value::Array(items) => Value::Array(items.into_iter().map(normalize_numbers).collect()),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, normalize_numbers(v)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Replace hook callback IDs with a placeholder, checking they are unique
+fn normalize_initialize(mut request: Value) -> Value {
+    let mut seen = Vec::new();
+    if let Some(events) = request["hooks"].as_object_mut() {
+        for matchers in events.values_mut() {
+            for matcher in matchers.as_array_mut().unwrap() {
+                for id in matcher["hookCallbackIds"].as_array_mut().unwrap() {
+                    let id_str = id.as_str().unwrap().to_string();
+                    assert!(id_str.starts_with("hook_"), "bad callback ID {}", id_str);
+                    assert!(!seen.contains(&id_str), "duplicate callback ID {}", id_str);
+                    seen.push(id_str);
+                    *id = json!("hook_*");
+                }
+            }
+        }
+    }
+    normalize_numbers(request)
+}
+
+struct Captured {
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    initialize: Value,
+}
+
+async fn run_case(golden: &Value) -> Captured {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path();
+    let script = ScenarioBuilder::new("conformance").build();
+    std::fs::write(
+        dir.join("script.json"),
+        serde_json::to_string(&script).unwrap(),
+    )
+    .unwrap();
+
+    let mut options = options_from_golden(golden);
+    options.cli_path = Some(FAKE_CLI.into());
+    options.skip_version_check = true;
+    options.env.extend([
+        (
+            "CLAUDE_FAKE_SCRIPT".to_string(),
+            dir.join("script.json").display().to_string(),
+        ),
+        (
+            "CLAUDE_FAKE_ARGV_OUT".to_string(),
+            dir.join("argv.json").display().to_string(),
+        ),
+        (
+            "CLAUDE_FAKE_STDIN_OUT".to_string(),
+            dir.join("stdin.jsonl").display().to_string(),
+        ),
+        ("CLAUDE_FAKE_SPEED".to_string(), "0".to_string()),
+    ]);
+
+    let mut client = ClaudeClient::new(options);
+    client.connect().await.unwrap();
+    client.disconnect().await.unwrap();
+
+    let argv: Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("argv.json")).unwrap()).unwrap();
+    let stdin = std::fs::read_to_string(dir.join("stdin.jsonl")).unwrap();
+
+    let inherited: HashMap<String, String> = std::env::vars().collect();
+    let env = serde_json::from_value::<HashMap<String, String>>(argv["env"].clone())
+        .unwrap()
+        .into_iter()
+        .filter(|(key, value)| {
+            !key.starts_with("CLAUDE_FAKE_")
+                && !SDK_IDENTITY_ENV.contains(&key.as_str())
+                && inherited.get(key) != Some(value)
+        })
+        .collect();
+
+    let initialize = stdin
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .find(|line| line["request"]["subtype"] == "initialize")
+        .expect("SDK should send initialize")["request"]
+        .clone();
+
+    Captured {
+        args: serde_json::from_value(argv["args"].clone()).unwrap(),
+        env,
+        initialize,
+    }
+}
+
+#[tokio::test]
+async fn test_goldens_are_present() {
+    let goldens = load_goldens();
+    assert!(
+        goldens.len() >= 6,
+        "expected conformance goldens in fixtures/conformance"
+    );
+    for golden in &goldens {
+        assert!(golden["name"].is_string());
+        assert!(golden["expected"]["args"].is_array());
+        assert!(golden["expected"]["env"].is_object());
+        assert!(golden["expected"]["initialize"].is_object());
+    }
+}
+
+#[tokio::test]
+async fn test_cli_arguments_match_python_sdk() {
+    for golden in load_goldens() {
+        let captured = run_case(&golden).await;
+        let expected: Vec<String> =
+            serde_json::from_value(golden["expected"]["args"].clone()).unwrap();
+        let mut expected = normalize_args(&expected);
+        expected.retain(|group| !is_open_gap(group));
+
+        assert_eq!(
+            normalize_args(&captured.args),
+            expected,
+            "{}: CLI arguments differ from the Python SDK",
+            golden["name"]
+        );
+    }
+}
+
+fn is_open_gap(group: &(String, Option<Value>)) -> bool {
+    OPEN_GAPS.iter().any(|gap| matches_gap(group, gap))
+}
+
+fn matches_gap(
+    (flag, value): &(String, Option<Value>),
+    (gap_flag, gap_value): &(&str, &str),
+) -> bool {
+    flag == gap_flag && value.as_ref() == Some(&json!(gap_value))
+}
+
+#[tokio::test]
+async fn test_open_gaps_still_diverge() {
+    for gap in OPEN_GAPS {
+        let mut in_goldens = false;
+        for golden in load_goldens() {
+            let expected: Vec<String> =
+                serde_json::from_value(golden["expected"]["args"].clone()).unwrap();
+            if !normalize_args(&expected)
+                .iter()
+                .any(|g| matches_gap(g, gap))
+            {
+                continue;
+            }
+            in_goldens = true;
+
+            let captured = run_case(&golden).await;
+            assert!(
+                !normalize_args(&captured.args)
+                    .iter()
+                    .any(|g| matches_gap(g, gap)),
+                "{}: {:?} now matches the Python SDK; remove it from OPEN_GAPS",
+                golden["name"],
+                gap
+            );
+        }
+        assert!(in_goldens, "{:?} no longer appears in any golden", gap);
+    }
+}
+
+#[tokio::test]
+async fn test_environment_matches_python_sdk() {
+    for golden in load_goldens() {
+        let captured = run_case(&golden).await;
+        let expected: HashMap<String, String> =
+            serde_json::from_value(golden["expected"]["env"].clone()).unwrap();
+
+        assert_eq!(
+            captured.env, expected,
+            "{}: environment differs from the Python SDK",
+            golden["name"]
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_initialize_matches_python_sdk() {
+    for golden in load_goldens() {
+        let captured = run_case(&golden).await;
+
+        assert_eq!(
+            normalize_initialize(captured.initialize),
+            normalize_initialize(golden["expected"]["initialize"].clone()),
+            "{}: initialize request differs from the Python SDK",
+            golden["name"]
+        );
+    }
+}
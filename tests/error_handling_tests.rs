@@ -116,6 +116,43 @@ fn test_invalid_cwd_is_file() {
     );
 }
 
+#[test]
+fn test_try_new_rejects_conflicting_options() {
+    use claude_agent_sdk_rs::{ClaudeClient, OptionIssueKind};
+
+    let options = ClaudeAgentOptions::builder()
+        .resume("session-1")
+        .continue_conversation(true)
+        .output_format(serde_json::json!({"type": "json_schema"}))
+        .build();
+
+    let Err(ClaudeError::InvalidOptions(err)) = ClaudeClient::try_new(options) else {
+        panic!("Expected InvalidOptions");
+    };
+    let kinds: Vec<_> = err.issues.iter().map(|i| (i.field, i.kind)).collect();
+    assert_eq!(
+        kinds,
+        [
+            ("resume", OptionIssueKind::Conflict),
+            ("output_format", OptionIssueKind::InvalidValue)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_query_rejects_invalid_options_before_spawning() {
+    let options = ClaudeAgentOptions::builder()
+        .cli_path("/nonexistent/claude")
+        .settings("{\"permissions\": ")
+        .build();
+
+    let err = claude_agent_sdk_rs::query("hi", Some(options))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClaudeError::InvalidOptions(_)), "{err}");
+    assert!(err.to_string().contains("settings"), "{err}");
+}
+
 // =============================================================================
 // Image Validation Tests
 // =============================================================================
@@ -414,3 +451,61 @@ fn test_result_builder_defaults() {
         panic!("Expected result message");
     }
 }
+
+// =============================================================================
+// Plugin Error Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_connect_fails_on_invalid_plugin() {
+    let options = ClaudeAgentOptions::builder()
+        .plugins(vec![claude_agent_sdk_rs::SdkPluginConfig::local(
+            "./fixtures/does-not-exist",
+        )])
+        .build();
+    let mut client = claude_agent_sdk_rs::ClaudeClient::new(options);
+
+    let err = client.connect().await.unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ClaudeError::Plugin(claude_agent_sdk_rs::PluginError::DirectoryNotFound { .. })
+        ),
+        "Expected a plugin error, got {:?}",
+        err
+    );
+}
+
+#[tokio::test]
+async fn test_one_shot_query_fails_on_invalid_plugin() {
+    let options = ClaudeAgentOptions::builder()
+        .plugins(vec![claude_agent_sdk_rs::SdkPluginConfig::local(
+            "./fixtures/does-not-exist",
+        )])
+        .build();
+
+    let err = claude_agent_sdk_rs::query("hello", Some(options.clone()))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClaudeError::Plugin(_)), "got {:?}", err);
+
+    let err = claude_agent_sdk_rs::query_stream("hello", Some(options))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err, ClaudeError::Plugin(_)), "got {:?}", err);
+}
+
+#[tokio::test]
+async fn test_connect_with_transport_fails_on_invalid_plugin() {
+    let options = ClaudeAgentOptions::builder()
+        .plugins(vec![claude_agent_sdk_rs::SdkPluginConfig::local(
+            "./fixtures/does-not-exist",
+        )])
+        .build();
+    let transport = std::sync::Arc::new(MockTransport::builder().build());
+    let mut client = claude_agent_sdk_rs::ClaudeClient::with_transport(transport, options);
+
+    let err = client.connect_with_transport().await.unwrap_err();
+    assert!(matches!(err, ClaudeError::Plugin(_)), "got {:?}", err);
+}
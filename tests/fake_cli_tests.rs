@@ -0,0 +1,1065 @@
+#![cfg(feature = "testing")]
+//! End-to-end tests through the real subprocess transport using `claude-fake`
+//!
+//! These tests spawn the `claude-fake` binary in place of the `claude` CLI, so argument
+//! building, environment handling, stdio framing and the control protocol are all
+//! exercised without network access.
+
+use claude_agent_sdk_rs::cli::{CliFeature, CliLocator, CliSource, CliVersion};
+use claude_agent_sdk_rs::testing::builders::{
+    AssistantMessageBuilder, ResultMessageBuilder, SystemMessageBuilder,
+};
+use claude_agent_sdk_rs::testing::{
+    CassetteMode, HookRecorder, Scenario, ScenarioBuilder, ScheduledMessage, SnapshotPlayer,
+};
+use claude_agent_sdk_rs::{
+    ClaudeAgentOptions, ClaudeClient, ClaudeError, ContentBlock, HookEvent, HookMatcher,
+    McpServerConfig, McpServers, McpToolResultContent, Message, ProcessIsolation, ProcessLimits,
+    ShutdownPolicy, ShutdownReport, ShutdownStage, ToolResult, create_sdk_mcp_server, query, tool,
+};
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const FAKE_CLI: &str = env!("CARGO_BIN_EXE_claude-fake");
+
+/// Temporary directory holding the script and capture files for one test
+struct FakeEnv {
+    dir: tempfile::TempDir,
+}
+
+impl FakeEnv {
+    fn new(scenario: &Scenario) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("script.json"),
+            serde_json::to_string(scenario).unwrap(),
+        )
+        .unwrap();
+        Self { dir }
+    }
+
+    fn path(&self) -> &std::path::Path {
+        self.dir.path()
+    }
+
+    fn env(&self) -> HashMap<String, String> {
+        HashMap::from([
+            (
+                "CLAUDE_FAKE_SCRIPT".to_string(),
+                self.path().join("script.json").display().to_string(),
+            ),
+            (
+                "CLAUDE_FAKE_ARGV_OUT".to_string(),
+                self.path().join("argv.json").display().to_string(),
+            ),
+            (
+                "CLAUDE_FAKE_STDIN_OUT".to_string(),
+                self.path().join("stdin.jsonl").display().to_string(),
+            ),
+            ("CLAUDE_FAKE_SPEED".to_string(), "0".to_string()),
+        ])
+    }
+
+    fn argv(&self) -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(self.path().join("argv.json")).unwrap())
+            .unwrap()
+    }
+
+    fn stdin(&self) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(self.path().join("stdin.jsonl"))
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+}
+
+fn hello_scenario() -> Scenario {
+    ScenarioBuilder::new("hello")
+        .on_connect(SystemMessageBuilder::default().build())
+        .exchange()
+        .respond(
+            AssistantMessageBuilder::new()
+                .text("Hello from fake")
+                .build(),
+        )
+        .then_result(ResultMessageBuilder::default().build())
+        .build()
+}
+
+fn control_request(request: serde_json::Value) -> ScheduledMessage {
+    ScheduledMessage {
+        value: serde_json::json!({"type": "control_request", "request": request}),
+        timing: Default::default(),
+    }
+}
+
+async fn collect_response(client: &mut ClaudeClient) -> Vec<Message> {
+    let mut messages = Vec::new();
+    let mut stream = client.receive_response();
+    while let Some(message) = stream.next().await {
+        messages.push(message.unwrap());
+    }
+    messages
+}
+
+fn assistant_text(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .filter_map(|m| match m {
+            Message::Assistant(a) => Some(&a.message.content),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|block| match block {
+            ContentBlock::Text(text) => Some(text.text.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_client_session_through_subprocess() {
+    let fake = FakeEnv::new(&hello_scenario());
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .model("claude-sonnet-4-5")
+        .allowed_tools(vec!["Read".to_string(), "Grep".to_string()])
+        .env(fake.env())
+        .build();
+
+    let mut client = ClaudeClient::new(options);
+    client.connect().await.unwrap();
+    client.query("Hi").await.unwrap();
+    let messages = collect_response(&mut client).await;
+    client.disconnect().await.unwrap();
+
+    assert_eq!(assistant_text(&messages), "Hello from fake");
+    assert!(matches!(messages.last(), Some(Message::Result(_))));
+
+    let argv = fake.argv();
+    let args: Vec<&str> = argv["args"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| a.as_str().unwrap())
+        .collect();
+    assert!(
+        args.windows(2)
+            .any(|w| w == ["--model", "claude-sonnet-4-5"])
+    );
+    assert!(
+        args.windows(2)
+            .any(|w| w == ["--allowedTools", "Read,Grep"])
+    );
+    assert!(
+        args.windows(2)
+            .any(|w| w == ["--input-format", "stream-json"])
+    );
+    assert_eq!(argv["env"]["CLAUDE_CODE_ENTRYPOINT"], "sdk-rs");
+
+    let stdin = fake.stdin();
+    assert_eq!(stdin[0]["request"]["subtype"], "initialize");
+    assert!(stdin.iter().any(|line| line["type"] == "user"));
+}
+
+#[tokio::test]
+async fn test_fake_cli_replays_json_scenario_file() {
+    let fake = FakeEnv::new(&hello_scenario());
+    std::fs::write(
+        fake.path().join("script.json"),
+        r#"{
+            "name": "capital",
+            "on_connect": [{"type": "system", "subtype": "init", "session_id": "json-session"}],
+            "exchanges": [{
+                "trigger": "capital of France",
+                "expect_writes": ["capital of France"],
+                "responses": [
+                    {"type": "assistant", "message": {"model": "m", "content": [{"type": "text", "text": "Paris"}]}},
+                    {"message": {"type": "result", "subtype": "success", "duration_ms": 1,
+                                 "duration_api_ms": 1, "is_error": false, "num_turns": 1,
+                                 "session_id": "json-session"},
+                     "delay_ms": 0}
+                ]
+            }]
+        }"#,
+    )
+    .unwrap();
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(fake.env())
+        .build();
+
+    let mut client = ClaudeClient::new(options);
+    client.connect().await.unwrap();
+    client
+        .query("What is the capital of France?")
+        .await
+        .unwrap();
+    let messages = collect_response(&mut client).await;
+    client.disconnect().await.unwrap();
+
+    assert_eq!(assistant_text(&messages), "Paris");
+    assert!(matches!(messages.last(), Some(Message::Result(_))));
+}
+
+#[tokio::test]
+async fn test_one_shot_query_through_subprocess() {
+    let fake = FakeEnv::new(&hello_scenario());
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(fake.env())
+        .build();
+
+    let messages = query("Say hello", Some(options)).await.unwrap();
+
+    assert_eq!(assistant_text(&messages), "Hello from fake");
+    let args = fake.argv()["args"].clone();
+    assert!(
+        !args
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("--input-format"))
+    );
+}
+
+#[tokio::test]
+async fn test_hook_callback_round_trip() {
+    let mut scenario = hello_scenario();
+    scenario.exchanges[0].responses.insert(
+        0,
+        control_request(serde_json::json!({
+            "subtype": "hook_callback",
+            "input": {
+                "hook_event_name": "PreToolUse",
+                "session_id": "fake-session",
+                "transcript_path": "/tmp/transcript",
+                "cwd": "/tmp",
+                "tool_name": "Bash",
+                "tool_input": {"command": "ls"}
+            }
+        })),
+    );
+    let fake = FakeEnv::new(&scenario);
+
+    let recorder = HookRecorder::new();
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(fake.env())
+        .hooks(HashMap::from([(
+            HookEvent::PreToolUse,
+            vec![
+                HookMatcher::builder()
+                    .hooks(vec![recorder.pre_tool_use_callback()])
+                    .build(),
+            ],
+        )]))
+        .build();
+
+    let mut client = ClaudeClient::new(options);
+    client.connect().await.unwrap();
+    client.query("List files").await.unwrap();
+    let messages = collect_response(&mut client).await;
+    client.disconnect().await.unwrap();
+
+    assert_eq!(assistant_text(&messages), "Hello from fake");
+    recorder.assert_tool_used("Bash").await;
+
+    let stdin = fake.stdin();
+    let reply = stdin
+        .iter()
+        .find(|line| line["response"]["request_id"] == "req_fake_1")
+        .expect("hook reply should be written to stdin");
+    assert_eq!(reply["response"]["subtype"], "success");
+}
+
+#[tokio::test]
+async fn test_sdk_mcp_round_trip() {
+    let mut scenario = hello_scenario();
+    scenario.exchanges[0].responses.insert(
+        0,
+        control_request(serde_json::json!({
+            "subtype": "mcp_message",
+            "server_name": "calc",
+            "message": {
+                "jsonrpc": "2.0",
+                "id": 7,
+                "method": "tools/call",
+                "params": {"name": "add", "arguments": {"a": 2, "b": 3}}
+            }
+        })),
+    );
+    let fake = FakeEnv::new(&scenario);
+
+    let calls = Arc::new(Mutex::new(0));
+    let counter = Arc::clone(&calls);
+    let server = create_sdk_mcp_server(
+        "calc",
+        "1.0.0",
+        vec![tool!(
+            "add",
+            "Add two numbers",
+            serde_json::json!({"type": "object"}),
+            move |args: serde_json::Value| {
+                let counter = Arc::clone(&counter);
+                async move {
+                    *counter.lock().unwrap() += 1;
+                    let sum = args["a"].as_i64().unwrap() + args["b"].as_i64().unwrap();
+                    Ok(ToolResult {
+                        content: vec![McpToolResultContent::Text {
+                            text: sum.to_string(),
+                        }],
+                        is_error: false,
+                    })
+                }
+            }
+        )],
+    );
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(fake.env())
+        .mcp_servers(McpServers::Dict(HashMap::from([(
+            "calc".to_string(),
+            McpServerConfig::Sdk(server),
+        )])))
+        .build();
+
+    let mut client = ClaudeClient::new(options);
+    client.connect().await.unwrap();
+    client.query("Add 2 and 3").await.unwrap();
+    collect_response(&mut client).await;
+    client.disconnect().await.unwrap();
+
+    assert_eq!(*calls.lock().unwrap(), 1);
+    let stdin = fake.stdin();
+    let reply = stdin
+        .iter()
+        .find(|line| line["response"]["request_id"] == "req_fake_1")
+        .expect("MCP reply should be written to stdin");
+    let mcp_response = &reply["response"]["response"]["mcp_response"];
+    assert_eq!(mcp_response["id"], 7);
+    assert_eq!(mcp_response["result"]["content"][0]["text"], "5");
+
+    let mcp_config = fake.argv()["args"]
+        .as_array()
+        .unwrap()
+        .windows(2)
+        .find(|w| w[0] == "--mcp-config")
+        .map(|w| w[1].as_str().unwrap().to_string())
+        .unwrap();
+    assert!(mcp_config.contains(r#""type":"sdk""#));
+}
+
+fn oversized_scenario() -> Scenario {
+    let mut scenario = hello_scenario();
+    scenario.exchanges[0].responses.insert(
+        0,
+        ScheduledMessage {
+            value: serde_json::to_value(
+                AssistantMessageBuilder::new()
+                    .text("x".repeat(8 * 1024))
+                    .build(),
+            )
+            .unwrap(),
+            timing: Default::default(),
+        },
+    );
+    scenario
+}
+
+#[tokio::test]
+async fn test_oversized_line_is_skipped_and_session_continues() {
+    let fake = FakeEnv::new(&oversized_scenario());
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .max_buffer_size(4 * 1024)
+        .env(fake.env())
+        .build();
+
+    let mut client = ClaudeClient::new(options);
+    client.connect().await.unwrap();
+    client.query("Hi").await.unwrap();
+    let items: Vec<_> = client.receive_response().collect().await;
+    client.disconnect().await.unwrap();
+
+    let errors: Vec<_> = items
+        .iter()
+        .filter_map(|item| item.as_ref().err())
+        .collect();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        errors[0],
+        ClaudeError::BufferSizeExceeded(e) if e.limit == 4 * 1024 && e.line_bytes > 8 * 1024
+    ));
+    let messages: Vec<Message> = items.into_iter().filter_map(Result::ok).collect();
+    assert_eq!(assistant_text(&messages), "Hello from fake");
+    assert!(matches!(messages.last(), Some(Message::Result(_))));
+}
+
+#[tokio::test]
+async fn test_output_budget_ends_the_stream() {
+    let fake = FakeEnv::new(&oversized_scenario());
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .max_output_bytes(4 * 1024)
+        .env(fake.env())
+        .build();
+
+    let mut client = ClaudeClient::new(options);
+    client.connect().await.unwrap();
+    client.query("Hi").await.unwrap();
+    let items: Vec<_> = client.receive_response().collect().await;
+
+    assert!(matches!(
+        items.last(),
+        Some(Err(ClaudeError::Transport(message))) if message.contains("Output budget exceeded")
+    ));
+    assert!(
+        !items
+            .iter()
+            .any(|item| matches!(item, Ok(Message::Result(_))))
+    );
+}
+
+async fn shutdown_with_linger(linger: Option<&str>) -> ShutdownReport {
+    let fake = FakeEnv::new(&hello_scenario());
+    let mut env = fake.env();
+    if let Some(linger) = linger {
+        env.insert("CLAUDE_FAKE_LINGER".to_string(), linger.to_string());
+    }
+    let step = Duration::from_millis(300);
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(env)
+        .shutdown_policy(
+            ShutdownPolicy::builder()
+                .stdin_grace(step)
+                .term_timeout(step)
+                .kill_timeout(step)
+                .build(),
+        )
+        .build();
+
+    let mut client = ClaudeClient::new(options);
+    client.connect().await.unwrap();
+    client.query("Hi").await.unwrap();
+    collect_response(&mut client).await;
+    client.disconnect().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_shutdown_is_graceful_when_cli_exits_on_eof() {
+    let report = shutdown_with_linger(None).await;
+
+    assert_eq!(report.stage, ShutdownStage::Graceful);
+    assert!(report.exit_status.unwrap().success());
+    assert!(report.is_graceful());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_shutdown_escalates_to_sigterm() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let report = shutdown_with_linger(Some("eof")).await;
+
+    assert_eq!(report.stage, ShutdownStage::Terminated);
+    assert_eq!(report.exit_status.unwrap().signal(), Some(15));
+    assert!(report.elapsed >= Duration::from_millis(300));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_shutdown_escalates_to_sigkill() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let report = shutdown_with_linger(Some("term")).await;
+
+    assert_eq!(report.stage, ShutdownStage::Killed);
+    assert_eq!(report.exit_status.unwrap().signal(), Some(9));
+    assert!(report.elapsed >= Duration::from_millis(600));
+}
+
+/// Run a session whose fake CLI starts a long-lived child, returning the child's PID
+#[cfg(target_os = "linux")]
+async fn session_with_descendant(
+    isolation: ProcessIsolation,
+    kill_on_parent_death: bool,
+) -> (u32, ShutdownReport) {
+    let fake = FakeEnv::new(&hello_scenario());
+    let pid_file = fake.path().join("descendant.pid");
+    let mut env = fake.env();
+    env.insert(
+        "CLAUDE_FAKE_SPAWN_OUT".to_string(),
+        pid_file.display().to_string(),
+    );
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(env)
+        .process_isolation(isolation)
+        .kill_on_parent_death(kill_on_parent_death)
+        .build();
+
+    let mut client = ClaudeClient::new(options);
+    client.connect().await.unwrap();
+    client.query("Hi").await.unwrap();
+    assert!(matches!(
+        collect_response(&mut client).await.last(),
+        Some(Message::Result(_))
+    ));
+    let report = client.disconnect().await.unwrap();
+
+    let pid = std::fs::read_to_string(pid_file).unwrap().parse().unwrap();
+    (pid, report)
+}
+
+/// Whether `pid` exits within a second; zombies awaiting reaping count as exited
+#[cfg(target_os = "linux")]
+async fn descendant_exits(pid: u32) -> bool {
+    for _ in 0..50 {
+        match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            Err(_) => return true,
+            Ok(stat)
+                if stat
+                    .rsplit(')')
+                    .next()
+                    .unwrap()
+                    .trim_start()
+                    .starts_with('Z') =>
+            {
+                return true;
+            }
+            Ok(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+    false
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_descendants_outlive_cli_without_isolation() {
+    let (pid, report) = session_with_descendant(ProcessIsolation::Inherit, false).await;
+
+    assert!(report.is_graceful());
+    assert!(!descendant_exits(pid).await);
+    // Clean up the orphan this test deliberately left behind
+    unsafe {
+        libc::kill(pid as libc::pid_t, libc::SIGKILL);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_process_group_descendants_are_killed_on_disconnect() {
+    let (pid, report) = session_with_descendant(ProcessIsolation::ProcessGroup, false).await;
+
+    assert_eq!(report.stage, ShutdownStage::Graceful);
+    assert!(descendant_exits(pid).await);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_session_descendants_are_killed_on_disconnect() {
+    let (pid, report) = session_with_descendant(ProcessIsolation::Session, true).await;
+
+    assert_eq!(report.stage, ShutdownStage::Graceful);
+    assert!(descendant_exits(pid).await);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_process_limits_are_applied_before_exec() {
+    let fake = FakeEnv::new(&hello_scenario());
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(fake.env())
+        .process_limits(
+            ProcessLimits::builder()
+                .max_address_space(8 * 1024 * 1024 * 1024)
+                .max_cpu_time(Duration::from_millis(59_500))
+                .max_open_files(256)
+                .nice(7)
+                .build(),
+        )
+        .build();
+
+    let mut client = ClaudeClient::new(options);
+    client.connect().await.unwrap();
+    let pid = client.process_id().unwrap();
+
+    let limits = std::fs::read_to_string(format!("/proc/{}/limits", pid)).unwrap();
+    let limit = |name: &str| -> Vec<String> {
+        let line = limits.lines().find(|l| l.starts_with(name)).unwrap();
+        line[name.len()..]
+            .split_whitespace()
+            .take(2)
+            .map(str::to_string)
+            .collect()
+    };
+    assert_eq!(limit("Max open files"), ["256", "256"]);
+    assert_eq!(limit("Max cpu time"), ["60", "60"]);
+    assert_eq!(limit("Max address space"), ["8589934592", "8589934592"]);
+
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+    let nice = stat.rsplit(')').next().unwrap().split_whitespace().nth(16);
+    assert_eq!(nice, Some("7"));
+
+    client.query("Hi").await.unwrap();
+    collect_response(&mut client).await;
+    client.disconnect().await.unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_resource_usage_of_running_cli() {
+    let fake = FakeEnv::new(&hello_scenario());
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(fake.env())
+        .build();
+
+    let mut client = ClaudeClient::new(options);
+    assert!(client.process_id().is_none());
+    assert!(matches!(
+        client.resource_usage(),
+        Err(ClaudeError::InvalidConfig(_))
+    ));
+
+    client.connect().await.unwrap();
+    let usage = client.resource_usage().unwrap();
+    assert_eq!(Some(usage.pid), client.process_id());
+    assert!(usage.rss_bytes > 0);
+    assert!(usage.peak_rss_bytes >= usage.rss_bytes);
+    assert!(usage.virtual_bytes >= usage.rss_bytes);
+    assert!(usage.threads >= 1);
+    assert!(usage.open_files >= 3);
+
+    client.disconnect().await.unwrap();
+    assert!(client.process_id().is_none());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_invalid_process_limits_fail_connect() {
+    let fake = FakeEnv::new(&hello_scenario());
+    let connect = |limits: ProcessLimits| {
+        let options = ClaudeAgentOptions::builder()
+            .cli_path(FAKE_CLI)
+            .env(fake.env())
+            .process_limits(limits)
+            .build();
+        async move { ClaudeClient::new(options).connect().await.unwrap_err() }
+    };
+
+    let err = connect(ProcessLimits::builder().nice(40).build()).await;
+    assert!(matches!(err, ClaudeError::InvalidOptions(_)), "{err}");
+
+    let err = connect(
+        ProcessLimits::builder()
+            .cgroup(fake.path().join("not-a-cgroup"))
+            .build(),
+    )
+    .await;
+    assert!(matches!(err, ClaudeError::InvalidConfig(_)), "{err}");
+}
+
+#[tokio::test]
+async fn test_status_and_ping() {
+    let fake = FakeEnv::new(&hello_scenario());
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(fake.env())
+        .build();
+
+    let mut client = ClaudeClient::new(options);
+    let status = client.status();
+    assert!(!status.connected);
+    assert!(status.process.is_none());
+    assert!(client.ping().await.is_err());
+
+    client.connect().await.unwrap();
+    client.ping().await.unwrap();
+    assert!(
+        fake.stdin()
+            .iter()
+            .any(|line| line["request"]["subtype"] == "mcp_status")
+    );
+
+    client.query("Hi").await.unwrap();
+    collect_response(&mut client).await;
+
+    let status = client.status();
+    assert!(status.connected);
+    assert!(status.transport_ready);
+    assert_eq!(status.pending_control_requests, 0);
+    assert_eq!(status.queued_messages, 0);
+    assert!(status.last_activity.unwrap() <= std::time::SystemTime::now());
+    let process = status.process.unwrap();
+    assert_eq!(Some(process.pid), client.process_id());
+    assert_eq!(process.cli_path, PathBuf::from(FAKE_CLI));
+    assert_eq!(process.cli_version.as_deref(), Some("2.0.45"));
+    assert!(process.started_at <= status.last_activity.unwrap());
+
+    client.disconnect().await.unwrap();
+    assert!(!client.status().connected);
+}
+
+#[test]
+fn test_cli_locator_probes_explicit_path_first() {
+    let locator = CliLocator::builder().cli_path(FAKE_CLI).build();
+
+    let candidates = locator.candidates();
+    assert_eq!(candidates[0].path, PathBuf::from(FAKE_CLI));
+    assert_eq!(candidates[0].source, CliSource::Explicit);
+    assert_eq!(candidates[0].version, Some(CliVersion::new(2, 0, 45)));
+    assert_eq!(
+        candidates[0].version_output.as_deref(),
+        Some("2.0.45 (Claude Code)")
+    );
+    assert_eq!(
+        candidates[0].capabilities().unwrap().features(),
+        CliFeature::ALL.to_vec()
+    );
+
+    let located = locator.locate().unwrap();
+    assert_eq!(located.path, PathBuf::from(FAKE_CLI));
+}
+
+/// Write an executable wrapper around the fake CLI that reports `version`
+///
+/// CLI versions are cached per executable, so each reported version needs its own path.
+/// Every `--version` run is logged to `version-probes.log` next to the wrapper.
+#[cfg(unix)]
+fn fake_cli_reporting(dir: &std::path::Path, version: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = dir.join(format!("claude-{}", uuid::Uuid::new_v4().simple()));
+    let log = dir.join("version-probes.log");
+    std::fs::write(
+        &path,
+        format!(
+            "#!/bin/sh\n[ \"$1\" = --version ] && echo probe >> '{}'\nCLAUDE_FAKE_VERSION='{}' exec '{}' \"$@\"\n",
+            log.display(),
+            version,
+            FAKE_CLI
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[cfg(unix)]
+async fn connect_with_cli_version(
+    reported: &str,
+    configure: impl FnOnce(ClaudeAgentOptions) -> ClaudeAgentOptions,
+) -> claude_agent_sdk_rs::Result<()> {
+    let fake = FakeEnv::new(&hello_scenario());
+    let options = configure(
+        ClaudeAgentOptions::builder()
+            .cli_path(fake_cli_reporting(fake.path(), reported))
+            .env(fake.env())
+            .build(),
+    );
+
+    let mut client = ClaudeClient::new(options);
+    client.connect().await?;
+    client.disconnect().await?;
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_cli_version_bounds_are_enforced_on_connect() {
+    let too_old = connect_with_cli_version("2.0.5 (Claude Code)", |mut options| {
+        options.min_cli_version = Some(CliVersion::new(2, 0, 10));
+        options
+    })
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(&too_old, ClaudeError::CliVersion(e) if e.version.as_deref() == Some("2.0.5")),
+        "{too_old}"
+    );
+
+    // Explicit bounds are enforced even when the version check is skipped
+    let skipped = connect_with_cli_version("2.0.5 (Claude Code)", |mut options| {
+        options.min_cli_version = Some(CliVersion::new(2, 0, 10));
+        options.skip_version_check = true;
+        options
+    })
+    .await
+    .unwrap_err();
+    assert!(matches!(skipped, ClaudeError::CliVersion(_)), "{skipped}");
+
+    let too_new = connect_with_cli_version("3.1.0 (Claude Code)", |mut options| {
+        options.max_cli_version = Some(CliVersion::new(2, 99, 0));
+        options
+    })
+    .await
+    .unwrap_err();
+    assert!(
+        too_new.to_string().contains("maximum is 2.99.0"),
+        "{too_new}"
+    );
+
+    connect_with_cli_version("2.5.0 (Claude Code)", |mut options| {
+        options.min_cli_version = Some(CliVersion::new(2, 0, 10));
+        options.max_cli_version = Some(CliVersion::new(2, 99, 0));
+        options
+    })
+    .await
+    .unwrap();
+    let fake = FakeEnv::new(&hello_scenario());
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(fake_cli_reporting(fake.path(), "2.0.5 (Claude Code)"))
+        .min_cli_version(CliVersion::new(2, 0, 10))
+        .build();
+    let err = claude_agent_sdk_rs::cli::prewarm(&options)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClaudeError::CliVersion(_)), "{err}");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_cli_locator_locate_async_applies_bounds() {
+    let fake = FakeEnv::new(&hello_scenario());
+    let cli = fake_cli_reporting(fake.path(), "2.5.0 (Claude Code)");
+    let locator = CliLocator::builder()
+        .cli_path(&cli)
+        .min_version(CliVersion::new(2, 0, 10))
+        .build();
+
+    let located = locator.locate_async().await.unwrap();
+    assert_eq!(located.path, cli);
+    assert_eq!(located.source, CliSource::Explicit);
+    assert_eq!(located.version, Some(CliVersion::new(2, 5, 0)));
+    assert_eq!(locator.locate().unwrap(), located);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_options_unsupported_by_cli_are_rejected_before_spawn() {
+    let with_schema = |mut options: ClaudeAgentOptions| {
+        options.output_format =
+            Some(serde_json::json!({"type": "json_schema", "schema": {"type": "object"}}));
+        options
+    };
+
+    let err = connect_with_cli_version("2.0.20 (Claude Code)", with_schema)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClaudeError::CliVersion(_)), "{err}");
+    assert!(err.to_string().contains("json_schema"), "{err}");
+
+    connect_with_cli_version("2.0.45 (Claude Code)", with_schema)
+        .await
+        .unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_cli_version_is_probed_once_per_executable() {
+    let fake = FakeEnv::new(&hello_scenario());
+    let cli = fake_cli_reporting(fake.path(), "2.1.0 (Claude Code)");
+    let probes = || {
+        std::fs::read_to_string(fake.path().join("version-probes.log"))
+            .unwrap_or_default()
+            .lines()
+            .count()
+    };
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(&cli)
+        .env(fake.env())
+        .build();
+
+    let candidate = claude_agent_sdk_rs::cli::prewarm(&options).await.unwrap();
+    assert_eq!(candidate.version, Some(CliVersion::new(2, 1, 0)));
+    assert_eq!(probes(), 1);
+
+    for _ in 0..3 {
+        let mut client = ClaudeClient::new(options.clone());
+        client.connect().await.unwrap();
+        assert_eq!(
+            client.status().process.unwrap().cli_version.as_deref(),
+            Some("2.1.0")
+        );
+        client.disconnect().await.unwrap();
+    }
+    assert_eq!(probes(), 1);
+
+    // Replacing the executable invalidates its entry
+    let later = std::time::SystemTime::now() + Duration::from_secs(60);
+    std::fs::File::options()
+        .write(true)
+        .open(&cli)
+        .unwrap()
+        .set_modified(later)
+        .unwrap();
+    claude_agent_sdk_rs::cli::prewarm(&options).await.unwrap();
+    assert_eq!(probes(), 2);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_cli_version_probe_uses_env_but_is_shared_across_envs() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // A wrapper of its own, so no other test has cached this executable's version
+    let fake = FakeEnv::new(&hello_scenario());
+    let cli = fake.path().join("claude");
+    std::fs::write(&cli, format!("#!/bin/sh\nexec '{}' \"$@\"\n", FAKE_CLI)).unwrap();
+    std::fs::set_permissions(&cli, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let reporting =
+        |version: &str| HashMap::from([("CLAUDE_FAKE_VERSION".to_string(), version.to_string())]);
+
+    let first =
+        claude_agent_sdk_rs::cli::version_output(&cli, &reporting("2.3.4 (Claude Code)")).await;
+    let second =
+        claude_agent_sdk_rs::cli::version_output(&cli, &reporting("2.5.6 (Claude Code)")).await;
+    assert_eq!(first.as_deref(), Some("2.3.4 (Claude Code)"));
+    // The cache is keyed by executable only, so other clients reuse the first probe
+    assert_eq!(second.as_deref(), Some("2.3.4 (Claude Code)"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_concurrent_blocking_probes_run_once() {
+    let fake = FakeEnv::new(&hello_scenario());
+    let cli = fake_cli_reporting(fake.path(), "2.2.0 (Claude Code)");
+    let locator = CliLocator::builder().cli_path(&cli).build();
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| assert_eq!(locator.candidates()[0].path, cli));
+        }
+    });
+
+    let probes = std::fs::read_to_string(fake.path().join("version-probes.log")).unwrap();
+    assert_eq!(probes.lines().count(), 1);
+}
+
+#[tokio::test]
+async fn test_one_shot_query_reports_oversized_lines() {
+    let scenario = ScenarioBuilder::new("oversized")
+        .on_connect(SystemMessageBuilder::default().build())
+        .exchange()
+        .respond(
+            AssistantMessageBuilder::new()
+                .text("x".repeat(8192))
+                .build(),
+        )
+        .respond(AssistantMessageBuilder::new().text("fits").build())
+        .then_result(ResultMessageBuilder::default().build())
+        .build();
+    let fake = FakeEnv::new(&scenario);
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(fake.env())
+        .max_buffer_size(4096)
+        .build();
+
+    // A collected result with a message missing is an error, not a short Ok
+    let err = query("Hi", Some(options.clone())).await.unwrap_err();
+    assert!(matches!(err, ClaudeError::BufferSizeExceeded(e) if e.limit == 4096));
+
+    let items: Vec<_> = claude_agent_sdk_rs::query_stream("Hi", Some(options))
+        .await
+        .unwrap()
+        .collect()
+        .await;
+    assert!(matches!(items[1], Err(ClaudeError::BufferSizeExceeded(_))));
+    assert!(matches!(items.last(), Some(Ok(Message::Result(_)))));
+}
+
+#[tokio::test]
+async fn test_stderr_is_forwarded() {
+    let fake = FakeEnv::new(&hello_scenario());
+    let mut env = fake.env();
+    env.insert(
+        "CLAUDE_FAKE_STDERR".to_string(),
+        "warning: fake".to_string(),
+    );
+    env.insert("CLAUDE_FAKE_EXIT_CODE".to_string(), "1".to_string());
+
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let captured = Arc::clone(&lines);
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(env)
+        .stderr_callback(Arc::new(move |line: String| {
+            captured.lock().unwrap().push(line);
+        }))
+        .build();
+
+    let messages = query("Hi", Some(options)).await.unwrap();
+
+    assert!(matches!(messages.last(), Some(Message::Result(_))));
+    assert!(
+        lines
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|l| l.contains("warning: fake"))
+    );
+}
+
+#[tokio::test]
+async fn test_cassette_record_then_replay() {
+    let fake = FakeEnv::new(&hello_scenario());
+    let cassette = fake.path().join("cassettes").join("hello.json");
+    let mut env = fake.env();
+    env.insert(
+        "ANTHROPIC_API_KEY".to_string(),
+        "sk-ant-test-secret".to_string(),
+    );
+
+    // Record through the fake CLI
+    let options = ClaudeAgentOptions::builder()
+        .cli_path(FAKE_CLI)
+        .env(env)
+        .build();
+    let mut client = ClaudeClient::with_cassette(options, &cassette, CassetteMode::RecordIfMissing);
+    client.connect().await.unwrap();
+    client.query("Hi").await.unwrap();
+    let recorded = collect_response(&mut client).await;
+    client.disconnect().await.unwrap();
+
+    let saved = std::fs::read_to_string(&cassette).unwrap();
+    assert!(!saved.contains("sk-ant-test-secret"));
+    assert!(
+        !SnapshotPlayer::load(&cassette)
+            .unwrap()
+            .sent_messages()
+            .is_empty()
+    );
+
+    // Replay without any CLI
+    let options = ClaudeAgentOptions::builder()
+        .cli_path("/nonexistent/claude")
+        .build();
+    let mut client = ClaudeClient::with_cassette(options, &cassette, CassetteMode::RecordIfMissing);
+    client.connect().await.unwrap();
+    client.query("Hi").await.unwrap();
+    let replayed = collect_response(&mut client).await;
+    client.disconnect().await.unwrap();
+
+    assert_eq!(assistant_text(&replayed), assistant_text(&recorded));
+    assert_eq!(replayed.len(), recorded.len());
+
+    // A different prompt diverges from the recording
+    let mut client = ClaudeClient::with_cassette(
+        ClaudeAgentOptions::default(),
+        &cassette,
+        CassetteMode::Replay,
+    );
+    client.connect().await.unwrap();
+    let err = client.query("Bye").await.unwrap_err().to_string();
+    assert!(err.contains("Cassette divergence"));
+    assert!(err.contains(r#"+ $.message.content: "Bye""#));
+    client.disconnect().await.unwrap();
+}
@@ -7,12 +7,13 @@ use claude_agent_sdk_rs::testing::{
     MessageDirection, MockClient, MockTransport, PermissionRecorder, ScenarioBuilder,
     SnapshotPlayer, SnapshotRecorder, Transport,
     builders::{
-        AssistantMessageBuilder, ResultMessageBuilder, SystemMessageBuilder, ToolResultBuilder,
+        AssistantMessageBuilder, ControlCancelRequestBuilder, ResultMessageBuilder,
+        StreamEventBuilder, SystemMessageBuilder, ToolResultBuilder, UserMessageBuilder,
     },
     timing_profiles,
 };
 use claude_agent_sdk_rs::types::permissions::{PermissionResult, ToolPermissionContext};
-use claude_agent_sdk_rs::{ClaudeAgentOptions, Message};
+use claude_agent_sdk_rs::{ClaudeAgentOptions, ContentBlock, Message};
 use futures::StreamExt;
 use std::time::Duration;
 
@@ -272,6 +273,59 @@ fn test_result_message_builder_error() {
     }
 }
 
+#[tokio::test]
+async fn test_stream_event_builder_replays_partial_messages() {
+    let assistant = AssistantMessageBuilder::new()
+        .text("Streaming works")
+        .session_id("stream-session")
+        .build();
+    let Message::Assistant(ref inner) = assistant else {
+        unreachable!()
+    };
+
+    let mut scenario = ScenarioBuilder::new("partial_messages")
+        .timing(timing_profiles::instant())
+        .exchange();
+    for event in StreamEventBuilder::new()
+        .chunk_size(5)
+        .assistant(inner)
+        .build()
+    {
+        scenario = scenario.respond(event);
+    }
+    let scenario = scenario
+        .respond(assistant.clone())
+        .respond(
+            UserMessageBuilder::new()
+                .tool_result("toolu_1", "ok")
+                .build(),
+        )
+        .respond(ControlCancelRequestBuilder::new("req_1").build())
+        .then_result(ResultMessageBuilder::default().build())
+        .build();
+
+    let mut client = MockClient::from_scenario(scenario);
+    client.connect_with_transport().await.unwrap();
+    client.query("Stream please").await.unwrap();
+    let messages: Vec<Message> = client
+        .receive_response()
+        .map(|m| m.unwrap())
+        .collect()
+        .await;
+    client.disconnect().await.unwrap();
+
+    let streamed: String = messages
+        .iter()
+        .filter_map(|m| match m {
+            Message::StreamEvent(event) => event.event["delta"]["text"].as_str(),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(streamed, "Streaming works");
+    assert!(messages.iter().any(|m| matches!(m, Message::User(_))));
+    assert!(matches!(messages.last(), Some(Message::Result(_))));
+}
+
 // =============================================================================
 // Scenario Builder Tests
 // =============================================================================
@@ -553,6 +607,76 @@ async fn test_snapshot_to_mock_transport() {
     assert_eq!(msg2["type"], "assistant");
 }
 
+#[tokio::test]
+async fn test_scenario_file_drives_mock_client() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/scenarios/read_file.yaml"
+    );
+    let mut client = MockClient::from_scenario_file(path).unwrap();
+    client.connect_with_transport().await.unwrap();
+
+    client
+        .query("What is the capital of France?")
+        .await
+        .unwrap();
+    let first: Vec<_> = client.receive_response().collect().await;
+    assert!(matches!(
+        first.last(),
+        Some(Ok(Message::Result(r))) if r.num_turns == 1
+    ));
+
+    client.query("Read /etc/hosts").await.unwrap();
+    let second: Vec<_> = client.receive_response().collect().await;
+    assert!(
+        second
+            .iter()
+            .any(|m| matches!(m, Ok(Message::Assistant(_))))
+    );
+    assert!(matches!(
+        second.last(),
+        Some(Ok(Message::Result(r))) if r.total_cost_usd == Some(0.002)
+    ));
+
+    client.assert_expected_writes();
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_scenario_transcript_matches_golden() {
+    use claude_agent_sdk_rs::testing::Transcript;
+
+    let mut client = MockClient::from_scenario_file(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/scenarios/read_file.yaml"
+    ))
+    .unwrap();
+    client.connect_with_transport().await.unwrap();
+
+    let mut messages = Vec::new();
+    for prompt in ["What is the capital of France?", "Read /etc/hosts"] {
+        client.query(prompt).await.unwrap();
+        let response: Vec<_> = client.receive_response().collect().await;
+        messages.extend(response.into_iter().map(Result::unwrap));
+    }
+    client.disconnect().await.unwrap();
+
+    Transcript::from_messages(&messages).assert_golden(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/golden/read_file.golden"
+    ));
+}
+
+#[test]
+#[should_panic(expected = "expected a write containing")]
+fn test_assert_expected_writes_reports_missing_write() {
+    let scenario = ScenarioBuilder::new("missing")
+        .exchange()
+        .expect_write("never sent")
+        .build();
+    MockClient::from_scenario(scenario).assert_expected_writes();
+}
+
 // =============================================================================
 // Integration: Full Conversation Flow
 // =============================================================================
@@ -618,6 +742,526 @@ async fn test_full_conversation_flow_with_mock() {
     client.disconnect().await.unwrap();
 }
 
+// =============================================================================
+// SDK MCP Tool Context Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_sdk_tool_progress_is_sent_over_mcp_message_channel() {
+    use claude_agent_sdk_rs::{
+        McpServerConfig, McpServers, McpToolResultContent, ToolContext, ToolResult,
+        create_sdk_mcp_server, tool_with_context,
+    };
+    use std::collections::HashMap;
+
+    let server = create_sdk_mcp_server(
+        "build-tools",
+        "1.0.0",
+        vec![tool_with_context!(
+            "build",
+            "Run the build",
+            serde_json::json!({"type": "object"}),
+            |_args: serde_json::Value, ctx: ToolContext| async move {
+                ctx.report_progress(50.0, Some(100.0), Some("linking"))
+                    .await?;
+                Ok(ToolResult {
+                    content: vec![McpToolResultContent::Text {
+                        text: "built".to_string(),
+                    }],
+                    is_error: false,
+                })
+            }
+        )],
+    );
+
+    let options = ClaudeAgentOptions {
+        mcp_servers: McpServers::Dict(HashMap::from([(
+            "build-tools".to_string(),
+            McpServerConfig::Sdk(server),
+        )])),
+        ..Default::default()
+    };
+    let mut client = MockClient::from_transport(MockTransport::builder().build(), options);
+    client.connect_with_transport().await.unwrap();
+
+    client.transport().inject(serde_json::json!({
+        "type": "control_request",
+        "request_id": "cli_req_1",
+        "request": {
+            "subtype": "mcp_message",
+            "server_name": "build-tools",
+            "message": {
+                "jsonrpc": "2.0",
+                "id": 3,
+                "method": "tools/call",
+                "params": {
+                    "name": "build",
+                    "arguments": {},
+                    "_meta": {"progressToken": "progress-3"}
+                }
+            }
+        }
+    }));
+
+    let mut written = Vec::new();
+    for _ in 0..50 {
+        written = client.transport().written_messages_async().await;
+        if written.len() >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let progress = written
+        .iter()
+        .filter_map(|w| w.parsed.as_ref())
+        .find(|v| v["request"]["message"]["method"] == "notifications/progress")
+        .expect("progress notification should be written");
+    assert_eq!(progress["type"], "control_request");
+    assert_eq!(progress["request"]["subtype"], "mcp_message");
+    assert_eq!(progress["request"]["server_name"], "build-tools");
+    assert_eq!(
+        progress["request"]["message"]["params"]["progressToken"],
+        "progress-3"
+    );
+
+    let response = written
+        .iter()
+        .filter_map(|w| w.parsed.as_ref())
+        .find(|v| v["type"] == "control_response")
+        .expect("tool call response should be written");
+    assert_eq!(response["response"]["request_id"], "cli_req_1");
+    assert_eq!(
+        response["response"]["response"]["mcp_response"]["result"]["content"][0]["text"],
+        "built"
+    );
+
+    client.disconnect().await.unwrap();
+}
+
+// =============================================================================
+// CLI Simulator Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_simulator_drives_pre_tool_use_hook() {
+    use claude_agent_sdk_rs::testing::{CliSimulator, HookRecorder};
+    use claude_agent_sdk_rs::{ClaudeClient, HookEvent, HookMatcher};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    let transport = Arc::new(MockTransport::builder().build());
+    let simulator = CliSimulator::attach(Arc::clone(&transport));
+
+    let recorder = HookRecorder::new();
+    let options = ClaudeAgentOptions::builder()
+        .hooks(HashMap::from([(
+            HookEvent::PreToolUse,
+            vec![
+                HookMatcher::builder()
+                    .matcher("Bash")
+                    .hooks(vec![recorder.pre_tool_use_callback()])
+                    .build(),
+            ],
+        )]))
+        .build();
+    let mut client = ClaudeClient::with_transport(transport, options);
+    client
+        .connect_with_transport_and_initialize()
+        .await
+        .unwrap();
+
+    let init = simulator.initialize_request().unwrap();
+    assert_eq!(init["request"]["hooks"]["PreToolUse"][0]["matcher"], "Bash");
+
+    let output = simulator
+        .hook_event(
+            "PreToolUse",
+            serde_json::json!({"tool_name": "Bash", "tool_input": {"command": "ls"}}),
+        )
+        .await
+        .unwrap();
+    assert!(output.is_object());
+    recorder.assert_tool_used("Bash").await;
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_simulator_calls_sdk_mcp_tool() {
+    use claude_agent_sdk_rs::testing::CliSimulator;
+    use claude_agent_sdk_rs::{
+        ClaudeClient, McpServerConfig, McpServers, McpToolResultContent, ToolResult,
+        create_sdk_mcp_server, tool,
+    };
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    let server = create_sdk_mcp_server(
+        "calc",
+        "1.0.0",
+        vec![tool!(
+            "add",
+            "Add two numbers",
+            serde_json::json!({"type": "object"}),
+            |args: serde_json::Value| async move {
+                let sum = args["a"].as_i64().unwrap() + args["b"].as_i64().unwrap();
+                Ok(ToolResult {
+                    content: vec![McpToolResultContent::Text {
+                        text: sum.to_string(),
+                    }],
+                    is_error: false,
+                })
+            }
+        )],
+    );
+    let options = ClaudeAgentOptions::builder()
+        .mcp_servers(McpServers::Dict(HashMap::from([(
+            "calc".to_string(),
+            McpServerConfig::Sdk(server),
+        )])))
+        .build();
+
+    let transport = Arc::new(MockTransport::builder().build());
+    let simulator = CliSimulator::attach(Arc::clone(&transport));
+    let mut client = ClaudeClient::with_transport(transport, options);
+    client
+        .connect_with_transport_and_initialize()
+        .await
+        .unwrap();
+
+    let response = simulator
+        .call_tool("calc", "add", serde_json::json!({"a": 2, "b": 3}))
+        .await
+        .unwrap();
+    assert_eq!(response["result"]["content"][0]["text"], "5");
+
+    let response = simulator
+        .mcp_message(
+            "calc",
+            serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response["result"]["tools"][0]["name"], "add");
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_simulator_can_use_tool_allow_and_deny() {
+    use claude_agent_sdk_rs::ClaudeClient;
+    use claude_agent_sdk_rs::testing::CliSimulator;
+    use std::sync::Arc;
+
+    let recorder = PermissionRecorder::allow_tools(&["Read"]);
+    let options = ClaudeAgentOptions::builder()
+        .can_use_tool(recorder.as_callback())
+        .build();
+
+    let transport = Arc::new(MockTransport::builder().build());
+    let simulator = CliSimulator::attach(Arc::clone(&transport));
+    let mut client = ClaudeClient::with_transport(transport, options);
+    client
+        .connect_with_transport_and_initialize()
+        .await
+        .unwrap();
+
+    let allowed = simulator
+        .can_use_tool("Read", serde_json::json!({"file_path": "/tmp/a"}))
+        .await
+        .unwrap();
+    assert_eq!(allowed["behavior"], "allow");
+    assert_eq!(allowed["updatedInput"]["file_path"], "/tmp/a");
+
+    let denied = simulator
+        .can_use_tool("Bash", serde_json::json!({"command": "rm -rf /"}))
+        .await
+        .unwrap();
+    assert_eq!(denied["behavior"], "deny");
+
+    recorder.assert_asked("Read").await;
+    recorder.assert_asked("Bash").await;
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_session_assertions_over_simulated_session() {
+    use claude_agent_sdk_rs::testing::{CliSimulator, HookRecorder, SessionAssertions};
+    use claude_agent_sdk_rs::{ClaudeClient, HookEvent, HookMatcher, PermissionMode};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    let hooks = HookRecorder::new();
+    let permissions = PermissionRecorder::allow_tools(&["Read"]);
+    let options = ClaudeAgentOptions::builder()
+        .hooks(HashMap::from([(
+            HookEvent::PreToolUse,
+            vec![
+                HookMatcher::builder()
+                    .hooks(vec![hooks.pre_tool_use_callback()])
+                    .build(),
+            ],
+        )]))
+        .can_use_tool(permissions.as_callback())
+        .build();
+
+    let transport = Arc::new(MockTransport::builder().build());
+    let simulator = CliSimulator::attach(Arc::clone(&transport));
+    let mut client = ClaudeClient::with_transport(Arc::clone(&transport) as _, options);
+    client
+        .connect_with_transport_and_initialize()
+        .await
+        .unwrap();
+    client
+        .set_permission_mode(PermissionMode::AcceptEdits)
+        .await
+        .unwrap();
+
+    simulator
+        .hook_event(
+            "PreToolUse",
+            serde_json::json!({"tool_name": "Read", "tool_input": {"file_path": "/tmp/a"}}),
+        )
+        .await
+        .unwrap();
+    simulator
+        .can_use_tool("Bash", serde_json::json!({"command": "rm -rf /"}))
+        .await
+        .unwrap();
+    simulator.emit(
+        serde_json::to_value(
+            AssistantMessageBuilder::new()
+                .tool_use("Read", serde_json::json!({"file_path": "/tmp/a"}))
+                .build(),
+        )
+        .unwrap(),
+    );
+    simulator.emit(
+        serde_json::to_value(ResultMessageBuilder::default().cost_usd(0.01).build()).unwrap(),
+    );
+
+    let messages: Vec<Message> = client
+        .receive_response()
+        .filter_map(|m| async { m.ok() })
+        .collect()
+        .await;
+
+    SessionAssertions::new(messages)
+        .writes(&transport.written_messages())
+        .hooks(hooks.invocations().await)
+        .permissions(permissions.decisions().await)
+        .tools_called_exactly(&["Read"])
+        .tool_not_called("Bash")
+        .hook_fired_with(HookEvent::PreToolUse, |h| {
+            h.tool_name.as_deref() == Some("Read")
+        })
+        .permission_denied("Bash")
+        .permission_mode_set(PermissionMode::AcceptEdits)
+        .result_is_success()
+        .total_cost_below(0.05);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_simulator_reports_sdk_errors() {
+    use claude_agent_sdk_rs::ClaudeClient;
+    use claude_agent_sdk_rs::testing::CliSimulator;
+    use std::sync::Arc;
+
+    let transport = Arc::new(MockTransport::builder().build());
+    let simulator = CliSimulator::attach(Arc::clone(&transport));
+    let mut client = ClaudeClient::with_transport(transport, ClaudeAgentOptions::default());
+    client
+        .connect_with_transport_and_initialize()
+        .await
+        .unwrap();
+
+    // No can_use_tool callback configured
+    let err = simulator
+        .can_use_tool("Read", serde_json::json!({}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("can_use_tool"));
+
+    // Unknown MCP server
+    let err = simulator
+        .call_tool("missing", "add", serde_json::json!({}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("missing"));
+
+    client.disconnect().await.unwrap();
+}
+
+// =============================================================================
+// Fault Injection Tests
+// =============================================================================
+
+fn faulty_conversation(plan: claude_agent_sdk_rs::testing::FaultPlan) -> MockTransport {
+    MockTransport::builder()
+        .message(SystemMessageBuilder::default().build_json())
+        .message_after_write(
+            AssistantMessageBuilder::new()
+                .text("Still here")
+                .build_json(),
+            "user",
+        )
+        .message_after_write(ResultMessageBuilder::default().build_json(), "user")
+        .faults(plan)
+        .seed(99)
+        .speed_factor(0.0)
+        .build()
+}
+
+async fn collect_with_timeout(
+    client: &mut MockClient,
+) -> Vec<claude_agent_sdk_rs::Result<Message>> {
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        client.client().receive_response().collect::<Vec<_>>(),
+    )
+    .await
+    .expect("response stream should not hang")
+}
+
+#[tokio::test]
+async fn test_fault_garbage_and_split_lines_do_not_break_session() {
+    use claude_agent_sdk_rs::testing::FaultPlan;
+
+    let plan = FaultPlan::new()
+        .garbage_line(0, "Debugger attached.")
+        .garbage_line(1, "{\"truncated\": ")
+        .split_message(1, 5)
+        .slow_reads(1, 3);
+    let mut client = MockClient::from_transport(faulty_conversation(plan), Default::default());
+    client.connect_with_transport().await.unwrap();
+    client.query("Hi").await.unwrap();
+
+    let messages = collect_with_timeout(&mut client).await;
+    let messages: Vec<Message> = messages.into_iter().map(|m| m.unwrap()).collect();
+    assert!(matches!(messages[0], Message::System(_)));
+    assert!(matches!(messages[1], Message::Assistant(_)));
+    assert!(matches!(messages.last(), Some(Message::Result(_))));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fault_split_message_reaches_client_intact() {
+    use claude_agent_sdk_rs::testing::FaultPlan;
+
+    let text = "Ünïcödé survives being split ✓";
+    let transport = MockTransport::builder()
+        .message(AssistantMessageBuilder::new().text(text).build_json())
+        .message(ResultMessageBuilder::default().build_json())
+        .faults(FaultPlan::new().split_message(0, 12))
+        .seed(3)
+        .speed_factor(0.0)
+        .build();
+    let mut client = MockClient::from_transport(transport, Default::default());
+    client.connect_with_transport().await.unwrap();
+
+    let messages = collect_with_timeout(&mut client).await;
+    let Ok(Message::Assistant(assistant)) = &messages[0] else {
+        panic!(
+            "expected the split assistant message, got {:?}",
+            messages[0]
+        );
+    };
+    assert!(matches!(
+        &assistant.message.content[0],
+        ContentBlock::Text(block) if block.text == text
+    ));
+    assert!(matches!(messages.last(), Some(Ok(Message::Result(_)))));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fault_disconnect_mid_message_ends_stream() {
+    use claude_agent_sdk_rs::testing::FaultPlan;
+
+    let plan = FaultPlan::new().disconnect_mid_message(2);
+    let mut client = MockClient::from_transport(faulty_conversation(plan), Default::default());
+    client.connect_with_transport().await.unwrap();
+    client.query("Hi").await.unwrap();
+
+    let messages = collect_with_timeout(&mut client).await;
+    assert_eq!(messages.len(), 2);
+    assert!(!messages.iter().any(|m| matches!(m, Ok(Message::Result(_)))));
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ping_times_out_when_cli_does_not_answer() {
+    use claude_agent_sdk_rs::{ClaudeClient, ClaudeError};
+    use std::sync::Arc;
+
+    let transport = Arc::new(MockTransport::builder().build());
+    let mut client = ClaudeClient::with_transport(transport, ClaudeAgentOptions::default());
+    client.connect_with_transport().await.unwrap();
+
+    let err = client
+        .ping_with_timeout(Duration::from_millis(20))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClaudeError::Timeout(_)), "{err}");
+    assert_eq!(client.status().pending_control_requests, 0);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fault_write_failure_surfaces_as_error() {
+    use claude_agent_sdk_rs::testing::FaultPlan;
+
+    let plan = FaultPlan::new().fail_writes_after(1);
+    let mut client = MockClient::from_transport(faulty_conversation(plan), Default::default());
+    client.connect_with_transport().await.unwrap();
+
+    client.query("first").await.unwrap();
+    let err = client.query("second").await.unwrap_err();
+    assert!(err.to_string().contains("Broken pipe"));
+    assert_eq!(client.written_messages().len(), 1);
+
+    client.disconnect().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fault_process_exit_fails_pending_control_request() {
+    use claude_agent_sdk_rs::testing::FaultPlan;
+
+    let plan = FaultPlan::new().exit_after_writes(1, 137);
+    let mut client = MockClient::from_transport(faulty_conversation(plan), Default::default());
+    client.connect_with_transport().await.unwrap();
+
+    let result = tokio::time::timeout(Duration::from_secs(5), client.client().interrupt())
+        .await
+        .expect("pending control request should fail, not hang");
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("closed the connection")
+    );
+
+    let messages = collect_with_timeout(&mut client).await;
+    let last = messages.last().expect("process error should be reported");
+    assert!(
+        last.as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("exit code 137")
+    );
+
+    assert!(client.query("again").await.is_err());
+    client.disconnect().await.unwrap();
+}
+
 // =============================================================================
 // Edge Cases
 // =============================================================================
@@ -0,0 +1,364 @@
+#![cfg(feature = "proptest")]
+//! Property-based serde tests for the wire types
+//!
+//! Every generated value must survive a serialize → deserialize round trip through
+//! JSON text unchanged, and must serialize using only the field names the CLI
+//! expects, so a missing or wrong `#[serde(rename)]` fails here instead of at runtime.
+
+use claude_agent_sdk_rs::types::config::SandboxSettings;
+use claude_agent_sdk_rs::types::hooks::{HookInput, HookJsonOutput};
+use claude_agent_sdk_rs::types::permissions::PermissionUpdate;
+use claude_agent_sdk_rs::{ContentBlock, Message};
+use proptest::prelude::*;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Round-trip through JSON text and compare the resulting JSON values
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> (Value, Value) {
+    let text = serde_json::to_string(value).unwrap();
+    let parsed: T = serde_json::from_str(&text)
+        .unwrap_or_else(|e| panic!("failed to deserialize {}: {}", text, e));
+    (
+        serde_json::to_value(value).unwrap(),
+        serde_json::to_value(&parsed).unwrap(),
+    )
+}
+
+/// Assert that every key of `value` (an object) is one of `allowed`
+fn assert_keys(context: &str, value: &Value, allowed: &[&str]) {
+    let object = value
+        .as_object()
+        .unwrap_or_else(|| panic!("{} should be an object: {}", context, value));
+    for key in object.keys() {
+        assert!(
+            allowed.contains(&key.as_str()),
+            "{}: unexpected wire field '{}' in {}",
+            context,
+            key,
+            value
+        );
+    }
+}
+
+fn assert_one_of(context: &str, value: &Value, allowed: &[&str]) {
+    let s = value.as_str().unwrap_or_default();
+    assert!(
+        allowed.contains(&s),
+        "{}: unexpected value {} (expected one of {:?})",
+        context,
+        value,
+        allowed
+    );
+}
+
+fn is_extra(key: &str) -> bool {
+    key.starts_with("x_")
+}
+
+const PERMISSION_MODES: &[&str] = &["default", "acceptEdits", "plan", "bypassPermissions"];
+
+fn check_content_block(block: &Value) {
+    let block_type = block["type"].as_str().unwrap_or_default();
+    let allowed: &[&str] = match block_type {
+        "text" => &["type", "text"],
+        "thinking" => &["type", "thinking", "signature"],
+        "tool_use" => &["type", "id", "name", "input"],
+        "tool_result" => &["type", "tool_use_id", "content", "is_error"],
+        "image" => &["type", "source"],
+        other => panic!("unexpected content block type '{}'", other),
+    };
+    assert_keys("content block", block, allowed);
+    if block_type == "image" {
+        assert_one_of("image source", &block["source"]["type"], &["base64", "url"]);
+    }
+}
+
+fn check_message(message: &Value) {
+    let message_type = message["type"].as_str().unwrap_or_default();
+    match message_type {
+        "assistant" => {
+            assert_keys(
+                "assistant",
+                message,
+                &[
+                    "type",
+                    "message",
+                    "parent_tool_use_id",
+                    "session_id",
+                    "uuid",
+                ],
+            );
+            assert_keys(
+                "assistant.message",
+                &message["message"],
+                &["content", "model", "id", "stop_reason", "usage", "error"],
+            );
+            message["message"]["content"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .for_each(check_content_block);
+        }
+        "system" => {
+            let known = [
+                "type",
+                "subtype",
+                "cwd",
+                "session_id",
+                "tools",
+                "mcp_servers",
+                "model",
+                "permissionMode",
+                "uuid",
+            ];
+            for key in message.as_object().unwrap().keys() {
+                assert!(
+                    known.contains(&key.as_str()) || is_extra(key),
+                    "system: '{}'",
+                    key
+                );
+            }
+            if !message["permissionMode"].is_null() {
+                assert_one_of(
+                    "system.permissionMode",
+                    &message["permissionMode"],
+                    PERMISSION_MODES,
+                );
+            }
+        }
+        "result" => assert_keys(
+            "result",
+            message,
+            &[
+                "type",
+                "subtype",
+                "duration_ms",
+                "duration_api_ms",
+                "is_error",
+                "num_turns",
+                "session_id",
+                "total_cost_usd",
+                "usage",
+                "result",
+                "structured_output",
+            ],
+        ),
+        "stream_event" => assert_keys(
+            "stream_event",
+            message,
+            &["type", "uuid", "session_id", "event", "parent_tool_use_id"],
+        ),
+        "user" => {
+            let known = ["type", "text", "content", "uuid", "parent_tool_use_id"];
+            for key in message.as_object().unwrap().keys() {
+                assert!(
+                    known.contains(&key.as_str()) || is_extra(key),
+                    "user: '{}'",
+                    key
+                );
+            }
+        }
+        "control_cancel_request" => {
+            assert_keys("control_cancel_request", message, &["type", "request_id"])
+        }
+        other => panic!("unexpected message type '{}'", other),
+    }
+}
+
+fn check_hook_input(input: &Value) {
+    assert_one_of(
+        "hook_event_name",
+        &input["hook_event_name"],
+        &[
+            "PreToolUse",
+            "PostToolUse",
+            "UserPromptSubmit",
+            "Stop",
+            "SubagentStop",
+            "PreCompact",
+        ],
+    );
+    assert_keys(
+        "hook input",
+        input,
+        &[
+            "hook_event_name",
+            "session_id",
+            "transcript_path",
+            "cwd",
+            "permission_mode",
+            "tool_name",
+            "tool_input",
+            "tool_response",
+            "prompt",
+            "stop_hook_active",
+            "trigger",
+            "custom_instructions",
+        ],
+    );
+}
+
+fn check_hook_output(output: &Value) {
+    if output.get("async").is_some() {
+        assert_keys("async hook output", output, &["async", "asyncTimeout"]);
+        return;
+    }
+    assert_keys(
+        "hook output",
+        output,
+        &[
+            "continue",
+            "suppressOutput",
+            "stopReason",
+            "decision",
+            "systemMessage",
+            "reason",
+            "hookSpecificOutput",
+        ],
+    );
+    if let Some(specific) = output.get("hookSpecificOutput") {
+        assert_one_of(
+            "hookEventName",
+            &specific["hookEventName"],
+            &["PreToolUse", "PostToolUse", "UserPromptSubmit"],
+        );
+        assert_keys(
+            "hookSpecificOutput",
+            specific,
+            &[
+                "hookEventName",
+                "permissionDecision",
+                "permissionDecisionReason",
+                "updatedInput",
+                "additionalContext",
+            ],
+        );
+    }
+}
+
+fn check_permission_update(update: &Value) {
+    assert_keys(
+        "permission update",
+        update,
+        &[
+            "type",
+            "rules",
+            "behavior",
+            "mode",
+            "directories",
+            "destination",
+        ],
+    );
+    assert_one_of(
+        "permission update type",
+        &update["type"],
+        &[
+            "addRules",
+            "replaceRules",
+            "removeRules",
+            "setMode",
+            "addDirectories",
+            "removeDirectories",
+        ],
+    );
+    if let Some(rules) = update["rules"].as_array() {
+        for rule in rules {
+            assert_keys("permission rule", rule, &["toolName", "ruleContent"]);
+        }
+    }
+    if !update["behavior"].is_null() {
+        assert_one_of("behavior", &update["behavior"], &["allow", "deny", "ask"]);
+    }
+    if !update["mode"].is_null() {
+        assert_one_of("mode", &update["mode"], PERMISSION_MODES);
+    }
+    if !update["destination"].is_null() {
+        assert_one_of(
+            "destination",
+            &update["destination"],
+            &[
+                "userSettings",
+                "projectSettings",
+                "localSettings",
+                "session",
+            ],
+        );
+    }
+}
+
+fn check_sandbox(settings: &Value) {
+    assert_keys(
+        "sandbox",
+        settings,
+        &[
+            "enabled",
+            "autoAllowBashIfSandboxed",
+            "excludedCommands",
+            "allowUnsandboxedCommands",
+            "network",
+            "ignoreViolations",
+            "enableWeakerNestedSandbox",
+        ],
+    );
+    if let Some(network) = settings.get("network") {
+        assert_keys(
+            "sandbox.network",
+            network,
+            &[
+                "allowUnixSockets",
+                "allowAllUnixSockets",
+                "allowLocalBinding",
+                "httpProxyPort",
+                "socksProxyPort",
+            ],
+        );
+    }
+    if let Some(ignore) = settings.get("ignoreViolations") {
+        assert_keys("sandbox.ignoreViolations", ignore, &["file", "network"]);
+    }
+}
+
+proptest! {
+    #[test]
+    fn message_round_trip(message in any::<Message>()) {
+        let (original, parsed) = round_trip(&message);
+        prop_assert_eq!(&original, &parsed);
+        check_message(&original);
+    }
+
+    #[test]
+    fn content_block_round_trip(block in any::<ContentBlock>()) {
+        let (original, parsed) = round_trip(&block);
+        prop_assert_eq!(&original, &parsed);
+        check_content_block(&original);
+    }
+
+    #[test]
+    fn hook_input_round_trip(input in any::<HookInput>()) {
+        let (original, parsed) = round_trip(&input);
+        prop_assert_eq!(&original, &parsed);
+        check_hook_input(&original);
+    }
+
+    #[test]
+    fn hook_output_round_trip(output in any::<HookJsonOutput>()) {
+        let (original, parsed) = round_trip(&output);
+        prop_assert_eq!(&original, &parsed);
+        check_hook_output(&original);
+    }
+
+    #[test]
+    fn permission_update_round_trip(update in any::<PermissionUpdate>()) {
+        let (original, parsed) = round_trip(&update);
+        prop_assert_eq!(&original, &parsed);
+        check_permission_update(&original);
+    }
+
+    #[test]
+    fn sandbox_settings_round_trip(settings in any::<SandboxSettings>()) {
+        let (original, parsed) = round_trip(&settings);
+        prop_assert_eq!(&original, &parsed);
+        check_sandbox(&original);
+    }
+}
@@ -0,0 +1,214 @@
+#!/usr/bin/env python3
+"""Generate protocol conformance goldens from the vendored Python SDK.
+
+For every case in fixtures/conformance/, this script rebuilds the Python
+ClaudeAgentOptions from the "options" object and records what the Python SDK
+would send to the CLI:
+
+- "args": the command line from SubprocessCLITransport, without the CLI path
+- "env": environment entries the SDK adds on top of os.environ, excluding the
+  SDK identity variables (CLAUDE_CODE_ENTRYPOINT, CLAUDE_AGENT_SDK_VERSION)
+- "initialize": the initialize control request written by Query
+
+The "expected" section of each file is overwritten; "name", "description" and
+"options" are kept. Run from the repository root after
+`make update-submodule`:
+
+    uv run --project tools python tools/generate_conformance_goldens.py
+"""
+
+import asyncio
+import json
+import os
+import sys
+from pathlib import Path
+
+ROOT = Path(__file__).parent.parent
+sys.path.insert(0, str(ROOT / "vendors" / "claude-agent-sdk-python" / "src"))
+
+from claude_agent_sdk import (  # noqa: E402
+    AgentDefinition,
+    ClaudeAgentOptions,
+    HookMatcher,
+    create_sdk_mcp_server,
+)
+from claude_agent_sdk._internal import query as query_module  # noqa: E402
+from claude_agent_sdk._internal.transport import Transport  # noqa: E402
+from claude_agent_sdk._internal.transport import subprocess_cli  # noqa: E402
+
+FIXTURES_DIR = ROOT / "fixtures" / "conformance"
+SDK_IDENTITY_ENV = {"CLAUDE_CODE_ENTRYPOINT", "CLAUDE_AGENT_SDK_VERSION"}
+
+
+def save_fixture(path: Path, data: dict):
+    """Save a golden back to the fixtures directory."""
+    with open(path, "w") as f:
+        json.dump(data, f, indent=2)
+        f.write("\n")
+    print(f"✓ Saved {path.name}")
+
+
+async def noop_hook(input_data, tool_use_id, context):
+    return {}
+
+
+def build_options(options: dict) -> ClaudeAgentOptions:
+    """Turn a golden "options" object into ClaudeAgentOptions."""
+    kwargs = dict(options)
+
+    if "agents" in kwargs:
+        kwargs["agents"] = {
+            name: AgentDefinition(**agent) for name, agent in kwargs["agents"].items()
+        }
+
+    if "mcp_servers" in kwargs:
+        servers = {}
+        for name, config in kwargs["mcp_servers"].items():
+            if config.get("type") == "sdk":
+                server = create_sdk_mcp_server(name=config["name"], tools=[])
+                servers[name] = server
+            else:
+                servers[name] = config
+        kwargs["mcp_servers"] = servers
+
+    if "hooks" in kwargs:
+        kwargs["hooks"] = {
+            event: [
+                HookMatcher(
+                    matcher=m.get("matcher"),
+                    hooks=[noop_hook] * m["hooks"],
+                    timeout=m.get("timeout"),
+                )
+                for m in matchers
+            ]
+            for event, matchers in kwargs["hooks"].items()
+        }
+
+    return ClaudeAgentOptions(**kwargs)
+
+
+class _Captured(Exception):
+    """Raised from the patched open_process once the command is recorded."""
+
+
+async def capture_process(options: ClaudeAgentOptions) -> tuple[list[str], dict]:
+    """Capture the CLI command and environment without spawning anything."""
+    captured = {}
+
+    async def fake_open_process(cmd, **kwargs):
+        captured["cmd"] = list(cmd)
+        captured["env"] = dict(kwargs.get("env") or {})
+        raise _Captured
+
+    async def empty_stream():
+        return
+        yield  # pragma: no cover
+
+    original = subprocess_cli.anyio.open_process
+    subprocess_cli.anyio.open_process = fake_open_process
+    os.environ["CLAUDE_AGENT_SDK_SKIP_VERSION_CHECK"] = "1"
+    try:
+        transport = subprocess_cli.SubprocessCLITransport(
+            prompt=empty_stream(),
+            options=ClaudeAgentOptions(**{**options.__dict__, "cli_path": "claude"}),
+        )
+        try:
+            await transport.connect()
+        except Exception:
+            # connect() may wrap the sentinel in a CLIConnectionError
+            if "cmd" not in captured:
+                raise
+    finally:
+        subprocess_cli.anyio.open_process = original
+
+    env = {
+        key: value
+        for key, value in captured["env"].items()
+        if key not in SDK_IDENTITY_ENV and os.environ.get(key) != value
+    }
+    return captured["cmd"][1:], env
+
+
+class RecordingTransport(Transport):
+    """Transport that records writes and answers the initialize request."""
+
+    def __init__(self):
+        self.writes = []
+        self.incoming: asyncio.Queue = asyncio.Queue()
+
+    async def connect(self):
+        pass
+
+    async def write(self, data: str):
+        message = json.loads(data)
+        self.writes.append(message)
+        if message.get("type") == "control_request":
+            await self.incoming.put(
+                {
+                    "type": "control_response",
+                    "response": {
+                        "subtype": "success",
+                        "request_id": message["request_id"],
+                        "response": {},
+                    },
+                }
+            )
+
+    async def read_messages(self):
+        while True:
+            yield await self.incoming.get()
+
+    async def close(self):
+        pass
+
+    def is_ready(self) -> bool:
+        return True
+
+    async def end_input(self):
+        pass
+
+
+async def capture_initialize(options: ClaudeAgentOptions) -> dict:
+    """Capture the initialize request Query sends for these options."""
+    from claude_agent_sdk import ClaudeSDKClient
+
+    hooks = None
+    if options.hooks:
+        client = ClaudeSDKClient(options)
+        hooks = client._convert_hooks_to_internal_format(options.hooks)
+    transport = RecordingTransport()
+    query = query_module.Query(
+        transport=transport,
+        is_streaming_mode=True,
+        can_use_tool=None,
+        hooks=hooks,
+        sdk_mcp_servers={},
+    )
+    await query.start()
+    await query.initialize()
+    await query.close()
+    return next(
+        w["request"] for w in transport.writes if w["request"]["subtype"] == "initialize"
+    )
+
+
+async def generate(path: Path):
+    golden = json.loads(path.read_text())
+    options = build_options(golden["options"])
+    args, env = await capture_process(options)
+    golden["expected"] = {
+        "args": args,
+        "env": env,
+        "initialize": await capture_initialize(options),
+    }
+    save_fixture(path, golden)
+
+
+async def main():
+    print("\n=== Generating conformance goldens ===")
+    for path in sorted(FIXTURES_DIR.glob("*.json")):
+        await generate(path)
+
+
+if __name__ == "__main__":
+    asyncio.run(main())